@@ -26,6 +26,7 @@ extern crate rustc_target;
 extern crate rustc_type_ir;
 
 mod exporter;
+mod inclusion;
 
 use std::collections::HashSet;
 
@@ -125,6 +126,17 @@ fn main() {
         Box::new(exporter::ExtractionCallbacks {
             inline_macro_calls: options.inline_macro_calls.clone(),
             body_types: options.command.body_kinds(),
+            mono_entry_points: options.command.mono_entry_points(),
+            borrowck_facts: options.command.borrowck_facts(),
+            layouts: options.command.layouts(),
+            // Inclusion clauses only select items of the crate being
+            // translated: dependencies are used as context only.
+            include_namespaces: match &options.command {
+                Command::Backend(backend) if is_primary_package => {
+                    backend.translation_options.include_namespaces.clone()
+                }
+                _ => vec![],
+            },
             macro_calls: std::collections::HashMap::new(),
         })
     } else {
@@ -154,6 +166,11 @@ fn main() {
                 Command::Backend(BackendOptions { backend, .. }) => {
                     vec!["--cfg".into(), format!("hax_backend_{backend}")]
                 }
+                Command::Lint {
+                    backend: Some(backend),
+                } => {
+                    vec!["--cfg".into(), format!("hax_backend_{backend}")]
+                }
                 _ => vec![],
             })
             .chain(features.into_iter().map(|s| format!("-Zcrate-attr={}", s)))
@@ -44,9 +44,10 @@ fn dummy_thir_body(
 
 /// Precompute all THIR bodies in a certain order so that we avoid
 /// stealing issues (theoretically...)
-fn precompute_local_thir_bodies(
-    tcx: TyCtxt<'_>,
-) -> impl Iterator<Item = (rustc_hir::def_id::DefId, ThirBundle<'_>)> {
+fn precompute_local_thir_bodies<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    include: impl Fn(rustc_span::def_id::LocalDefId) -> bool + 'tcx,
+) -> impl Iterator<Item = (rustc_hir::def_id::DefId, ThirBundle<'tcx>)> {
     use rustc_hir::def::DefKind::*;
     use rustc_hir::*;
 
@@ -89,6 +90,7 @@ fn precompute_local_thir_bodies(
                 _ => true
             }
         })
+        .filter(move |ldid| include(*ldid))
         .sorted_by_key(|ldid| const_level_of(tcx, *ldid))
         .filter(move |ldid| tcx.hir().maybe_body_owned_by(*ldid).is_some())
         .map(move |ldid| {
@@ -120,50 +122,94 @@ fn precompute_local_thir_bodies(
 }
 
 /// Browse a crate and translate every item from HIR+THIR to "THIR'"
-/// (I call "THIR'" the AST described in this crate)
+/// (I call "THIR'" the AST described in this crate). Items are
+/// written to `haxmeta` as soon as they are translated.
 #[tracing::instrument(skip_all)]
-fn convert_thir<'tcx, Body: hax_frontend_exporter::IsBody>(
+fn convert_thir<
+    'tcx,
+    Body: hax_frontend_exporter::IsBody + hax_frontend_exporter::visitors::Visitable + serde::Serialize,
+>(
     options: &hax_frontend_exporter_options::Options,
     macro_calls: HashMap<hax_frontend_exporter::Span, hax_frontend_exporter::Span>,
+    mono_entry_points: &[hax_types::cli_options::Namespace],
+    include_namespaces: &[hax_types::cli_options::InclusionClause],
     tcx: TyCtxt<'tcx>,
-) -> (
-    Vec<rustc_span::Span>,
-    Vec<hax_frontend_exporter::DefId>,
-    Vec<(
-        hax_frontend_exporter::DefId,
-        hax_frontend_exporter::ImplInfos,
-    )>,
-    Vec<hax_frontend_exporter::Item<Body>>,
-    hax_frontend_exporter::id_table::Table,
+    mut haxmeta: hax_types::driver_api::HaxMetaWriter<impl std::io::Write, Body>,
 ) {
+    use hax_frontend_exporter::visitors::Visitable;
     use hax_frontend_exporter::WithGlobalCacheExt;
     let mut state = hax_frontend_exporter::state::State::new(tcx, options.clone());
     state.base.macro_infos = Rc::new(macro_calls);
-    for (def_id, thir) in precompute_local_thir_bodies(tcx) {
+    let selection = Rc::new(crate::inclusion::Selection::new(tcx, include_namespaces));
+    state.base.dropped_bodies = Rc::new(selection.dropped_bodies.clone());
+    for (def_id, thir) in precompute_local_thir_bodies(tcx, {
+        let selection = selection.clone();
+        move |ldid| selection.includes_body(tcx, ldid)
+    }) {
         state.with_item_cache(def_id, |caches| caches.thir = Some(thir));
     }
+    let new_entries = || state.with_global_cache(|cache| cache.id_table_session.new_entries());
 
-    let result = hax_frontend_exporter::inline_macro_invocations(tcx.hir().items(), &state);
+    let mut comment_targets = hax_frontend_exporter::comments::CommentTargets::default();
+    let mut files = HashSet::new();
+    let mut diagnostics = vec![];
+    for item in hax_frontend_exporter::inline_macro_invocations(
+        tcx.hir()
+            .items()
+            .filter(|id| selection.includes(id.owner_id.def_id)),
+        &state,
+    ) {
+        comment_targets.add(&item);
+        files.extend(item.span.filename.to_path().map(|path| path.to_path_buf()));
+        // Items that failed to be translated are exported as
+        // `ItemKind::Error` placeholders: report them.
+        item.for_each(|item: &hax_frontend_exporter::Item<Body>| {
+            if let hax_frontend_exporter::ItemKind::Error(details) = &item.kind {
+                diagnostics.push(hax_types::diagnostics::Diagnostics {
+                    kind: hax_types::diagnostics::Kind::AssertionFailure {
+                        details: details.clone(),
+                    },
+                    span: vec![item.span.clone()],
+                    context: "Frontend".into(),
+                    owner_id: Some(item.owner_id.clone()),
+                })
+            }
+        });
+        haxmeta.item(item, new_entries());
+    }
+
+    let mono_items = hax_frontend_exporter::mono_items(&state, mono_entry_points);
+    let adt_defs = if options.layouts {
+        hax_frontend_exporter::adt_defs(&state)
+    } else {
+        vec![]
+    };
     let impl_infos = hax_frontend_exporter::impl_def_ids_to_impled_types_and_bounds(&state)
         .into_iter()
         .collect();
-    let exported_spans = state.with_global_cache(|cache| cache.spans.keys().copied().collect());
-    let exported_def_ids = state.with_global_cache(|cache| {
+    let def_ids = state.with_global_cache(|cache| {
         cache
             .per_item
             .values()
             .filter_map(|per_item_cache| per_item_cache.def_id.clone())
             .collect()
     });
-    let cache_map = state.with_global_cache(|cache| cache.id_table_session.table().clone());
-
-    (
-        exported_spans,
-        exported_def_ids,
+    let comments: Vec<_> = files
+        .into_iter()
+        .flat_map(|path| hax_frontend_exporter::comments::comments_of_file(path).ok())
+        .flatten()
+        .collect();
+    let item_comments = comment_targets.attach(&comments);
+    let footer = hax_types::driver_api::HaxMetaFooter {
         impl_infos,
-        result,
-        cache_map,
-    )
+        def_ids,
+        comments,
+        item_comments,
+        mono_items,
+        adt_defs,
+        diagnostics,
+    };
+    haxmeta.finish(footer, new_entries());
 }
 
 /// Collect a map from spans to macro calls
@@ -193,12 +239,19 @@ pub(crate) struct ExtractionCallbacks {
     pub inline_macro_calls: Vec<hax_types::cli_options::Namespace>,
     pub macro_calls: HashMap<hax_frontend_exporter::Span, hax_frontend_exporter::Span>,
     pub body_types: Vec<hax_types::cli_options::ExportBodyKind>,
+    pub mono_entry_points: Vec<hax_types::cli_options::Namespace>,
+    pub borrowck_facts: bool,
+    pub layouts: bool,
+    /// The inclusion clauses to apply to the items of the crate.
+    pub include_namespaces: Vec<hax_types::cli_options::InclusionClause>,
 }
 
 impl From<ExtractionCallbacks> for hax_frontend_exporter_options::Options {
     fn from(opts: ExtractionCallbacks) -> hax_frontend_exporter_options::Options {
         hax_frontend_exporter_options::Options {
             inline_macro_calls: opts.inline_macro_calls,
+            borrowck_facts: opts.borrowck_facts,
+            layouts: opts.layouts,
         }
     }
 }
@@ -253,7 +306,13 @@ impl Callbacks for ExtractionCallbacks {
                         .into_iter(),
                     _ => vec![].into_iter(),
                 })
-                .map(|path| path.with_extension("haxmeta"))
+                // The driver writes `{crate_name}-{cg_metadata}.haxmeta`
+                // next to `lib{crate_name}-{cg_metadata}.{rlib,so,...}`
+                .map(|path| {
+                    let file_name = path.with_extension("haxmeta");
+                    let file_name = file_name.file_name().unwrap().to_string_lossy();
+                    path.with_file_name(file_name.strip_prefix("lib").unwrap_or(&file_name))
+                })
                 .collect();
 
             let cg_metadata = opts.cg.metadata[0].clone();
@@ -262,32 +321,19 @@ impl Callbacks for ExtractionCallbacks {
             let output_dir = compiler.sess.io.output_dir.clone().unwrap();
             let haxmeta_path = output_dir.join(format!("{crate_name}-{cg_metadata}.haxmeta",));
 
-            let mut file = BufWriter::new(File::create(&haxmeta_path).unwrap());
+            let file = BufWriter::new(File::create(&haxmeta_path).unwrap());
 
-            use hax_types::driver_api::{with_kind_type, HaxMeta};
+            use hax_types::driver_api::{with_kind_type, HaxMetaHeader, HaxMetaWriter};
             with_kind_type!(
                 self.body_types.clone(),
                 <Body>|| {
-                    let (spans, def_ids, impl_infos, items, cache_map) =
-                        convert_thir(&self.clone().into(), self.macro_calls.clone(), tcx);
-                    let files: HashSet<PathBuf> = HashSet::from_iter(
-                        items
-                            .iter()
-                            .flat_map(|item| item.span.filename.to_path().map(|path| path.to_path_buf()))
-                    );
-                    let haxmeta: HaxMeta<Body> = HaxMeta {
+                    let header = HaxMetaHeader {
                         crate_name,
                         cg_metadata,
                         externs,
-                        impl_infos,
-                        items,
-                        comments: files.into_iter()
-                            .flat_map(|path|hax_frontend_exporter::comments::comments_of_file(path).ok())
-                            .flatten()
-                            .collect(),
-                        def_ids,
                     };
-                    haxmeta.write(&mut file, cache_map);
+                    let haxmeta: HaxMetaWriter<_, Body> = HaxMetaWriter::new(file, &header);
+                    convert_thir(&self.clone().into(), self.macro_calls.clone(), &self.mono_entry_points, &self.include_namespaces, tcx, haxmeta);
                 }
             );
 
@@ -0,0 +1,569 @@
+//! Evaluation of the inclusion clauses of `cargo hax into` (see
+//! [`TranslationOptions::include_namespaces`]) in the driver, so that
+//! the items the engine would discard are not translated at all.
+//!
+//! This mirrors `filter_by_inclusion_clauses` in the engine
+//! (`engine/lib/dependencies.ml`, see [`DependencyGraph::select`]),
+//! on an over-approximation of the dependency graph the engine
+//! computes. The engine applies the
+//! clauses again on the items we export: exporting an item it then
+//! discards is harmless, while missing one of its dependencies is not.
+//!
+//! [`TranslationOptions::include_namespaces`]: hax_types::cli_options::TranslationOptions::include_namespaces
+
+use hax_lib_macros_types::AttrPayload;
+use hax_types::cli_options::{DepsKind, InclusionClause, InclusionKind};
+use rustc_hir::def::Res;
+use rustc_hir::def_id::{DefId, LocalDefId, LOCAL_CRATE};
+use rustc_hir::definitions::DefPathData;
+use rustc_hir::intravisit::{self, Visitor};
+use rustc_hir::{AssocItemKind, ItemKind};
+use rustc_middle::hir::nested_filter;
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_span::symbol::Symbol;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// The items of the crate to translate.
+pub(crate) struct Selection {
+    /// The selected items, or `None` when every item is selected.
+    items: Option<HashSet<LocalDefId>>,
+    /// The definitions whose body should not be translated: they
+    /// belong to items selected by a `+:` clause.
+    pub(crate) dropped_bodies: HashSet<LocalDefId>,
+}
+
+impl Selection {
+    pub(crate) fn new(tcx: TyCtxt<'_>, clauses: &[InclusionClause]) -> Self {
+        let items: Vec<LocalDefId> = tcx.hir().items().map(|id| id.owner_id.def_id).collect();
+        let paths: HashMap<LocalDefId, Vec<Option<String>>> = items
+            .iter()
+            .map(|&item| (item, engine_path(tcx, item)))
+            .collect();
+
+        // The selection starts with every item: it can only shrink if
+        // some clause excludes items.
+        let excludes = clauses
+            .iter()
+            .any(|clause| matches!(clause.kind, InclusionKind::Excluded));
+        let selection = excludes.then(|| {
+            let graph = DependencyGraph::new(tcx, &items);
+            let clauses: Vec<_> = clauses
+                .iter()
+                .map(|clause| {
+                    let matches =
+                        |item: &LocalDefId| clause.namespace.matches_def_path(&paths[item]);
+                    (
+                        &clause.kind,
+                        items.iter().copied().filter(matches).collect(),
+                    )
+                })
+                .collect();
+            graph.select(&items, &clauses)
+        });
+
+        // Like the engine, the body of an item is dropped when the
+        // last clause matching it is a `+:` clause.
+        let dropped_bodies = items
+            .iter()
+            .copied()
+            .filter(|item| {
+                clauses
+                    .iter()
+                    .rev()
+                    .find(|clause| clause.namespace.matches_def_path(&paths[item]))
+                    .is_some_and(|clause| matches!(clause.kind, InclusionKind::SignatureOnly))
+            })
+            .filter(|item| {
+                !payloads(tcx, *item).any(|payload| matches!(payload, AttrPayload::NeverErased))
+            })
+            .flat_map(|item| signature_only_bodies(tcx, item))
+            .collect();
+
+        Selection {
+            items: selection,
+            dropped_bodies,
+        }
+    }
+
+    /// Whether the item `item` should be translated.
+    pub(crate) fn includes(&self, item: LocalDefId) -> bool {
+        self.items
+            .as_ref()
+            .map_or(true, |items| items.contains(&item))
+    }
+
+    /// Whether the body of the body owner `owner` belongs to an item
+    /// that should be translated.
+    pub(crate) fn includes_body(&self, tcx: TyCtxt<'_>, owner: LocalDefId) -> bool {
+        match &self.items {
+            None => true,
+            Some(items) => enclosing_item(tcx, items, owner).is_some(),
+        }
+    }
+}
+
+/// The path of `item` as the engine matches it against the namespaces
+/// of inclusion clauses (`Concrete_ident.matches_namespace`): the
+/// crate name, then the chunks of the path of the exported `DefId`.
+/// Chunks the engine has no name for are `None`: they are only
+/// matched by globs.
+fn engine_path(tcx: TyCtxt<'_>, item: LocalDefId) -> Vec<Option<String>> {
+    std::iter::once(Some(tcx.crate_name(LOCAL_CRATE).to_ident_string()))
+        .chain(
+            tcx.def_path(item.to_def_id())
+                .data
+                .iter()
+                .map(|item| match item.data {
+                    DefPathData::TypeNs(s)
+                    | DefPathData::ValueNs(s)
+                    | DefPathData::MacroNs(s)
+                    | DefPathData::LifetimeNs(s) => Some(s.to_ident_string()),
+                    DefPathData::Impl => Some("impl".into()),
+                    DefPathData::AnonConst => Some("anon_const".into()),
+                    _ => None,
+                }),
+        )
+        .collect()
+}
+
+/// The payloads of the hax attributes (`#[_hax::json(...)]`) of `did`.
+fn payloads(tcx: TyCtxt<'_>, did: LocalDefId) -> impl Iterator<Item = AttrPayload> + '_ {
+    let path = [
+        Symbol::intern(hax_lib_macros_types::HAX_TOOL),
+        Symbol::intern("json"),
+    ];
+    tcx.get_attrs_unchecked(did.to_def_id())
+        .iter()
+        .filter(move |attr| attr.path_matches(&path))
+        .filter_map(|attr| {
+            let payload = attr.meta_item_list()?.first()?.lit()?.kind.str()?;
+            serde_json::from_str(payload.as_str()).ok()
+        })
+}
+
+/// The bodies the engine drops when importing `item` signature-only:
+/// those of functions and statics, and those of the methods of
+/// impls (unless the impl implements a trait with associated types).
+fn signature_only_bodies(tcx: TyCtxt<'_>, item: LocalDefId) -> Vec<LocalDefId> {
+    match &tcx.hir().expect_item(item).kind {
+        ItemKind::Fn(..) | ItemKind::Static(..) => vec![item],
+        ItemKind::Impl(impl_)
+            if impl_.of_trait.is_none()
+                || !impl_
+                    .items
+                    .iter()
+                    .any(|item| matches!(item.kind, AssocItemKind::Type)) =>
+        {
+            impl_
+                .items
+                .iter()
+                .filter(|item| matches!(item.kind, AssocItemKind::Fn { .. }))
+                .map(|item| item.id.owner_id.def_id)
+                .collect()
+        }
+        _ => vec![],
+    }
+}
+
+/// The item among `items` that contains the local definition `did`.
+fn enclosing_item(
+    tcx: TyCtxt<'_>,
+    items: &HashSet<LocalDefId>,
+    did: LocalDefId,
+) -> Option<LocalDefId> {
+    let mut did = did;
+    while !items.contains(&did) {
+        did = tcx.opt_local_parent(did)?;
+    }
+    Some(did)
+}
+
+/// An over-approximation of the dependency graph of the engine: an
+/// item depends on every local item it mentions, on the
+/// implementations of the traits and types it mentions, and on the
+/// items it is associated with via hax attributes.
+struct DependencyGraph<Item> {
+    dependencies: HashMap<Item, HashSet<Item>>,
+    /// The dependencies of the functions, without their bodies.
+    signature_dependencies: HashMap<Item, HashSet<Item>>,
+    associated_items: HashMap<Item, HashSet<Item>>,
+}
+
+impl DependencyGraph<LocalDefId> {
+    fn new(tcx: TyCtxt<'_>, items: &[LocalDefId]) -> Self {
+        let item_set: HashSet<LocalDefId> = items.iter().copied().collect();
+        let to_items = |dids: HashSet<DefId>| -> HashSet<LocalDefId> {
+            dids.into_iter()
+                .filter_map(|did| did.as_local())
+                .filter_map(|did| enclosing_item(tcx, &item_set, did))
+                .collect()
+        };
+
+        // The impls of each trait and type, which trait resolution
+        // might pick wherever the trait or the type is used.
+        let mut impls: HashMap<LocalDefId, HashSet<LocalDefId>> = HashMap::new();
+        for &item in items {
+            if !matches!(tcx.hir().expect_item(item).kind, ItemKind::Impl(_)) {
+                continue;
+            }
+            let mut collector = Collector::new(tcx);
+            collector.add_ty(tcx.type_of(item).instantiate_identity());
+            if let Some(trait_ref) = tcx.impl_trait_ref(item) {
+                let trait_ref = trait_ref.instantiate_identity();
+                collector.dependencies.insert(trait_ref.def_id);
+                trait_ref.args.types().for_each(|ty| collector.add_ty(ty));
+            }
+            for anchor in to_items(collector.dependencies) {
+                impls.entry(anchor).or_default().insert(item);
+            }
+        }
+
+        // Items with a `Uid` attribute, and items pointing to them via
+        // `AssociatedItem` attributes.
+        let mut uids = HashMap::new();
+        let mut associations: Vec<(LocalDefId, String)> = vec![];
+        for did in tcx.hir_crate_items(()).definitions() {
+            let Some(item) = enclosing_item(tcx, &item_set, did) else {
+                continue;
+            };
+            for payload in payloads(tcx, did) {
+                match payload {
+                    AttrPayload::Uid(uid) => {
+                        uids.insert(uid.uid, item);
+                    }
+                    AttrPayload::AssociatedItem { item: uid, .. } => {
+                        associations.push((item, uid.uid))
+                    }
+                    _ => (),
+                }
+            }
+        }
+        let mut associated_items: HashMap<LocalDefId, HashSet<LocalDefId>> = HashMap::new();
+        for (item, uid) in associations {
+            if let Some(associated) = uids.get(&uid) {
+                associated_items
+                    .entry(item)
+                    .or_default()
+                    .insert(*associated);
+            }
+        }
+
+        let dependencies_of = |item: LocalDefId, with_bodies: bool| {
+            let mut collector = Collector::new(tcx);
+            collector.skip_bodies = !with_bodies;
+            collector.visit_item(tcx.hir().expect_item(item));
+            let mentioned = to_items(collector.dependencies);
+            let mut dependencies: HashSet<LocalDefId> = mentioned
+                .iter()
+                .flat_map(|item| impls.get(item).into_iter().flatten())
+                .copied()
+                .collect();
+            dependencies.extend(mentioned);
+            dependencies.extend(associated_items.get(&item).into_iter().flatten());
+            dependencies
+        };
+        let dependencies = items
+            .iter()
+            .map(|&item| (item, dependencies_of(item, true)))
+            .collect();
+        // The engine only drops the bodies of functions.
+        let signature_dependencies = items
+            .iter()
+            .filter(|item| matches!(tcx.hir().expect_item(**item).kind, ItemKind::Fn(..)))
+            .map(|&item| (item, dependencies_of(item, false)))
+            .collect();
+
+        DependencyGraph {
+            dependencies,
+            signature_dependencies,
+            associated_items,
+        }
+    }
+}
+
+impl<Item: Copy + Eq + Hash> DependencyGraph<Item> {
+    /// The items in `roots` and their dependencies, transitively. The
+    /// bodies of the items in `without_body` are ignored.
+    fn transitive_dependencies_of(
+        &self,
+        roots: impl IntoIterator<Item = Item>,
+        without_body: &HashSet<Item>,
+    ) -> HashSet<Item> {
+        let mut visited = HashSet::new();
+        let mut todo: Vec<Item> = roots.into_iter().collect();
+        while let Some(item) = todo.pop() {
+            if !visited.insert(item) {
+                continue;
+            }
+            let dependencies = without_body
+                .contains(&item)
+                .then(|| self.signature_dependencies.get(&item))
+                .flatten()
+                .or_else(|| self.dependencies.get(&item));
+            todo.extend(dependencies.into_iter().flatten());
+        }
+        visited
+    }
+
+    fn associated_items(&self, item: Item) -> impl Iterator<Item = Item> + '_ {
+        self.associated_items
+            .get(&item)
+            .into_iter()
+            .flatten()
+            .copied()
+    }
+
+    /// One pass of `filter_by_inclusion_clauses'`: applies the clauses
+    /// (each given with the items its namespace matches) to the items
+    /// of `selection`. Returns the selected items, and the items whose
+    /// body the engine drops for the next pass: the dependencies of the
+    /// items matched by `+~` clauses.
+    fn apply_clauses(
+        &self,
+        selection: &HashSet<Item>,
+        clauses: &[(&InclusionKind, Vec<Item>)],
+        without_body: &HashSet<Item>,
+    ) -> (HashSet<Item>, HashSet<Item>) {
+        let mut result = selection.clone();
+        let mut drop_body = HashSet::new();
+        for (kind, matched) in clauses {
+            let matched0: HashSet<Item> = matched
+                .iter()
+                .copied()
+                .filter(|item| selection.contains(item))
+                .collect();
+            let matched = match kind {
+                InclusionKind::Included(DepsKind::Transitive | DepsKind::Shallow) => {
+                    self.transitive_dependencies_of(matched0.iter().copied(), without_body)
+                }
+                _ => matched0.clone(),
+            };
+            if matches!(
+                kind,
+                InclusionKind::Included(DepsKind::Shallow) | InclusionKind::SignatureOnly
+            ) {
+                drop_body.extend(matched.iter().copied());
+                drop_body.retain(|item| !matched0.contains(item));
+            }
+            match kind {
+                InclusionKind::Excluded => result.retain(|item| !matched.contains(item)),
+                _ => result.extend(matched),
+            }
+        }
+        (result, drop_body)
+    }
+
+    /// The items the engine selects (`filter_by_inclusion_clauses`),
+    /// given the items each clause matches. The clauses are applied a
+    /// first time; then, on the selected items, a second time ignoring
+    /// the bodies of the dependencies of `+~` clauses: their own
+    /// dependencies are only the ones of their signatures. The second
+    /// pass only keeps items of the first one. Last, like
+    /// the engine, the items associated with the selected ones are
+    /// added (e.g. pre- and post-conditions).
+    fn select(&self, items: &[Item], clauses: &[(&InclusionKind, Vec<Item>)]) -> HashSet<Item> {
+        let all = items.iter().copied().collect();
+        let (first, drop_body) = self.apply_clauses(&all, clauses, &HashSet::new());
+        let (mut selection, _) = self.apply_clauses(&first, clauses, &drop_body);
+        selection.retain(|item| first.contains(item));
+        let associated: Vec<Item> = selection
+            .iter()
+            .flat_map(|item| self.associated_items(*item))
+            .collect();
+        selection.extend(associated);
+        selection
+    }
+}
+
+/// Collects the definitions an item mentions: in paths, in the types
+/// of its expressions and patterns, and via method calls and
+/// overloaded operators.
+struct Collector<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    typeck_results: Option<&'tcx ty::TypeckResults<'tcx>>,
+    dependencies: HashSet<DefId>,
+    /// Whether the bodies (of functions, constants...) are skipped.
+    skip_bodies: bool,
+}
+
+impl<'tcx> Collector<'tcx> {
+    fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Collector {
+            tcx,
+            typeck_results: None,
+            dependencies: HashSet::new(),
+            skip_bodies: false,
+        }
+    }
+
+    fn add_ty(&mut self, ty: ty::Ty<'tcx>) {
+        for arg in ty.walk() {
+            match arg.unpack() {
+                ty::GenericArgKind::Type(ty) => match ty.kind() {
+                    ty::Adt(def, _) => {
+                        self.dependencies.insert(def.did());
+                    }
+                    ty::FnDef(did, _) | ty::Foreign(did) => {
+                        self.dependencies.insert(*did);
+                    }
+                    ty::Alias(_, alias) => {
+                        self.dependencies.insert(alias.def_id);
+                    }
+                    ty::Dynamic(predicates, ..) => {
+                        self.dependencies.extend(predicates.iter().map(|predicate| {
+                            match predicate.skip_binder() {
+                                ty::ExistentialPredicate::Trait(trait_ref) => trait_ref.def_id,
+                                ty::ExistentialPredicate::Projection(projection) => {
+                                    projection.def_id
+                                }
+                                ty::ExistentialPredicate::AutoTrait(did) => did,
+                            }
+                        }));
+                    }
+                    _ => (),
+                },
+                ty::GenericArgKind::Const(ct) => {
+                    if let ty::ConstKind::Unevaluated(uv) = ct.kind() {
+                        self.dependencies.insert(uv.def);
+                    }
+                }
+                ty::GenericArgKind::Lifetime(_) => (),
+            }
+        }
+    }
+
+    fn add_node(&mut self, hir_id: rustc_hir::HirId) {
+        let Some(results) = self.typeck_results else {
+            return;
+        };
+        if let Some(did) = results.type_dependent_def_id(hir_id) {
+            self.dependencies.insert(did);
+        }
+        if let Some(ty) = results.node_type_opt(hir_id) {
+            self.add_ty(ty);
+        }
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for Collector<'tcx> {
+    type NestedFilter = nested_filter::All;
+
+    fn nested_visit_map(&mut self) -> Self::Map {
+        self.tcx.hir()
+    }
+
+    /// Nested items are items on their own: the engine only sees
+    /// them via the paths that mention them.
+    fn visit_nested_item(&mut self, _id: rustc_hir::ItemId) {}
+
+    fn visit_nested_body(&mut self, id: rustc_hir::BodyId) {
+        if self.skip_bodies {
+            return;
+        }
+        let results = self.typeck_results.replace(self.tcx.typeck_body(id));
+        intravisit::walk_body(self, self.tcx.hir().body(id));
+        self.typeck_results = results;
+    }
+
+    fn visit_path(&mut self, path: &rustc_hir::Path<'tcx>, _id: rustc_hir::HirId) {
+        if let Res::Def(_, did) = path.res {
+            self.dependencies.insert(did);
+        }
+        intravisit::walk_path(self, path)
+    }
+
+    fn visit_qpath(
+        &mut self,
+        qpath: &'tcx rustc_hir::QPath<'tcx>,
+        id: rustc_hir::HirId,
+        span: rustc_span::Span,
+    ) {
+        if let Some(results) = self.typeck_results {
+            if let Res::Def(_, did) = results.qpath_res(qpath, id) {
+                self.dependencies.insert(did);
+            }
+        }
+        intravisit::walk_qpath(self, qpath, id)
+    }
+
+    fn visit_expr(&mut self, expr: &'tcx rustc_hir::Expr<'tcx>) {
+        self.add_node(expr.hir_id);
+        intravisit::walk_expr(self, expr)
+    }
+
+    fn visit_pat(&mut self, pat: &'tcx rustc_hir::Pat<'tcx>) {
+        self.add_node(pat.hir_id);
+        intravisit::walk_pat(self, pat)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hax_types::cli_options::Namespace;
+
+    /// Items `0..5`: `0` calls `1` in its body; `1` mentions `3` in
+    /// its signature and calls `2` in its body; `4` is unrelated and
+    /// associated with `0`.
+    fn graph() -> (Vec<u32>, DependencyGraph<u32>) {
+        let set = |items: &[u32]| items.iter().copied().collect::<HashSet<_>>();
+        let dependencies = HashMap::from([
+            (0, set(&[1])),
+            (1, set(&[2, 3])),
+            (2, set(&[])),
+            (3, set(&[])),
+            (4, set(&[])),
+        ]);
+        let signature_dependencies = HashMap::from([(0, set(&[])), (1, set(&[3]))]);
+        let associated_items = HashMap::from([(0, set(&[4]))]);
+        let graph = DependencyGraph {
+            dependencies,
+            signature_dependencies,
+            associated_items,
+        };
+        ((0..5).collect(), graph)
+    }
+
+    fn select(clauses: &[(InclusionKind, Vec<u32>)]) -> Vec<u32> {
+        let (items, graph) = graph();
+        let clauses: Vec<_> = clauses
+            .iter()
+            .map(|(kind, matched)| (kind, matched.clone()))
+            .collect();
+        let mut selection: Vec<_> = graph.select(&items, &clauses).into_iter().collect();
+        selection.sort();
+        selection
+    }
+
+    #[test]
+    fn transitive_and_shallow_inclusions() {
+        let all = || (InclusionKind::Excluded, (0..5).collect());
+        let transitive = InclusionKind::Included(DepsKind::Transitive);
+        assert_eq!(select(&[all(), (transitive, vec![0])]), vec![0, 1, 2, 3, 4]);
+        // The bodies of the dependencies of `0` are ignored: `2` is
+        // only called by the body of `1`.
+        let shallow = InclusionKind::Included(DepsKind::Shallow);
+        assert_eq!(select(&[all(), (shallow, vec![0])]), vec![0, 1, 3, 4]);
+        let none = InclusionKind::Included(DepsKind::None);
+        assert_eq!(select(&[all(), (none, vec![1])]), vec![1]);
+    }
+
+    #[test]
+    fn later_clauses_win() {
+        let transitive = InclusionKind::Included(DepsKind::Transitive);
+        assert_eq!(
+            select(&[(transitive, vec![0]), (InclusionKind::Excluded, vec![1])]),
+            vec![0, 2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn unnamed_chunks_only_match_globs() {
+        let namespace = |s: &str| Namespace::from(s.to_string());
+        let path = vec![Some("krate".to_string()), None, Some("f".to_string())];
+        assert!(namespace("krate::*::f").matches_def_path(&path));
+        assert!(namespace("krate::**").matches_def_path(&path));
+        assert!(!namespace("krate::f").matches_def_path(&path));
+    }
+}
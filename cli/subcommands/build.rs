@@ -19,6 +19,7 @@ fn json_schema_static_asset() {
         hax_types::cli_options::Options,
         hax_types::diagnostics::Diagnostics,
         hax_types::engine_api::EngineOptions,
+        hax_types::engine_api::EngineInput,
         hax_types::engine_api::Output,
         hax_types::engine_api::WithDefIds<hax_frontend_exporter::ThirBody>,
         hax_types::engine_api::protocol::FromEngine,
@@ -0,0 +1,293 @@
+//! `cargo hax json --call-graph`: walks the THIR or MIR bodies
+//! exported by the frontend and collects the calls they make, as
+//! caller → callee edges. Trait method calls are resolved to the
+//! method of the concrete implementation whenever this implementation
+//! is known and local.
+
+use hax_frontend_exporter::visitors::{Event, Visitable, Visitor};
+use hax_frontend_exporter::*;
+use hax_types::cli_options::CallGraphFormat;
+use serde::Serialize;
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
+
+/// How the callee of a call was determined.
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "kind")]
+pub enum CallKind {
+    /// A call to a function or an inherent method.
+    Direct,
+    /// A call to the trait method `trait_method`, resolved to the
+    /// method of a local implementation.
+    Resolved { trait_method: DefId },
+    /// A call to a trait method whose implementation is not known
+    /// statically (e.g. it comes from a bound), or is not local, or
+    /// inherits the default method of the trait. `impl` is the
+    /// implementation, when it is known.
+    Unresolved { r#impl: Option<DefId> },
+    /// A call through `dyn Trait` or a function pointer.
+    Dynamic,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CallEdge {
+    pub caller: DefId,
+    /// The called function, `None` for a function pointer.
+    pub callee: Option<DefId>,
+    #[serde(flatten)]
+    pub kind: CallKind,
+    pub span: Span,
+}
+
+/// The methods of the local implementations, indexed by their impl
+/// and their name.
+type ImplMethods = HashMap<(DefId, DefPathItem), DefId>;
+
+fn name(def_id: &DefId) -> Option<DefPathItem> {
+    def_id.path.last().map(|item| item.data.clone())
+}
+
+struct CallGraphBuilder<'a, Body> {
+    callers: Vec<DefId>,
+    impl_methods: &'a ImplMethods,
+    edges: Vec<CallEdge>,
+    body: PhantomData<Body>,
+}
+
+impl<Body: IsBody + Visitable> CallGraphBuilder<'_, Body> {
+    fn owner_id(node: &dyn Any) -> Option<&DefId> {
+        if let Some(item) = node.downcast_ref::<Item<Body>>() {
+            Some(&item.owner_id)
+        } else if let Some(item) = node.downcast_ref::<ImplItem<Body>>() {
+            Some(&item.owner_id)
+        } else if let Some(item) = node.downcast_ref::<TraitItem<Body>>() {
+            Some(&item.owner_id)
+        } else {
+            None
+        }
+    }
+
+    /// Resolves a call to `fun`, given the implementation of the trait
+    /// `fun` belongs to, if any.
+    fn resolve(&self, fun: &DefId, r#trait: Option<&ImplExpr>) -> (Option<DefId>, CallKind) {
+        let Some(impl_expr) = r#trait else {
+            return (Some(fun.clone()), CallKind::Direct);
+        };
+        let kind = match &impl_expr.r#impl {
+            ImplExprAtom::Concrete { id, .. } => {
+                let method = name(fun).and_then(|name| self.impl_methods.get(&(id.clone(), name)));
+                if let Some(method) = method {
+                    let kind = CallKind::Resolved {
+                        trait_method: fun.clone(),
+                    };
+                    return (Some(method.clone()), kind);
+                }
+                CallKind::Unresolved {
+                    r#impl: Some(id.clone()),
+                }
+            }
+            ImplExprAtom::Dyn => CallKind::Dynamic,
+            _ => CallKind::Unresolved { r#impl: None },
+        };
+        (Some(fun.clone()), kind)
+    }
+
+    /// The callee of `node` if it is a call, and the span of the call.
+    fn call(&self, node: &dyn Any) -> Option<((Option<DefId>, CallKind), Span)> {
+        if let Some(expr) = node.downcast_ref::<Expr>() {
+            let ExprKind::Call { fun, r#trait, .. } = expr.contents.as_ref() else {
+                return None;
+            };
+            let callee = match fun.contents.as_ref() {
+                ExprKind::GlobalName { id, .. } => {
+                    self.resolve(id, r#trait.as_ref().map(|(impl_expr, _)| impl_expr))
+                }
+                _ => (None, CallKind::Dynamic),
+            };
+            Some((callee, expr.span.clone()))
+        } else if let Some(terminator) = node.downcast_ref::<Terminator>() {
+            let TerminatorKind::Call {
+                fun,
+                trait_info,
+                fn_span,
+                ..
+            } = &terminator.kind
+            else {
+                return None;
+            };
+            let callee = match fun {
+                FunOperand::Id(id) => self.resolve(id, trait_info.as_ref()),
+                FunOperand::Move(_) => (None, CallKind::Dynamic),
+            };
+            Some((callee, fn_span.clone()))
+        } else {
+            None
+        }
+    }
+}
+
+impl<Body: IsBody + Visitable> Visitor for CallGraphBuilder<'_, Body> {
+    fn visit(&mut self, node: &dyn Any, event: Event) {
+        if let Some(owner_id) = Self::owner_id(node) {
+            match event {
+                Event::Enter => self.callers.push(owner_id.clone()),
+                Event::Exit => {
+                    self.callers.pop();
+                }
+            }
+        }
+        let Event::Enter = event else { return };
+        let Some(((callee, kind), span)) = self.call(node) else {
+            return;
+        };
+        let caller = self.callers.last().unwrap().clone();
+        self.edges.push(CallEdge {
+            caller,
+            callee,
+            kind,
+            span,
+        })
+    }
+}
+
+/// Collects the calls made by the bodies of `items`.
+pub fn call_graph<Body: IsBody + Visitable>(items: &[Item<Body>]) -> Vec<CallEdge> {
+    let mut impl_methods = HashMap::new();
+    for item in items {
+        if let ItemKind::Impl(Impl { items, .. }) = &item.kind {
+            for impl_item in items {
+                let owner_id = &impl_item.owner_id;
+                if let (Some(r#impl), Some(name)) = (&owner_id.parent, name(owner_id)) {
+                    impl_methods.insert((r#impl.clone(), name), owner_id.clone());
+                }
+            }
+        }
+    }
+    let mut builder = CallGraphBuilder::<Body> {
+        callers: vec![],
+        impl_methods: &impl_methods,
+        edges: vec![],
+        body: PhantomData,
+    };
+    for item in items {
+        item.drive(&mut builder);
+    }
+    builder.edges
+}
+
+/// Renders `def_id` as a Rust-like path, e.g. `krate::{impl#0}::f`.
+fn render(def_id: &DefId) -> String {
+    std::iter::once(def_id.krate.clone())
+        .chain(def_id.path.iter().map(|item| {
+            let d = item.disambiguator;
+            match &item.data {
+                DefPathItem::CrateRoot { name } => name.clone(),
+                DefPathItem::TypeNs(s)
+                | DefPathItem::ValueNs(s)
+                | DefPathItem::MacroNs(s)
+                | DefPathItem::LifetimeNs(s) => s.clone(),
+                DefPathItem::Impl => format!("{{impl#{d}}}"),
+                DefPathItem::Closure => format!("{{closure#{d}}}"),
+                other => format!("{{{other:?}#{d}}}"),
+            }
+        }))
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// Renders `edges` as a Graphviz graph. Dynamic calls are dashed,
+/// unresolved trait method calls are dotted. Several calls between
+/// the same functions are drawn as one edge.
+fn to_dot(edges: &[CallEdge]) -> String {
+    let mut lines = vec![];
+    let mut seen = HashSet::new();
+    for edge in edges {
+        let callee = match &edge.callee {
+            Some(callee) => render(callee),
+            None => "<dynamic>".into(),
+        };
+        let style = match edge.kind {
+            CallKind::Direct | CallKind::Resolved { .. } => "solid",
+            CallKind::Unresolved { .. } => "dotted",
+            CallKind::Dynamic => "dashed",
+        };
+        let line = format!(
+            "  {:?} -> {:?} [style={style}];\n",
+            render(&edge.caller),
+            callee
+        );
+        if seen.insert(line.clone()) {
+            lines.push(line)
+        }
+    }
+    format!("digraph call_graph {{\n{}}}\n", lines.concat())
+}
+
+/// Writes the call graph of `items` in `format` to `dest`.
+pub fn write<Body: IsBody + Visitable>(
+    items: &[Item<Body>],
+    format: CallGraphFormat,
+    mut dest: impl std::io::Write,
+) -> std::io::Result<()> {
+    let edges = call_graph(items);
+    match format {
+        CallGraphFormat::Json => serde_json::to_writer(&mut dest, &edges)?,
+        CallGraphFormat::Dot => dest.write_all(to_dot(&edges).as_bytes())?,
+    }
+    dest.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn def_id(index: u32, name: &str) -> DefId {
+        serde_json::from_value(serde_json::json!({
+            "contents": {
+                "id": index,
+                "value": {
+                    "krate": "krate",
+                    "path": [{"data": {"ValueNs": name}, "disambiguator": 0}],
+                    "parent": null,
+                    "index": [0, index],
+                    "is_local": true,
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    fn span() -> Span {
+        serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "value": {
+                "lo": {"line": 1, "col": 0},
+                "hi": {"line": 1, "col": 1},
+                "filename": {"Custom": "lib.rs"},
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn to_dot_merges_identical_edges() {
+        let (f, g) = (def_id(1, "f"), def_id(2, "g"));
+        let edge = |callee: Option<&DefId>, kind| CallEdge {
+            caller: f.clone(),
+            callee: callee.cloned(),
+            kind,
+            span: span(),
+        };
+        let edges = [
+            edge(Some(&g), CallKind::Direct),
+            edge(None, CallKind::Dynamic),
+            edge(Some(&g), CallKind::Direct),
+            edge(Some(&g), CallKind::Unresolved { r#impl: None }),
+        ];
+        assert_eq!(
+            to_dot(&edges),
+            "digraph call_graph {\n  \"krate::f\" -> \"krate::g\" [style=solid];\n  \"krate::f\" -> \"<dynamic>\" [style=dashed];\n  \"krate::f\" -> \"krate::g\" [style=dotted];\n}\n"
+        );
+    }
+}
@@ -14,8 +14,10 @@ use std::io::Write;
 use std::path::PathBuf;
 use std::process;
 
+mod call_graph;
 mod engine_debug_webapp;
-use hax_frontend_exporter::id_table;
+mod lint;
+use hax_frontend_exporter::{id_table, ThirBody};
 
 /// Return a toolchain argument to pass to `cargo`: when the correct nightly is
 /// already present, this is None, otherwise we (1) ensure `rustup` is available
@@ -219,6 +221,22 @@ impl HaxMessage {
                     "hax: running `cargo build` was not successful, continuing anyway.".to_string();
                 eprintln!("{}", renderer.render(Level::Warning.title(&title)));
             }
+            Self::HaxMetaReadFailure { path, reason } => {
+                let title = format!("hax: could not read {}: {}", path.display(), reason);
+                eprintln!("{}", renderer.render(Level::Error.title(&title)));
+            }
+            Self::OutputWriteFailure { reason } => {
+                let title = format!("hax: could not write the output: {}", reason);
+                eprintln!("{}", renderer.render(Level::Error.title(&title)));
+            }
+            Self::DependencyHaxMetaReadFailure { path, reason } => {
+                let title = format!(
+                    "hax: could not read {}: {}. The items of this dependency are ignored.",
+                    path.display(),
+                    reason
+                );
+                eprintln!("{}", renderer.render(Level::Warning.title(&title)));
+            }
             Self::WarnExperimentalBackend { backend } => {
                 let title = format!(
                     "hax: Experimental backend \"{}\" is work in progress.",
@@ -230,20 +248,116 @@ impl HaxMessage {
     }
 }
 
-/// Runs `hax-engine`
+/// Opens a `haxmeta` file, to be read chunk by chunk.
+fn open_haxmeta<Body: hax_frontend_exporter::IsBody>(
+    path: &std::path::Path,
+) -> Result<HaxMetaReader<fs::File, Body>, HaxMetaReadError>
+where
+    Body: for<'de> serde::Deserialize<'de>,
+{
+    HaxMetaReader::new(fs::File::open(path)?)
+}
+
+/// Serializes the results of an iterator as a sequence, consuming the
+/// iterator. The first error of the iterator fails the serialization.
+struct SerializeIter<I>(std::cell::RefCell<I>);
+
+impl<T, E, I> serde::Serialize for SerializeIter<I>
+where
+    T: serde::Serialize,
+    E: std::fmt::Display,
+    I: Iterator<Item = Result<T, E>>,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{Error, SerializeSeq};
+        let mut seq = serializer.serialize_seq(None)?;
+        for element in &mut *self.0.borrow_mut() {
+            seq.serialize_element(&element.map_err(S::Error::custom)?)?;
+        }
+        seq.end()
+    }
+}
+
+/// Runs `hax-engine` on the items of a `haxmeta` file, which are
+/// forwarded to the engine as it requests them. Reports the
+/// diagnostics of the frontend found in the `haxmeta` file.
 fn run_engine(
-    haxmeta: HaxMeta<hax_frontend_exporter::ThirBody>,
-    id_table: id_table::Table,
+    path: &std::path::Path,
     working_dir: PathBuf,
     manifest_dir: PathBuf,
     backend: &BackendOptions<()>,
     message_format: MessageFormat,
 ) -> bool {
+    let report_read_failure = |error: HaxMetaReadError| {
+        HaxMessage::HaxMetaReadFailure {
+            path: path.to_path_buf(),
+            reason: error.to_string(),
+        }
+        .report(message_format, None)
+    };
+    // The engine needs the impl infos of the footer before the items:
+    // the file is read a first time, dropping items as they are read,
+    // then items are read again as the engine requests them.
+    let first_pass = open_haxmeta::<ThirBody>(path).and_then(|haxmeta| {
+        let externs = haxmeta.header.externs.clone();
+        let (footer, table) = haxmeta.for_each_item(drop)?;
+        Ok((externs, footer, table))
+    });
+    let (externs, footer, mut table) = match first_pass {
+        Ok(first_pass) => first_pass,
+        Err(error) => {
+            report_read_failure(error);
+            return true;
+        }
+    };
+    let items = match open_haxmeta::<ThirBody>(path) {
+        Ok(haxmeta) => haxmeta.items(),
+        Err(error) => {
+            report_read_failure(error);
+            return true;
+        }
+    };
+    let mut error = report_frontend_diagnostics(footer.diagnostics, &working_dir, message_format);
+
+    // Dependencies that were not translated by hax have no `haxmeta`
+    // file and are skipped. Dependencies whose `haxmeta` file cannot be
+    // read are reported and skipped.
+    let dependencies_items = externs
+        .into_iter()
+        .filter(|path| path.exists())
+        .flat_map(|path| {
+            let (items, open_error) = match open_haxmeta::<ThirBody>(&path) {
+                Ok(haxmeta) => (Some(haxmeta.items()), None),
+                Err(error) => (None, Some(error)),
+            };
+            items
+                .into_iter()
+                .flatten()
+                .chain(open_error.map(Err))
+                .map_while(move |item| match item {
+                    Ok(item) => Some(item),
+                    Err(error) => {
+                        HaxMessage::DependencyHaxMetaReadFailure {
+                            path: path.clone(),
+                            reason: error.to_string(),
+                        }
+                        .report(message_format, None);
+                        None
+                    }
+                })
+        });
     let engine_options = EngineOptions {
         backend: backend.clone(),
-        input: haxmeta.items,
-        impl_infos: haxmeta.impl_infos,
+        impl_infos: footer.impl_infos,
     };
+    let mut inputs = std::iter::once(Ok(EngineInput::Options(engine_options)))
+        .chain(
+            dependencies_items
+                .map(|item| Ok(EngineInput::DependencyItems(id_table::Inlined(vec![item])))),
+        )
+        .chain(items.map(|item| item.map(|item| EngineInput::Items(vec![item]))))
+        .chain(std::iter::once(Ok(EngineInput::End)));
+
     let mut engine_subprocess = find_hax_engine(message_format)
         .stdin(std::process::Stdio::piped())
         .stdout(std::process::Stdio::piped())
@@ -258,7 +372,6 @@ fn run_engine(
         })
         .unwrap();
 
-    let mut error = false;
     let mut output = Output {
         diagnostics: vec![],
         files: vec![],
@@ -281,10 +394,6 @@ fn run_engine(
             };
         }
 
-        id_table::WithTable::run(id_table, engine_options, |with_table| {
-            send!(with_table);
-        });
-
         let out_dir = backend.output_dir.clone().unwrap_or({
             let relative_path: PathBuf = [
                 "proofs",
@@ -384,6 +493,23 @@ fn run_engine(
                 FromEngine::Ping => {
                     send!(&ToEngine::Pong);
                 }
+                FromEngine::RequestInput => match inputs.next() {
+                    // The first chunk, the options, ships the table of
+                    // the whole file.
+                    Some(Ok(input)) => {
+                        id_table::WithTable::run(std::mem::take(&mut table), input, |with_table| {
+                            send!(with_table);
+                        })
+                    }
+                    Some(Err(error)) => {
+                        report_read_failure(error);
+                        drop(stdin);
+                        let _ = engine_subprocess.kill();
+                        let _ = engine_subprocess.wait();
+                        return true;
+                    }
+                    None => panic!("The engine requested input after its end"),
+                },
             }
         }
         if backend.stats {
@@ -535,27 +661,105 @@ fn compute_haxmeta_files(options: &Options) -> (Vec<EmitHaxMetaMessage>, i32) {
     (haxmeta_files, exit_code)
 }
 
+/// Reports the diagnostics emitted by the frontend while exporting a
+/// `haxmeta` file. Returns `true` if there was any.
+fn report_frontend_diagnostics(
+    diagnostics: Vec<hax_types::diagnostics::Diagnostics>,
+    working_dir: &std::path::Path,
+    message_format: MessageFormat,
+) -> bool {
+    let mut rctx = ReportCtx::default();
+    let error = !diagnostics.is_empty();
+    for diagnostic in diagnostics {
+        HaxMessage::Diagnostic {
+            diagnostic,
+            working_dir: working_dir.to_path_buf(),
+        }
+        .report(message_format, Some(&mut rctx));
+    }
+    error
+}
+
 /// Run the command given by the user
 fn run_command(options: &Options, haxmeta_files: Vec<EmitHaxMetaMessage>) -> bool {
     match options.command.clone() {
         Command::JSON {
             output_file,
-            kind,
             include_extra,
             use_ids,
+            call_graph,
+            mono_entry_points,
+            layouts,
             ..
         } => {
+            let include_extra = include_extra || !mono_entry_points.is_empty() || layouts;
+            let kind = options.command.body_kinds();
+            let mut error = false;
             with_kind_type!(kind, <Body>|| {
-                for EmitHaxMetaMessage { path, .. } in haxmeta_files {
-                    let (haxmeta, id_table): (HaxMeta<Body>, _) = HaxMeta::read(fs::File::open(&path).unwrap());
+                for EmitHaxMetaMessage { working_dir, path, .. } in haxmeta_files {
+                    let report_read_failure = |error: HaxMetaReadError| {
+                        HaxMessage::HaxMetaReadFailure { path: path.clone(), reason: error.to_string() }
+                            .report(options.message_format, None)
+                    };
+                    // Fingerprints depend on every item, and the footer
+                    // comes after the items: the file is read a first
+                    // time, item by item, then items are written as
+                    // they are read again.
+                    let mut fingerprinter = hax_frontend_exporter::Fingerprinter::default();
+                    let mut call_graph_items = vec![];
+                    let first_pass = open_haxmeta::<Body>(&path).and_then(|haxmeta| {
+                        haxmeta.for_each_item(|item| {
+                            if call_graph.is_some() {
+                                call_graph_items.push(item)
+                            } else {
+                                fingerprinter.add(&item)
+                            }
+                        })
+                    });
+                    let (footer, id_table) = match first_pass {
+                        Ok(first_pass) => first_pass,
+                        Err(e) => {
+                            report_read_failure(e);
+                            error = true;
+                            continue;
+                        }
+                    };
+                    error |= report_frontend_diagnostics(footer.diagnostics, &working_dir, options.message_format);
                     let dest = output_file.open_or_stdout();
 
-                    (if include_extra {
+                    if let Some(format) = call_graph {
+                        if let Err(e) = call_graph::write(&call_graph_items, format, dest) {
+                            HaxMessage::OutputWriteFailure { reason: e.to_string() }
+                                .report(options.message_format, None);
+                            error = true;
+                        }
+                        continue;
+                    }
+
+                    let fingerprints = fingerprinter.finish();
+                    let items = match open_haxmeta::<Body>(&path) {
+                        Ok(haxmeta) => SerializeIter(std::cell::RefCell::new(haxmeta.items().map(|item| {
+                            item.map(|mut item| {
+                                item.set_fingerprints(&fingerprints);
+                                item
+                            })
+                        }))),
+                        Err(e) => {
+                            report_read_failure(e);
+                            error = true;
+                            continue;
+                        }
+                    };
+
+                    let result = if include_extra {
                         let data = WithDefIds {
-                            def_ids: haxmeta.def_ids,
-                            impl_infos: haxmeta.impl_infos,
-                            items: haxmeta.items,
-                            comments: haxmeta.comments,
+                            def_ids: footer.def_ids,
+                            impl_infos: footer.impl_infos,
+                            items,
+                            comments: footer.comments,
+                            item_comments: footer.item_comments,
+                            mono_items: footer.mono_items,
+                            adt_defs: footer.adt_defs,
                         };
                         if use_ids {
                             id_table::WithTable::run(id_table, data, |with_table| {
@@ -566,21 +770,75 @@ fn run_command(options: &Options, haxmeta_files: Vec<EmitHaxMetaMessage>) -> boo
                         }
                     } else {
                         if use_ids {
-                            id_table::WithTable::run(id_table, haxmeta.items, |with_table| {
+                            id_table::WithTable::run(id_table, items, |with_table| {
                                 serde_json::to_writer(dest, with_table)
                             })
                         } else {
-                            serde_json::to_writer(dest, &haxmeta.items)
+                            serde_json::to_writer(dest, &items)
                         }
-                    })
-                        .unwrap()
-
+                    };
+                    match result {
+                        // Errors that are not I/O errors come from
+                        // reading items.
+                        Err(e) if !e.is_io() => {
+                            HaxMessage::HaxMetaReadFailure { path, reason: e.to_string() }
+                                .report(options.message_format, None);
+                            error = true;
+                        }
+                        Err(e) => {
+                            HaxMessage::OutputWriteFailure { reason: e.to_string() }
+                                .report(options.message_format, None);
+                            error = true;
+                        }
+                        Ok(()) => (),
+                    }
                 }
             });
-            false
+            error
+        }
+        Command::Lint { backend } => {
+            let mut rctx = ReportCtx::default();
+            let mut error = false;
+            for EmitHaxMetaMessage {
+                working_dir, path, ..
+            } in haxmeta_files
+            {
+                let mut diagnostics = vec![];
+                let result = open_haxmeta::<ThirBody>(&path).and_then(|haxmeta| {
+                    haxmeta.for_each_item(|item| {
+                        diagnostics
+                            .extend(lint::lint(std::slice::from_ref(&item), backend.as_ref()))
+                    })
+                });
+                let footer = match result {
+                    Ok((footer, _)) => footer,
+                    Err(e) => {
+                        HaxMessage::HaxMetaReadFailure {
+                            path,
+                            reason: e.to_string(),
+                        }
+                        .report(options.message_format, None);
+                        error = true;
+                        continue;
+                    }
+                };
+                error |= report_frontend_diagnostics(
+                    footer.diagnostics,
+                    &working_dir,
+                    options.message_format,
+                );
+                for diagnostic in diagnostics {
+                    error = true;
+                    HaxMessage::Diagnostic {
+                        diagnostic,
+                        working_dir: working_dir.clone(),
+                    }
+                    .report(options.message_format, Some(&mut rctx));
+                }
+            }
+            error
         }
         Command::Backend(backend) => {
-            use hax_frontend_exporter::ThirBody as Body;
             use Backend;
 
             if matches!(backend.backend, Backend::Easycrypt | Backend::ProVerif(..)) {
@@ -597,18 +855,13 @@ fn run_command(options: &Options, haxmeta_files: Vec<EmitHaxMetaMessage>) -> boo
                 path,
             } in haxmeta_files
             {
-                let (haxmeta, id_table): (HaxMeta<Body>, _) =
-                    HaxMeta::read(fs::File::open(&path).unwrap());
-
-                error = error
-                    || run_engine(
-                        haxmeta,
-                        id_table,
-                        working_dir,
-                        manifest_dir,
-                        &backend,
-                        options.message_format,
-                    );
+                error |= run_engine(
+                    &path,
+                    working_dir,
+                    manifest_dir,
+                    &backend,
+                    options.message_format,
+                );
             }
             error
         }
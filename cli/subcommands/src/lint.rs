@@ -0,0 +1,180 @@
+//! `cargo hax lint`: walks the items exported by the frontend and
+//! reports the constructs hax cannot translate, before any backend
+//! runs.
+
+use hax_frontend_exporter::visitors::{Event, Visitable, Visitor};
+use hax_frontend_exporter::*;
+use hax_types::cli_options::Backend;
+use hax_types::diagnostics::{Diagnostics, Kind};
+use std::any::Any;
+use std::collections::HashSet;
+
+/// Whether `kind` is a problem for `backend`. When no backend is
+/// given, every construct some backend does not support is reported.
+fn relevant(kind: &Kind, backend: Option<&Backend<()>>) -> bool {
+    match (kind, backend) {
+        (Kind::DynTrait, Some(backend)) => !backend.supports_dyn(),
+        (Kind::UnsafeBlock, Some(backend)) => !backend.supports_unsafe(),
+        _ => true,
+    }
+}
+
+/// Collects diagnostics while keeping track of the innermost span
+/// enclosing the visited node: types, constants or trait
+/// resolutions have no span on their own.
+struct Linter<'a> {
+    spans: Vec<Span>,
+    owner_id: &'a DefId,
+    backend: Option<&'a Backend<()>>,
+    seen: HashSet<(u16, Option<Span>)>,
+    diagnostics: Vec<Diagnostics>,
+}
+
+impl Linter<'_> {
+    fn span_of(node: &dyn Any) -> Option<&Span> {
+        if let Some(e) = node.downcast_ref::<Expr>() {
+            Some(&e.span)
+        } else if let Some(p) = node.downcast_ref::<Pat>() {
+            Some(&p.span)
+        } else if let Some(c) = node.downcast_ref::<ConstantExpr>() {
+            Some(&c.span)
+        } else if let Some(b) = node.downcast_ref::<Block>() {
+            Some(&b.span)
+        } else {
+            None
+        }
+    }
+
+    /// The diagnostic kind `node` gives rise to, if any, and whether
+    /// `node` is located, i.e. has a span of its own. Unlocated nodes
+    /// (e.g. types) are reported once per item.
+    fn kind_of(node: &dyn Any) -> Option<(Kind, bool)> {
+        let placeholder = |node: &str, details: &str| Kind::FrontendPlaceholder {
+            node: node.into(),
+            details: details.into(),
+        };
+        if let Some(e) = node.downcast_ref::<ExprKind>() {
+            let kind = match e {
+                ExprKind::InlineAsm => Some(Kind::InlineAsm),
+                ExprKind::Todo(s) => Some(placeholder("expression", s)),
+                ExprKind::Yield { .. }
+                | ExprKind::Closure {
+                    movability: Some(_),
+                    ..
+                } => Some(Kind::Coroutine),
+                ExprKind::RawBorrow { .. } => Some(Kind::RawPointer),
+                _ => None,
+            };
+            kind.map(|kind| (kind, true))
+        } else if let Some(t) = node.downcast_ref::<TyKind>() {
+            let kind = match t {
+                TyKind::Todo(s) => Some(placeholder("type", s)),
+                TyKind::RawPtr(..) => Some(Kind::RawPointer),
+                TyKind::Dynamic(..) => Some(Kind::DynTrait),
+                TyKind::Coroutine(..) => Some(Kind::Coroutine),
+                _ => None,
+            };
+            kind.map(|kind| (kind, false))
+        } else if let Some(ConstantExprKind::Todo(s)) = node.downcast_ref() {
+            Some((placeholder("constant", s), true))
+        } else if let Some(ImplExprAtom::Error(s)) = node.downcast_ref() {
+            Some((placeholder("trait resolution", s), false))
+        } else if let Some(BlockSafety::ExplicitUnsafe) = node.downcast_ref() {
+            Some((Kind::UnsafeBlock, true))
+        } else {
+            None
+        }
+    }
+}
+
+impl Visitor for Linter<'_> {
+    fn visit(&mut self, node: &dyn Any, event: Event) {
+        if let Some(span) = Self::span_of(node) {
+            match event {
+                Event::Enter => self.spans.push(span.clone()),
+                Event::Exit => {
+                    self.spans.pop();
+                }
+            }
+        }
+        let Event::Enter = event else { return };
+        let Some((kind, located)) = Self::kind_of(node) else {
+            return;
+        };
+        let span = self.spans.last().unwrap().clone();
+        let key = (kind.discriminant(), located.then(|| span.clone()));
+        if relevant(&kind, self.backend) && self.seen.insert(key) {
+            self.diagnostics.push(Diagnostics {
+                kind,
+                span: vec![span],
+                context: "lint".into(),
+                owner_id: Some(self.owner_id.clone()),
+            })
+        }
+    }
+}
+
+/// Reports the unsupported constructs found in `items`, tailored to
+/// `backend` if given.
+pub fn lint<Body: IsBody + Visitable>(
+    items: &[Item<Body>],
+    backend: Option<&Backend<()>>,
+) -> Vec<Diagnostics> {
+    items
+        .iter()
+        .flat_map(|item| {
+            let mut linter = Linter {
+                spans: vec![item.span.clone()],
+                owner_id: &item.owner_id,
+                backend,
+                seen: HashSet::new(),
+                diagnostics: vec![],
+            };
+            item.drive(&mut linter);
+            linter.diagnostics
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind_name(node: &dyn Any) -> Option<(String, bool)> {
+        Linter::kind_of(node).map(|(kind, located)| (format!("{kind:?}"), located))
+    }
+
+    #[test]
+    fn kinds() {
+        assert_eq!(
+            kind_name(&ExprKind::InlineAsm),
+            Some(("InlineAsm".into(), true))
+        );
+        assert_eq!(
+            kind_name(&ExprKind::Todo("OffsetOf".into())),
+            Some((
+                "FrontendPlaceholder { node: \"expression\", details: \"OffsetOf\" }".into(),
+                true
+            ))
+        );
+        assert_eq!(
+            kind_name(&BlockSafety::ExplicitUnsafe),
+            Some(("UnsafeBlock".into(), true))
+        );
+        assert_eq!(kind_name(&BlockSafety::Safe), None);
+    }
+
+    #[test]
+    fn relevance_follows_backends() {
+        let fstar: Backend<()> = Backend::Fstar(clap::Parser::parse_from(["fstar"]));
+        let coq: Backend<()> = Backend::Coq;
+        let easycrypt: Backend<()> = Backend::Easycrypt;
+        for kind in [Kind::DynTrait, Kind::UnsafeBlock] {
+            assert!(relevant(&kind, None));
+            assert!(relevant(&kind, Some(&coq)));
+            assert!(relevant(&kind, Some(&easycrypt)));
+            assert!(!relevant(&kind, Some(&fstar)));
+        }
+        assert!(relevant(&Kind::InlineAsm, Some(&fstar)));
+    }
+}
@@ -0,0 +1,362 @@
+//! Tests of `cargo hax json` on small crates, written in a temporary
+//! directory.
+
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Once;
+
+/// The rustc driver of hax, built once.
+fn driver() -> PathBuf {
+    static BUILD: Once = Once::new();
+    BUILD.call_once(|| {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        assert!(Command::new(env!("CARGO"))
+            .args(["build", "--bin", "driver-hax-frontend-exporter"])
+            .current_dir(root)
+            .status()
+            .unwrap()
+            .success());
+    });
+    Path::new(env!("CARGO_BIN_EXE_cargo-hax")).with_file_name("driver-hax-frontend-exporter")
+}
+
+/// Exports the crate `name` whose `lib.rs` is `lib` with `cargo hax
+/// json`, passing it the options `args`.
+fn export(name: &str, lib: &str, args: &[&str]) -> Value {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("json")
+        .join(name);
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(
+        dir.join("Cargo.toml"),
+        format!("[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[workspace]\n"),
+    )
+    .unwrap();
+    std::fs::write(dir.join("src/lib.rs"), lib).unwrap();
+    let output = dir.join("export.json");
+    let status = Command::new(env!("CARGO_BIN_EXE_cargo-hax"))
+        .arg("json")
+        .arg("-o")
+        .arg(&output)
+        .args(args)
+        .env("HAX_RUSTC_DRIVER_BINARY", driver())
+        .current_dir(&dir)
+        .status()
+        .unwrap();
+    assert!(status.success(), "`cargo hax json` failed on `{name}`");
+    serde_json::from_reader(std::fs::File::open(output).unwrap()).unwrap()
+}
+
+/// The last component of the path of a `DefId`, if it is named.
+fn def_id_name(def_id: &Value) -> Option<&str> {
+    let path = def_id["contents"]["value"]["path"].as_array()?;
+    let data = path.last()?["data"].as_object()?;
+    data.values().next()?.as_str()
+}
+
+/// Collects the values of the fields named `key` in `value`.
+fn values_of<'a>(key: &str, value: &'a Value, values: &mut Vec<&'a Value>) {
+    match value {
+        Value::Array(array) => array.iter().for_each(|value| values_of(key, value, values)),
+        Value::Object(map) => {
+            values.extend(map.get(key));
+            map.values().for_each(|value| values_of(key, value, values))
+        }
+        _ => (),
+    }
+}
+
+/// The path of a `DefId` (without its crate), e.g. `{impl}::f`.
+fn path(def_id: &Value) -> String {
+    let path = def_id["contents"]["value"]["path"].as_array().unwrap();
+    let names: Vec<_> = path
+        .iter()
+        .map(|item| match &item["data"] {
+            Value::Object(data) => data.values().next().unwrap().as_str().unwrap(),
+            Value::String(data) if data == "Impl" => "{impl}",
+            data => panic!("unexpected path item {data}"),
+        })
+        .collect();
+    names.join("::")
+}
+
+/// The item named `name` in the items `items` of a crate.
+fn item<'a>(items: &'a Value, name: &str) -> &'a Value {
+    items
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|item| def_id_name(&item["owner_id"]) == Some(name))
+        .unwrap_or_else(|| panic!("no item `{name}`"))
+}
+
+/// The item named `name` of the inherent `impl` blocks in `items`.
+fn impl_item<'a>(items: &'a Value, name: &str) -> &'a Value {
+    items
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|item| item["kind"]["Impl"]["items"].as_array())
+        .flatten()
+        .find(|item| def_id_name(&item["owner_id"]) == Some(name))
+        .unwrap_or_else(|| panic!("no impl item `{name}`"))
+}
+
+const CONSTANTS: &str = r#"
+pub const N: u32 = 3;
+pub const S: &str = "hi";
+pub const R: &u32 = &5;
+pub static BYTES: &[u8] = b"ab";
+pub struct W;
+impl W {
+    pub const A: u8 = N as u8 + 4;
+}
+pub fn read() -> u32 {
+    *R + W::A as u32
+}
+"#;
+
+#[test]
+fn constant_values() {
+    let items = export("constant_values", CONSTANTS, &[]);
+    let value = |kind: &Value| kind.as_array().unwrap().last().unwrap()["contents"].clone();
+    assert_eq!(
+        value(&item(&items, "N")["kind"]["Const"])["Literal"]["Int"]["Uint"][0],
+        "3"
+    );
+    assert_eq!(
+        value(&item(&items, "S")["kind"]["Const"])["Literal"]["Str"][0],
+        "hi"
+    );
+    assert!(value(&item(&items, "BYTES")["kind"]["Static"])["Literal"].is_object());
+    assert_eq!(
+        value(&impl_item(&items, "A")["kind"]["Const"])["Literal"]["Int"]["Uint"][0],
+        "7"
+    );
+    // References other than `&str` and `&[u8]` have no value.
+    assert!(item(&items, "R")["kind"]["Const"][3].is_null());
+}
+
+#[test]
+fn constant_values_are_not_exported_with_mir() {
+    let items = export(
+        "constant_values_mir",
+        CONSTANTS,
+        &["-k", "mir-built", "-k", "thir"],
+    );
+    assert!(item(&items, "N")["kind"]["Const"][3].is_null());
+    assert!(impl_item(&items, "A")["kind"]["Const"][2].is_null());
+}
+
+#[test]
+fn layouts() {
+    let lib = r#"
+#[repr(C)]
+pub struct S {
+    pub a: u8,
+    pub b: u32,
+    pub c: u16,
+}
+pub struct G<T>(pub T);
+"#;
+    let extra = export("layouts", lib, &["--layouts"]);
+    let adt_def = |name| {
+        extra["adt_defs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|adt_def| def_id_name(&adt_def["did"]) == Some(name))
+            .unwrap_or_else(|| panic!("no ADT `{name}`"))
+    };
+    let layout = &adt_def("S")["layout"];
+    assert_eq!(layout["size"], 12);
+    assert_eq!(layout["align"], 4);
+    assert_eq!(layout["field_offsets"], serde_json::json!([0, 4, 8]));
+    // Generic ADTs have no layout.
+    assert!(adt_def("G")["layout"].is_null());
+    // Layouts are only computed with `--layouts`.
+    let extra = export("layouts_off", lib, &["--include-extra"]);
+    assert!(extra["adt_defs"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn mono_items() {
+    let lib = r#"
+pub trait Tr {
+    fn m(&self) -> u32;
+}
+pub struct A;
+impl Tr for A {
+    fn m(&self) -> u32 {
+        1
+    }
+}
+pub fn id<T>(x: T) -> T {
+    x
+}
+pub fn call<T: Tr>(x: &T) -> u32 {
+    x.m()
+}
+pub fn main() -> u32 {
+    id(1u8) as u32 + id(2u32) + call(&A)
+}
+"#;
+    let extra = export("mono_items", lib, &["--mono", "mono_items::main"]);
+    let mono_items = extra["mono_items"].as_array().unwrap();
+    let copies = |name| {
+        mono_items
+            .iter()
+            .filter(|item| def_id_name(&item["def_id"]) == Some(name))
+            .collect::<Vec<_>>()
+    };
+    // `id` is copied for each of its instantiations.
+    let mut id_args: Vec<_> = copies("id")
+        .iter()
+        .map(|item| item["generic_args"][0]["Type"]["value"]["Uint"].clone())
+        .collect();
+    id_args.sort_by_key(|ty| ty.to_string());
+    assert_eq!(id_args, ["U32", "U8"]);
+    // The call to `Tr::m` in `call::<A>` is resolved to the method of
+    // the implementation of `Tr` for `A`.
+    let [call] = copies("call")[..] else {
+        panic!("`call` must be copied once")
+    };
+    let mut globals = vec![];
+    values_of("GlobalName", &call["def"], &mut globals);
+    let in_impl = |def_id: &Value| def_id["contents"]["value"]["path"][0]["data"] == "Impl";
+    let called_m: Vec<_> = globals
+        .iter()
+        .map(|global| &global["id"])
+        .filter(|def_id| def_id_name(def_id) == Some("m"))
+        .collect();
+    assert!(!called_m.is_empty() && called_m.into_iter().all(in_impl));
+    let [m] = copies("m")[..] else {
+        panic!("`m` must be copied once")
+    };
+    assert!(in_impl(&m["def_id"]));
+}
+
+#[test]
+fn call_graph() {
+    let lib = r#"
+pub trait Tr {
+    fn m(&self) -> u32;
+    fn d(&self) -> u32 {
+        0
+    }
+}
+pub struct A;
+impl Tr for A {
+    fn m(&self) -> u32 {
+        g()
+    }
+}
+pub fn g() -> u32 {
+    2
+}
+pub fn generic<T: Tr>(x: &T) -> u32 {
+    x.m()
+}
+pub fn f(v: Vec<u32>, d: &dyn Tr) -> u32 {
+    let c = |x: u32| g() + x;
+    A.m() + A.d() + c(1) + v.len() as u32 + v.iter().count() as u32 + d.m()
+}
+"#;
+    let edges = export("call_graph", lib, &["--call-graph", "json"]);
+    let edges: Vec<_> = edges
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|edge| {
+            let name = |def_id: &Value| def_id_name(def_id).unwrap_or("").to_string();
+            (name(&edge["caller"]), path(&edge["callee"]), edge)
+        })
+        .collect();
+    let edge = |caller: &str, callee: &str| {
+        edges
+            .iter()
+            .find(|edge| (edge.0.as_str(), edge.1.as_str()) == (caller, callee))
+            .unwrap_or_else(|| panic!("no call from `{caller}` to `{callee}`"))
+            .2
+    };
+    assert_eq!(edge("m", "g")["kind"], "Direct");
+    // Calls in closures are calls of the enclosing function; calling
+    // a closure calls a method of `Fn`.
+    assert_eq!(edge("f", "g")["kind"], "Direct");
+    assert_eq!(edge("f", "ops::function::Fn::call")["kind"], "Unresolved");
+    // `A.m()` is resolved to the method of the implementation, but
+    // `A.d()` uses the default method of the trait.
+    let resolved = edge("f", "{impl}::m");
+    assert_eq!(resolved["kind"], "Resolved");
+    assert_eq!(path(&resolved["trait_method"]), "Tr::m");
+    let default = edge("f", "Tr::d");
+    assert_eq!(default["kind"], "Unresolved");
+    assert_eq!(path(&default["impl"]), "{impl}");
+    // The implementation is unknown for a bound.
+    let bound = edge("generic", "Tr::m");
+    assert_eq!(bound["kind"], "Unresolved");
+    assert!(bound["impl"].is_null());
+    assert_eq!(edge("f", "Tr::m")["kind"], "Dynamic");
+    // Calls into dependencies.
+    let len = edge("f", "vec::{impl}::len");
+    assert_eq!(len["kind"], "Direct");
+    assert_eq!(len["callee"]["contents"]["value"]["krate"], "alloc");
+    let count = edge("f", "iter::traits::iterator::Iterator::count");
+    assert_eq!(count["kind"], "Unresolved");
+    assert!(count["impl"].is_object());
+    // Calls are also found in MIR bodies.
+    let mir = export(
+        "call_graph_mir",
+        lib,
+        &["-k", "mir-built", "--call-graph", "json"],
+    );
+    assert!(mir
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|edge| { edge["kind"] == "Resolved" && path(&edge["callee"]) == "{impl}::m" }));
+}
+
+#[test]
+fn borrowck_facts() {
+    let lib = r#"
+pub fn f(v: &mut Vec<u32>) -> u32 {
+    let first = &v[0];
+    let x = *first;
+    v.push(x);
+    x
+}
+"#;
+    // `--borrowck-facts` adds `mir-promoted` bodies to the default
+    // THIR bodies.
+    let items = export("borrowck_facts", lib, &["--borrowck-facts"]);
+    let bodies = item(&items, "f")["kind"]["Fn"][1]["body"].clone();
+    let [mir, thir] = &bodies.as_array().unwrap()[..] else {
+        panic!("`f` must have two bodies")
+    };
+    assert!(thir["contents"].is_object());
+    assert_eq!(mir["phase"]["Analysis"], "Initial");
+    // The shared borrow `&v[0]` and the two-phase borrow of `v` by
+    // `v.push(x)`.
+    let facts = &mir["borrowck_facts"];
+    let loans: Vec<_> = facts["loans"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|loan| loan["kind"].clone())
+        .collect();
+    assert_eq!(
+        loans,
+        [
+            serde_json::json!("Shared"),
+            serde_json::json!({"Mut": {"kind": "TwoPhaseBorrow"}})
+        ]
+    );
+    assert_eq!(facts["loan_issued_at"].as_array().unwrap().len(), 2);
+    assert!(!facts["cfg_edge"].as_array().unwrap().is_empty());
+    // Facts are only exported with `--borrowck-facts`.
+    let items = export("borrowck_facts_off", lib, &["-k", "mir-promoted"]);
+    let body = &item(&items, "f")["kind"]["Fn"][1]["body"];
+    assert!(body["basic_blocks"].is_object() && body["borrowck_facts"].is_null());
+}
@@ -280,8 +280,6 @@ struct
       (c Rust_primitives__hax__int__add, (2, "+"));
       (c Rust_primitives__hax__int__sub, (2, "-"));
       (c Rust_primitives__hax__int__mul, (2, "*"));
-      (c Rust_primitives__hax__int__div, (2, "/"));
-      (c Rust_primitives__hax__int__rem, (2, "%"));
       (c Rust_primitives__hax__int__ge, (2, ">="));
       (c Rust_primitives__hax__int__le, (2, "<="));
       (c Rust_primitives__hax__int__gt, (2, ">"));
@@ -291,6 +289,52 @@ struct
     ]
     |> Map.of_alist_exn (module Global_ident)
 
+  (** Matches [forall(|x1, ..., xN| with_triggers(|| triggers, formula))]
+      (or the same with [exists]), or [forall_in(domain, |x|
+      with_triggers(|| triggers, formula))] (or the same with
+      [exists_in]), where [triggers] is a term or a tuple of terms.
+      Returns the quantifier, the domain of a bounded quantifier, the
+      binders, the trigger terms and the formula. *)
+  let expect_quantifier_with_triggers (e : expr) :
+      ([ `Forall | `Exists ]
+      * expr option
+      * (local_ident * ty) list
+      * expr list
+      * expr)
+      option =
+    let unascribe e = match e.e with Ascription { e; _ } -> e | _ -> e in
+    let* f, args, _, _, _ = U.Expect.app e in
+    let* quantifier, domain, closure =
+      match (f.e, args) with
+      | GlobalVar f, [ closure ] when Global_ident.eq_name Hax_lib__forall f ->
+          Some (`Forall, None, closure)
+      | GlobalVar f, [ closure ] when Global_ident.eq_name Hax_lib__exists f ->
+          Some (`Exists, None, closure)
+      | GlobalVar f, [ domain; closure ]
+        when Global_ident.eq_name Hax_lib__forall_in f ->
+          Some (`Forall, Some domain, closure)
+      | GlobalVar f, [ domain; closure ]
+        when Global_ident.eq_name Hax_lib__exists_in f ->
+          Some (`Exists, Some domain, closure)
+      | _ -> None
+    in
+    let* params, body = U.Expect.closure closure in
+    let* binders = List.map ~f:U.Expect.pbinding_simple params |> sequence in
+    let* f, args, _, _, _ = U.Expect.app (unascribe body) in
+    match (f.e, args) with
+    | GlobalVar f, [ triggers; formula ]
+      when Global_ident.eq_name Hax_lib__with_triggers f ->
+        let* _, triggers = U.Expect.closure triggers in
+        let triggers =
+          match (unascribe triggers).e with
+          | GlobalVar (`TupleCons 0) -> []
+          | Construct { constructor = `TupleCons _; fields; _ } ->
+              List.map ~f:snd fields
+          | _ -> [ triggers ]
+        in
+        Some (quantifier, domain, binders, triggers, formula)
+    | _ -> None
+
   let rec pty span (t : ty) =
     match t with
     | TBool -> F.term_of_lid [ "bool" ]
@@ -519,6 +563,46 @@ struct
             @@ "pexpr: expected a integer, found the following non-digit \
                 chars: '" ^ s ^ "'");
         F.AST.Const (F.Const.Const_int (s, None)) |> F.term
+    | App _ when Option.is_some (expect_quantifier_with_triggers e) ->
+        let quantifier, domain, binders, triggers, formula =
+          expect_quantifier_with_triggers e |> Option.value_exn
+        in
+        let fbinders =
+          List.map
+            ~f:(fun (var, typ) ->
+              F.mk_e_binder
+              @@ F.AST.Annotated (plocal_ident var, pty e.span typ))
+            binders
+        in
+        let patterns =
+          if List.is_empty triggers then [] else [ List.map ~f:pexpr triggers ]
+        in
+        let formula = pexpr formula in
+        (* A bounded quantifier ranges over the elements its domain
+           contains, as [Hax_lib.forall_in] and [Hax_lib.exists_in] *)
+        let formula =
+          match (domain, binders) with
+          | Some domain, [ (var, _) ] ->
+              let contains =
+                F.mk_e_app
+                  (F.term_of_lid [ "Hax_lib"; "f_contains" ])
+                  [
+                    pexpr domain;
+                    F.term @@ F.AST.Var (F.lid_of_id @@ plocal_ident var);
+                  ]
+              in
+              let op =
+                match quantifier with `Forall -> "==>" | `Exists -> "/\\"
+              in
+              F.term @@ F.AST.Op (F.id op, [ contains; formula ])
+          | _ -> formula
+        in
+        let quantified = (fbinders, ([], patterns), formula) in
+        F.term
+        @@
+        match quantifier with
+        | `Forall -> F.AST.QForall quantified
+        | `Exists -> F.AST.QExists quantified
     | App { f; args; generic_args; bounds_impls = _; trait } ->
         let trait_generic_args = Option.map ~f:snd trait in
         fun_application (pexpr f) args ~span:e.span ~trait_generic_args
@@ -813,10 +897,38 @@ struct
             Option.value ~default:trivial_post post )
       else None
     in
+    let smt_patterns =
+      let smt_pattern t =
+        F.mk_e_app (F.term_of_lid [ "SMTPat" ]) [ pexpr t ]
+      in
+      (* The proc-macros box the pattern terms, in a tuple if there
+         are several *)
+      let rec unbox (e : expr) =
+        match e.e with
+        | Ascription { e; _ } -> unbox e
+        | App { f = { e = GlobalVar f; _ }; args = [ e ]; _ }
+          when Global_ident.eq_name Rust_primitives__unsize f
+               || Global_ident.eq_name Alloc__boxed__Impl__new f ->
+            unbox e
+        | _ -> e
+      in
+      let terms (e : expr) =
+        match (unbox e).e with
+        | Construct { constructor = `TupleCons _; fields; _ } ->
+            List.map ~f:snd fields
+        | _ -> [ unbox e ]
+      in
+      if is_lemma then
+        Attrs.associated_expr SmtPatterns attrs
+        |> Option.map ~f:(terms >> List.map ~f:smt_pattern)
+        |> Option.map ~f:(F.AST.mkConsList F.dummyRange)
+      else None
+    in
     let args =
       (Option.map ~f:(fun (req, ens) -> [ req; ens ]) prepost_bundle
       |> Option.value ~default:[])
       @ Option.to_list decreases
+      @ Option.to_list smt_patterns
     in
     match args with
     | [] -> typ
@@ -925,8 +1037,10 @@ struct
         in
         let pat = F.pat @@ F.AST.PatApp (pat, pat_args) in
         let qualifier = F.AST.(NoLetQualifier) in
+        (* Ghost functions exist only for verification *)
+        let quals = if Attrs.ghost e.attrs then [ F.AST.NoExtract ] else [] in
         let impl =
-          F.decl ~fsti:false
+          F.decl ~fsti:false ~quals
           @@ F.AST.TopLevelLet (qualifier, [ (pat, pexpr body) ])
         in
         let interface_mode = ctx.interface_mode && not (List.is_empty params) in
@@ -959,10 +1073,11 @@ struct
         in
         let pat = F.pat @@ F.AST.PatAscribed (pat, (ty, None)) in
         let full =
-          F.decl @@ F.AST.TopLevelLet (qualifier, [ (pat, pexpr body) ])
+          F.decl ~quals
+          @@ F.AST.TopLevelLet (qualifier, [ (pat, pexpr body) ])
         in
 
-        let intf = F.decl ~fsti:true (F.AST.Val (name, arrow_typ)) in
+        let intf = F.decl ~fsti:true ~quals (F.AST.Val (name, arrow_typ)) in
 
         let erased = erased_impl name arrow_typ [] generics in
         let impl, full =
@@ -21,32 +21,32 @@ end)
 
 module Attrs = Attr_payloads.MakeBase (Error)
 
-let import_thir_items (include_clauses : Types.inclusion_clause list)
-    (items : Types.item_for__decorated_for__expr_kind list) : Ast.Rust.item list
-    =
-  let imported_items =
-    List.map
-      ~f:(fun item ->
-        let ident = Concrete_ident.(of_def_id Kind.Value item.owner_id) in
-        let most_precise_clause =
-          (* Computes the include clause that apply to `item`, if any *)
-          List.filter
-            ~f:(fun clause ->
-              Concrete_ident.matches_namespace clause.Types.namespace ident)
-            include_clauses
-          |> List.last
-        in
-        let type_only =
-          (* Shall we drop the body? *)
-          Option.map
-            ~f:(fun clause -> [%matches? Types.SignatureOnly] clause.kind)
-            most_precise_clause
-          |> Option.value ~default:false
-        in
-        Import_thir.import_item ~type_only item)
-      items
-    |> List.map ~f:snd
+(** Imports one item of the crate, together with the errors of its
+    import. *)
+let import_thir_item (include_clauses : Types.inclusion_clause list)
+    (item : Types.item_for__decorated_for__expr_kind) =
+  let ident = Concrete_ident.(of_def_id Kind.Value item.owner_id) in
+  let most_precise_clause =
+    (* Computes the include clause that apply to `item`, if any *)
+    List.filter
+      ~f:(fun clause ->
+        Concrete_ident.matches_namespace clause.Types.namespace ident)
+      include_clauses
+    |> List.last
   in
+  let type_only =
+    (* Shall we drop the body? *)
+    Option.map
+      ~f:(fun clause -> [%matches? Types.SignatureOnly] clause.kind)
+      most_precise_clause
+    |> Option.value ~default:false
+  in
+  Import_thir.import_item ~type_only item |> snd
+
+(** Selects the imported items of the crate according to the include
+    clauses, and reports the errors of the selected items. *)
+let process_imported_items (include_clauses : Types.inclusion_clause list)
+    imported_items : Ast.Rust.item list =
   Logs.info (fun m -> m "Items translated");
   let items = List.concat_map ~f:fst imported_items in
   let associated_items =
@@ -88,6 +88,93 @@ let import_thir_items (include_clauses : Types.inclusion_clause list)
   List.iter ~f:Diagnostics.Core.report reports;
   items
 
+(** Imports the items of the dependencies of the crate. Those are
+    imported signature-only, and serve as context only: they are never
+    translated by the backends. Errors are discarded. *)
+let import_dependencies_items
+    (items : Types.item_for__decorated_for__expr_kind list) : Ast.Rust.item list
+    =
+  List.concat_map
+    ~f:(fun item -> Import_thir.import_item ~type_only:true item |> snd |> fst)
+    items
+
+(** Shallow parses a `id_table::Node<T>` (or a raw `T`) JSON *)
+let parse_id_table_node (json : Yojson.Safe.t) :
+    (int64 * Yojson.Safe.t) list * Yojson.Safe.t =
+  let expect_uint64 = function
+    | `Intlit str -> Some (Int64.of_string str)
+    | `Int id -> Some (Int.to_int64 id)
+    | _ -> None
+  in
+  let table, value =
+    match json with
+    | `List [ table; value ] -> (table, value)
+    | _ -> failwith "parse_id_table_node: expected a tuple at top-level"
+  in
+  let table =
+    match table with
+    | `List json_list -> json_list
+    | _ -> failwith "parse_id_table_node: `map` is supposed to be a list"
+  in
+  let table =
+    List.map
+      ~f:(function
+        | `List [ id; `Assoc [ (_, contents) ] ] ->
+            let id =
+              expect_uint64 id
+              |> Option.value_exn
+                   ~message:"parse_id_table_node: id: expected int64"
+            in
+            (id, contents)
+        | _ -> failwith "parse_id_table_node: expected a list of size two")
+      table
+  in
+  (table, value)
+
+(** Asks `cargo-hax` for the next chunk of the input of the engine, and
+    reads it. The table of each chunk extends the tables of the previous
+    chunks. *)
+let read_input_chunk () : Types.engine_input =
+  Hax_io.write RequestInput;
+  let table, json =
+    Hax_io.read_json () |> Option.value_exn |> parse_id_table_node
+  in
+  table
+  |> List.iter ~f:(fun (id, json) ->
+         Hashtbl.add_exn Types.cache_map ~key:id ~data:(`JSON json));
+  Types.parse_engine_input json
+
+(** Reads the options of the engine, which start its input. *)
+let parse_options () =
+  match read_input_chunk () with
+  | Options options ->
+      Profiling.enabled := options.backend.profile;
+      options
+  | _ -> failwith "parse_options: expected the options first"
+
+(** Reads the items that follow the options, importing them chunk by
+    chunk: the THIR items are not kept once imported. Returns the
+    imported items of the crate, and the items of the dependencies. *)
+let import_input (include_clauses : Types.inclusion_clause list) =
+  let rec read_chunks items dependencies =
+    match read_input_chunk () with
+    | Items chunk ->
+        let imported =
+          Profiling.profile ThirImport (List.length chunk) (fun _ ->
+              List.map ~f:(import_thir_item include_clauses) chunk)
+        in
+        read_chunks (List.rev_append imported items) dependencies
+    | DependencyItems chunk ->
+        let imported =
+          Profiling.profile ThirImport (List.length chunk) (fun _ ->
+              import_dependencies_items chunk)
+        in
+        read_chunks items (List.rev_append imported dependencies)
+    | End -> (List.rev items, List.rev dependencies)
+    | Options _ -> failwith "import_input: unexpected options"
+  in
+  read_chunks [] []
+
 let run (options : Types.engine_options) : Types.output =
   setup_logs options;
   if options.backend.debug_engine |> Option.is_some then
@@ -101,22 +188,45 @@ let run (options : Types.engine_options) : Types.output =
     let include_clauses =
       options.backend.translation_options.include_namespaces
     in
-    let items =
-      Profiling.profile ThirImport (List.length options.input) (fun _ ->
-          import_thir_items include_clauses options.input)
-    in
-    let items =
+    let imported_items, dependencies_items = import_input include_clauses in
+    let items = process_imported_items include_clauses imported_items in
+    let filter_type_aliases items =
       if options.backend.extract_type_aliases then items
       else
         List.filter
           ~f:(function { v = TyAlias _; _ } -> false | _ -> true)
           items
     in
+    let items = filter_type_aliases items in
     Logs.info (fun m ->
         m "Applying phase for backend %s"
           ([%show: Diagnostics.Backend.t] M.backend));
     let items = apply_phases backend_options items in
-    let with_items = Attrs.with_items items in
+    (* The items of the dependencies go through the phases one by one:
+       their errors are not reported, the items that fail are dropped,
+       and they are neither bundled nor translated. They only provide
+       context (e.g. contracts) to the items of the crate. *)
+    let dependencies_items =
+      filter_type_aliases dependencies_items
+      |> List.concat_map ~f:(fun (item : Ast.Rust.item) ->
+             let items, errors =
+               Diagnostics.capture (fun () ->
+                   try Some (apply_phases backend_options [ item ])
+                   with Diagnostics.Error | Diagnostics.SpanFreeError.Exn _ ->
+                     None)
+             in
+             match items with
+             | Some items -> items
+             | None ->
+                 Logs.debug (fun m ->
+                     m "Dropping the dependency item %s: %s"
+                       (Concrete_ident.show item.ident)
+                       ([%show: Diagnostics.kind list]
+                          (List.map ~f:(fun (e : Diagnostics.t) -> e.kind)
+                             errors)));
+                 [])
+    in
+    let with_items = Attrs.with_items (items @ dependencies_items) in
     let bundles, _ =
       let module DepGraph = Dependencies.Make (InputLanguage) in
       DepGraph.recursive_bundles items
@@ -149,50 +259,6 @@ let run (options : Types.engine_options) : Types.output =
     debug_json = None;
   }
 
-(** Shallow parses a `id_table::Node<T>` (or a raw `T`) JSON *)
-let parse_id_table_node (json : Yojson.Safe.t) :
-    (int64 * Yojson.Safe.t) list * Yojson.Safe.t =
-  let expect_uint64 = function
-    | `Intlit str -> Some (Int64.of_string str)
-    | `Int id -> Some (Int.to_int64 id)
-    | _ -> None
-  in
-  let table, value =
-    match json with
-    | `List [ table; value ] -> (table, value)
-    | _ -> failwith "parse_id_table_node: expected a tuple at top-level"
-  in
-  let table =
-    match table with
-    | `List json_list -> json_list
-    | _ -> failwith "parse_id_table_node: `map` is supposed to be a list"
-  in
-  let table =
-    List.map
-      ~f:(function
-        | `List [ id; `Assoc [ (_, contents) ] ] ->
-            let id =
-              expect_uint64 id
-              |> Option.value_exn
-                   ~message:"parse_id_table_node: id: expected int64"
-            in
-            (id, contents)
-        | _ -> failwith "parse_id_table_node: expected a list of size two")
-      table
-  in
-  (table, value)
-
-let parse_options () =
-  let table, json =
-    Hax_io.read_json () |> Option.value_exn |> parse_id_table_node
-  in
-  table
-  |> List.iter ~f:(fun (id, json) ->
-         Hashtbl.add_exn Types.cache_map ~key:id ~data:(`JSON json));
-  let options = Types.parse_engine_options json in
-  Profiling.enabled := options.backend.profile;
-  options
-
 (** Entrypoint of the engine. Assumes `Hax_io.init` was called. *)
 let main () =
   let options = Profiling.profile (Other "parse_options") 1 parse_options in
@@ -58,6 +58,7 @@ module AssocRole = struct
       | ProcessInit
       | ProtocolMessages
       | ItemQuote
+      | SmtPatterns
     [@@deriving show, yojson, compare, sexp, eq]
   end
 
@@ -79,6 +80,7 @@ module AssocRole = struct
     | ProcessWrite -> ProcessWrite
     | ProcessInit -> ProcessInit
     | ProtocolMessages -> ProtocolMessages
+    | SmtPatterns -> SmtPatterns
 end
 
 module MakeBase (Error : Phase_utils.ERROR) = struct
@@ -120,6 +122,9 @@ module MakeBase (Error : Phase_utils.ERROR) = struct
   let lemma : attrs -> bool =
     payloads >> List.exists ~f:(fst >> [%matches? Types.Lemma])
 
+  let ghost : attrs -> bool =
+    payloads >> List.exists ~f:(fst >> [%matches? Types.Ghost])
+
   (* User code can be *decorated* (e.g. attributes `ensures` or
      `refine`). A decoration is attached to a user code via an
      `AssociatedItem` attribute, that specifies an unique identifier
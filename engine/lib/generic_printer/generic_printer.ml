@@ -50,8 +50,9 @@ module Annotation = struct
     let* span =
       Span.to_thir span
       |> List.find ~f:(fun (s : Types.span) ->
-             real_path s.filename |> Option.is_some)
+             real_path s.value.filename |> Option.is_some)
     in
+    let span = span.value in
     let* src_filename = real_path span.filename in
     let src_start = to_loc span.lo |> loc_to_loc in
     let src_end = to_loc span.hi |> loc_to_loc in
@@ -20,11 +20,11 @@ let assertion_failure (span : Thir.span list) (details : string) =
   let kind = T.AssertionFailure { details } in
   Diagnostics.SpanFreeError.raise ~span ThirImport kind
 
-let unimplemented ~issue_id (span : Thir.span list) (details : string) =
+let unimplemented ?issue_id (span : Thir.span list) (details : string) =
   let kind =
     T.Unimplemented
       {
-        issue_id = Some (MyInt64.of_int_exn issue_id);
+        issue_id = Option.map ~f:MyInt64.of_int_exn issue_id;
         details = String.(if details = "" then None else Some details);
       }
   in
@@ -768,6 +768,10 @@ end) : EXPR = struct
       | Yield _ ->
           unimplemented ~issue_id:924 [ e.span ]
             "Got expression `Yield`: coroutines are not supported by hax"
+      | InlineAsm ->
+          unimplemented [ e.span ]
+            "Got expression `InlineAsm`: inline assembly is not supported by \
+             hax"
       | Todo payload ->
           assertion_failure [ e.span ] ("expression Todo\n" ^ payload)
     in
@@ -1078,7 +1082,8 @@ end) : EXPR = struct
     | Todo _ -> assertion_failure [ span ] "type Todo"
   (* fun _ -> Ok Bool *)
 
-  and c_impl_expr (span : Thir.span) (ie : Thir.impl_expr) : impl_expr =
+  and c_impl_expr (span : Thir.span) ({ value = ie; _ } : Thir.impl_expr) :
+      impl_expr =
     let goal = c_trait_ref span ie.trait.value in
     let impl = { kind = c_impl_expr_atom span ie.impl; goal } in
     match ie.args with
@@ -1494,7 +1499,7 @@ and c_item_unwrapped ~ident ~type_only (item : Thir.item) : item list =
   let c_body = if drop_body then c_expr_drop_body else c_expr in
   (* TODO: things might be unnamed (e.g. constants) *)
   match (item.kind : Thir.item_kind) with
-  | Const (_, generics, body) ->
+  | Const (_, generics, body, _) ->
       mk
       @@ Fn
            {
@@ -1682,7 +1687,7 @@ and c_item_unwrapped ~ident ~type_only (item : Thir.item) : item list =
                     params;
                     safety = csafety safety;
                   }
-            | Const (_ty, e) ->
+            | Const (_ty, e, _) ->
                 Fn
                   {
                     name = item_def_id;
@@ -1753,7 +1758,7 @@ and c_item_unwrapped ~ident ~type_only (item : Thir.item) : item list =
                         else List.map ~f:(c_param item.span) params
                       in
                       IIFn { body = c_expr body; params }
-                  | Const (_ty, e) -> IIFn { body = c_expr e; params = [] }
+                  | Const (_ty, e, _) -> IIFn { body = c_expr e; params = [] }
                   | Type { ty; parent_bounds } ->
                       IIType
                         {
@@ -1828,6 +1833,7 @@ and c_item_unwrapped ~ident ~type_only (item : Thir.item) : item list =
   | ExternCrate _ | Static _ | Macro _ | Mod _ | ForeignMod _ | GlobalAsm _
   | TraitAlias _ ->
       mk NotImplementedYet
+  | Error message -> mk (HaxError message)
 
 let import_item ~type_only (item : Thir.item) :
     concrete_ident * (item list * Diagnostics.t list) =
@@ -74,6 +74,42 @@ struct
           { body; invariant = Some (pat, invariant) }
       | _ -> { body; invariant = None }
 
+    type while_annotations = {
+      loop_body : B.expr;
+      loop_invariant : B.expr option;
+      loop_decreases : B.expr option;
+    }
+
+    (** Extracts the calls to [_internal_while_loop_invariant] and
+    [_internal_loop_decreases] heading the body of a [while] or a
+    [loop] loop. *)
+    let rec extract_while_loop_annotations (body : B.expr) : while_annotations
+        =
+      match body.e with
+      | Let
+          {
+            monadic = None;
+            lhs = { p = PWild; _ };
+            rhs =
+              { e = App { f = { e = GlobalVar f; _ }; args = [ arg ]; _ }; _ };
+            body;
+          }
+        when Global_ident.eq_name Hax_lib___internal_while_loop_invariant f ->
+          let annotations = extract_while_loop_annotations body in
+          { annotations with loop_invariant = Some arg }
+      | Let
+          {
+            monadic = None;
+            lhs = { p = PWild; _ };
+            rhs =
+              { e = App { f = { e = GlobalVar f; _ }; args = [ arg ]; _ }; _ };
+            body;
+          }
+        when Global_ident.eq_name Hax_lib___internal_loop_decreases f ->
+          let annotations = extract_while_loop_annotations body in
+          { annotations with loop_decreases = Some arg }
+      | _ -> { loop_body = body; loop_invariant = None; loop_decreases = None }
+
     type iterator =
       | Range of { start : B.expr; end_ : B.expr }
       | Slice of B.expr
@@ -238,7 +274,7 @@ struct
       | Loop
           {
             body;
-            kind = WhileLoop { condition; _ };
+            kind = (WhileLoop _ | UnconditionalLoop) as kind;
             state = Some _ as state;
             control_flow;
             _;
@@ -246,7 +282,7 @@ struct
       | Loop
           {
             body;
-            kind = WhileLoop { condition; _ };
+            kind = (WhileLoop _ | UnconditionalLoop) as kind;
             state;
             control_flow = Some (BreakOrReturn, _) as control_flow;
             _;
@@ -259,25 +295,70 @@ struct
                 (M.pat_PWild ~span ~typ:unit.typ, unit)
           in
           let body = dexpr body in
-          let condition = dexpr condition in
-          let condition : B.expr =
-            M.expr_Closure ~params:[ bpat ] ~body:condition ~captures:[]
-              ~span:condition.span
-              ~typ:(TArrow ([ bpat.typ ], condition.typ))
+          let {
+            loop_body = body;
+            loop_invariant = invariant;
+            loop_decreases = decreases;
+          } =
+            extract_while_loop_annotations body
           in
-          let body : B.expr =
-            M.expr_Closure ~params:[ bpat ] ~body ~captures:[]
+          (* A [loop] loop is a [while true] loop *)
+          let condition =
+            match kind with
+            | WhileLoop { condition; _ } -> dexpr condition
+            | _ -> MS.expr_Literal ~typ:TBool (Bool true)
+          in
+          let closure (body : B.expr) : B.expr =
+            M.expr_Closure ~params:[ bpat ] ~body ~captures:[] ~span:body.span
               ~typ:(TArrow ([ bpat.typ ], body.typ))
-              ~span:body.span
           in
-          let fold_operator : Concrete_ident.name =
-            match control_flow with
-            | Some (BreakOrReturn, _) -> Rust_primitives__hax__while_loop_return
-            | Some (BreakOnly, _) -> Rust_primitives__hax__while_loop_cf
-            | None -> Rust_primitives__hax__while_loop
+          let fold_operator, annotations =
+            match (invariant, decreases) with
+            | None, None ->
+                let fold_operator : Concrete_ident.name =
+                  match control_flow with
+                  | Some (BreakOrReturn, _) ->
+                      Rust_primitives__hax__while_loop_return
+                  | Some (BreakOnly, _) -> Rust_primitives__hax__while_loop_cf
+                  | None -> Rust_primitives__hax__while_loop
+                in
+                (fold_operator, [])
+            | _ ->
+                let invariant =
+                  Option.value invariant
+                    ~default:(MS.expr_Literal ~typ:TBool (Bool true))
+                in
+                (* Without a measure, termination cannot be proven *)
+                let decreases =
+                  match decreases with
+                  | Some decreases -> decreases
+                  | None ->
+                      Error.raise
+                        {
+                          kind =
+                            AttributeRejected
+                              {
+                                reason =
+                                  "A `while` or `loop` loop with an invariant \
+                                   needs a termination measure: please add a \
+                                   `hax_lib::loop_decreases!`.";
+                              };
+                          span;
+                        }
+                in
+                let fold_operator : Concrete_ident.name =
+                  match control_flow with
+                  | Some (BreakOrReturn, _) ->
+                      Rust_primitives__hax__while_loop_inv_return
+                  | Some (BreakOnly, _) ->
+                      Rust_primitives__hax__while_loop_inv_cf
+                  | None -> Rust_primitives__hax__while_loop_inv
+                in
+                (fold_operator, [ closure invariant; closure decreases ])
           in
           UB.call ~kind:(AssociatedItem Value) fold_operator
-            [ condition; init; body ] span (dty span expr.typ)
+            ([ closure condition ] @ annotations @ [ init; closure body ])
+            span (dty span expr.typ)
       | Loop { state = None; _ } ->
           Error.unimplemented ~issue_id:405 ~details:"Loop without mutation"
             span
@@ -21,6 +21,9 @@ module Make (F : Features.T) =
           fn_replace : t;
           args : (expr -> bool) list;
           ret : ty -> bool;
+          lift : int list;
+              (** The indexes of the machine integer arguments that
+              should be lifted to mathematical integers *)
         }
         (** A pattern that helps matching against function applications *)
 
@@ -29,11 +32,11 @@ module Make (F : Features.T) =
         work with `_ -> _ option` so that we can chain them *)
 
         (** Constructs a predicate out of predicates and names *)
-        let mk (args : ('a, 'b) predicate list) (ret : ('c, 'd) predicate)
-            (fn : t) (fn_replace : t) : pattern =
+        let mk ?(lift = []) (args : ('a, 'b) predicate list)
+            (ret : ('c, 'd) predicate) (fn : t) (fn_replace : t) : pattern =
           let args = List.map ~f:(fun p x -> p x |> Option.is_some) args in
           let ret t = ret t |> Option.is_some in
-          { fn; fn_replace; args; ret }
+          { fn; fn_replace; args; ret; lift }
 
         open struct
           let etyp (e : expr) : ty = e.typ
@@ -59,6 +62,10 @@ module Make (F : Features.T) =
           let is_int : (ty, unit) predicate =
             tapp0 >>& eq_global_ident Hax_lib__int__Int >>& erase
 
+          let is_machine_int : (ty, unit) predicate = function
+            | TInt _ -> Some ()
+            | _ -> None
+
           let any _ = Some ()
           let int_any = mk [ etyp >> is_int ] any
           let int_int_any = mk [ etyp >> is_int; etyp >> is_int ] any
@@ -68,9 +75,35 @@ module Make (F : Features.T) =
           let rint_rint_any =
             mk [ etyp >> (tref >>& is_int); etyp >> (tref >>& is_int) ] any
 
+          let rint_rmachine_any =
+            mk ~lift:[ 1 ]
+              [ etyp >> (tref >>& is_int); etyp >> (tref >>& is_machine_int) ]
+              any
+
+          let rmachine_rint_any =
+            mk ~lift:[ 0 ]
+              [ etyp >> (tref >>& is_machine_int); etyp >> (tref >>& is_int) ]
+              any
+
           let any_rint = mk [ any ] (tref >>& is_int)
         end
 
+        (** Comparisons of mathematical integers, possibly with a machine
+        integer on one side *)
+        let comparisons =
+          [
+            (Core__cmp__PartialOrd__gt, Rust_primitives__hax__int__gt);
+            (Core__cmp__PartialOrd__ge, Rust_primitives__hax__int__ge);
+            (Core__cmp__PartialOrd__lt, Rust_primitives__hax__int__lt);
+            (Core__cmp__PartialOrd__le, Rust_primitives__hax__int__le);
+            (Core__cmp__PartialEq__ne, Rust_primitives__hax__int__ne);
+            (Core__cmp__PartialEq__eq, Rust_primitives__hax__int__eq);
+          ]
+          |> List.concat_map ~f:(fun (fn, fn_replace) ->
+                 List.map
+                   ~f:(fun mk -> mk fn fn_replace)
+                   [ rint_rint_any; rint_rmachine_any; rmachine_rint_any ])
+
         (** The list of replacements *)
         let patterns =
           [
@@ -84,21 +117,58 @@ module Make (F : Features.T) =
               Rust_primitives__hax__int__div;
             int_int_any Core__ops__arith__Rem__rem
               Rust_primitives__hax__int__rem;
-            rint_rint_any Core__cmp__PartialOrd__gt
-              Rust_primitives__hax__int__gt;
-            rint_rint_any Core__cmp__PartialOrd__ge
-              Rust_primitives__hax__int__ge;
-            rint_rint_any Core__cmp__PartialOrd__lt
-              Rust_primitives__hax__int__lt;
-            rint_rint_any Core__cmp__PartialOrd__le
-              Rust_primitives__hax__int__le;
-            rint_rint_any Core__cmp__PartialEq__ne Rust_primitives__hax__int__ne;
-            rint_rint_any Core__cmp__PartialEq__eq Rust_primitives__hax__int__eq;
+            int_any Core__ops__arith__Neg__neg Rust_primitives__hax__int__neg;
+            int_int_any Core__ops__bit__BitAnd__bitand
+              Rust_primitives__hax__int__bit_and;
+            int_int_any Core__ops__bit__BitOr__bitor
+              Rust_primitives__hax__int__bit_or;
+            int_int_any Core__ops__bit__BitXor__bitxor
+              Rust_primitives__hax__int__bit_xor;
+            int_int_any Core__ops__bit__Shl__shl
+              Rust_primitives__hax__int__shl;
+            int_int_any Core__ops__bit__Shr__shr
+              Rust_primitives__hax__int__shr;
             any_rint Hax_lib__int__Abstraction__lift
               Rust_primitives__hax__int__from_machine;
             int_any Hax_lib__int__Concretization__concretize
               Rust_primitives__hax__int__into_machine;
           ]
+          @ comparisons
+
+        (** Lifts the arguments of indexes [lift] in [args] to
+        mathematical integers *)
+        let lift_args (lift : int list) (args : expr list) : expr list =
+          let is_lifted i = List.mem lift i ~equal:Int.equal in
+          match List.filteri args ~f:(fun i _ -> not (is_lifted i)) with
+          | { typ; _ } :: _ ->
+              List.mapi args ~f:(fun i (arg : expr) ->
+                  if is_lifted i then
+                    let f =
+                      Ast.Global_ident.of_name Value
+                        Rust_primitives__hax__int__from_machine
+                    in
+                    let f =
+                      {
+                        arg with
+                        e = GlobalVar f;
+                        typ = TArrow ([ arg.typ ], typ);
+                      }
+                    in
+                    {
+                      arg with
+                      e =
+                        App
+                          {
+                            f;
+                            args = [ arg ];
+                            trait = None;
+                            generic_args = [];
+                            bounds_impls = [];
+                          };
+                      typ;
+                    }
+                  else arg)
+          | [] -> args
       end
 
       module Error = Phase_utils.MakeError (struct
@@ -131,7 +201,7 @@ module Make (F : Features.T) =
                       | _ -> false)
                 in
                 match matching with
-                | [ { fn_replace; _ } ] ->
+                | [ { fn_replace; lift; _ } ] ->
                     let f = Ast.Global_ident.of_name Value fn_replace in
                     let f = { f' with e = GlobalVar f } in
                     {
@@ -140,7 +210,7 @@ module Make (F : Features.T) =
                         App
                           {
                             f;
-                            args = l;
+                            args = lift_args lift l;
                             trait = None;
                             generic_args = [];
                             bounds_impls = [];
@@ -48,7 +48,7 @@ module Imported = struct
   let loc_of_thir ({ col; line } : Types.loc) : loc =
     { col = Int.of_string col; line = Int.of_string line }
 
-  let span_of_thir (s : Types.span) : span =
+  let span_of_thir ({ value = s; _ } : Types.span) : span =
     {
       filename = file_name_of_thir s.filename;
       hi = loc_of_thir s.hi;
@@ -74,11 +74,19 @@ module Imported = struct
   let loc_to_thir ({ col; line } : loc) : Types.loc =
     { col = Int.to_string col; line = Int.to_string line }
 
+  (** Spans built by the engine are detached from the id table of the
+      frontend: they are always serialized with their value. *)
+  let detached_id = Int64.of_int 0xFFFFFFFF
+
   let span_to_thir (s : span) : Types.span =
     {
-      filename = file_name_to_thir s.filename;
-      hi = loc_to_thir s.hi;
-      lo = loc_to_thir s.lo;
+      id = detached_id;
+      value =
+        {
+          filename = file_name_to_thir s.filename;
+          hi = loc_to_thir s.hi;
+          lo = loc_to_thir s.lo;
+        };
     }
 
   let display_loc (l : loc) : string =
@@ -30,6 +30,12 @@ fn dummy_hax_concrete_ident_wrapper<I: core::iter::Iterator<Item = u8>>(x: I, mu
     assert_eq!(1, 1);
     hax_lib::assert!(true);
     hax_lib::_internal_loop_invariant(|_: usize| true);
+    hax_lib::_internal_while_loop_invariant(true);
+    hax_lib::_internal_loop_decreases(hax_lib::int::Int::_unsafe_from_str("0"));
+    let _ = hax_lib::forall(|_: usize| hax_lib::with_triggers(|| (), true));
+    let _ = hax_lib::forall_in(0..1usize, |_| true);
+    let _ = hax_lib::exists_in(0..1usize, |_| true);
+    let _ = hax_lib::exists(|_: usize| true);
 
     let _ = [()].into_iter();
     let _: u16 = 6u8.into();
@@ -166,6 +172,9 @@ mod hax {
     fn while_loop() {}
     fn while_loop_cf() {}
     fn while_loop_return() {}
+    fn while_loop_inv() {}
+    fn while_loop_inv_cf() {}
+    fn while_loop_inv_return() {}
     fn repeat() {}
     fn update_at() {}
     mod monomorphized_update_at {
@@ -206,6 +215,13 @@ mod hax {
         fn div() {}
         fn mul() {}
         fn rem() {}
+        fn neg() {}
+
+        fn bit_and() {}
+        fn bit_or() {}
+        fn bit_xor() {}
+        fn shl() {}
+        fn shr() {}
 
         fn le() {}
         fn lt() {}
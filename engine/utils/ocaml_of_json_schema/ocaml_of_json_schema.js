@@ -528,6 +528,18 @@ let export_definition = (name, def) => {
     // return `type ${name} = ${type}\nlet parse_${name} (o: Yojson.Safe.t): ${name} = ${parse}\n`;
 };
 
+// The types shared through the id table of the frontend (see
+// `frontend/exporter/src/id_table.rs`): their Rust names, the OCaml
+// names of their contents and, for the transparent Rust wrappers
+// around a node, the OCaml name of the wrapper.
+const table_id_nodes = [
+    {rust: 'TyKind', ocaml: 'ty_kind'},
+    {rust: 'DefIdContents', ocaml: 'def_id_contents'},
+    {rust: 'SpanContents', ocaml: 'span_contents', alias: 'span'},
+    {rust: 'ImplExprContents', ocaml: 'impl_expr_contents', alias: 'impl_expr'},
+    {rust: 'GenericPredicatesContents', ocaml: 'generic_predicates_contents', alias: 'generic_predicates'},
+];
+
 function run(str){
     let contents = JSON.parse(str);
     const definitions = clean(contents.definitions);
@@ -538,8 +550,10 @@ function run(str){
 [@@@warning "-A"]`;
 
     let items = Object.entries(definitions)
-        .map(([name, def]) => ['Node_for_TyKind' == name ? 'node_for_ty_kind_generated' : name, def])
-        .map(([name, def]) => ['Node_for_DefIdContents' == name ? 'node_for_def_id_contents_generated' : name, def])
+        .map(([name, def]) => {
+            let node = table_id_nodes.find(({rust}) => 'Node_for_' + rust == name);
+            return [node ? 'node_for_' + node.ocaml + '_generated' : name, def];
+        })
         .map(
             ([name, def]) => export_definition(name, def)
         ).filter(x => x instanceof Object);
@@ -572,10 +586,10 @@ open ParseError
             + derive_clause
     );
     impl += `
-and node_for__ty_kind = node_for_ty_kind_generated
-and node_for__def_id_contents = node_for_def_id_contents_generated
+${table_id_nodes.map(({ocaml}) => `and node_for__${ocaml} = node_for_${ocaml}_generated`).join('\n')}
+${table_id_nodes.filter(({alias}) => alias).map(({ocaml, alias}) => `and ${alias} = node_for__${ocaml}`).join('\n')}
 
-type map_types = ${"[`TyKind of ty_kind | `DefIdContents of def_id_contents]"}
+type map_types = [${table_id_nodes.map(({rust, ocaml}) => `\`${rust} of ${ocaml}`).join(' | ')}]
 let cache_map: (int64, ${"[ `Value of map_types | `JSON of Yojson.Safe.t ]"}) Base.Hashtbl.t = Base.Hashtbl.create (module Base.Int64)
 
 let parse_table_id_node (type t) (name: string) (encode: t -> map_types) (decode: map_types -> t option) (parse: Yojson.Safe.t -> t) (o: Yojson.Safe.t): (t * int64) =
@@ -610,32 +624,22 @@ let parse_table_id_node (type t) (name: string) (encode: t -> map_types) (decode
         `parse_${name} (o: Yojson.Safe.t): ${name} = ${parse}`
     ).join('\nand '));
     impl += `
-and parse_node_for__ty_kind (o: Yojson.Safe.t): node_for__ty_kind =
-   let (value, id) =
-       parse_table_id_node "TyKind"
-           (fun value -> \`TyKind value)
-           (function | \`TyKind value -> Some value | _ -> None)
-           parse_ty_kind
-           o
-   in
-   {value; id}
-and parse_node_for__def_id_contents (o: Yojson.Safe.t): node_for__def_id_contents =
+${table_id_nodes.map(({rust, ocaml}) => `and parse_node_for__${ocaml} (o: Yojson.Safe.t): node_for__${ocaml} =
    let (value, id) =
-       parse_table_id_node "DefIdContents"
-           (fun value -> \`DefIdContents value)
-           (function | \`DefIdContents value -> Some value | _ -> None)
-           parse_def_id_contents
+       parse_table_id_node "${rust}"
+           (fun value -> \`${rust} value)
+           (function | \`${rust} value -> Some value | _ -> None)
+           parse_${ocaml}
            o
    in
-   {value; id}
+   {value; id}`).join('\n')}
 `;
     impl += ('');
     impl += ('let rec ' + items.map(({name, type, parse, to_json}) =>
         `to_json_${name} (o: ${name}): Yojson.Safe.t = ${to_json}`
     ).join('\nand '));
     impl += `
-and to_json_node_for__ty_kind {value; id} = to_json_node_for_ty_kind_generated {value; id}
-and to_json_node_for__def_id_contents {value; id} = to_json_node_for_def_id_contents_generated {value; id}
+${table_id_nodes.map(({ocaml}) => `and to_json_node_for__${ocaml} {value; id} = to_json_node_for_${ocaml}_generated {value; id}`).join('\n')}
 `;
 
 
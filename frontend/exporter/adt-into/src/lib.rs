@@ -470,6 +470,9 @@ pub fn derive_group(
             "Serializers" => quote! {
                 #[derive(::serde::Serialize, ::serde::Deserialize)]
             },
+            "Visitors" => quote! {
+                #[derive(::hax_adt_into::Visitable)]
+            },
             _ => {
                 errors.push(quote! {
                     const _: () = compile_error!(concat!(
@@ -484,3 +487,118 @@ pub fn derive_group(
         .collect();
     quote! {#(#errors)* #result #item}.into()
 }
+
+/// Destructures a set of fields into bindings `binding_0`,
+/// `binding_1`, etc. Returns the pattern (without the constructor
+/// path) and the list of bindings.
+fn fields_pattern(fields: &syn::Fields) -> (proc_macro2::TokenStream, Vec<syn::Ident>) {
+    let bindings: Vec<_> = fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| syn::Ident::new(&format!("binding_{}", i), field.span()))
+        .collect();
+    let pattern = match fields {
+        syn::Fields::Unit => quote! {},
+        syn::Fields::Named(named) => {
+            let names = named.named.iter().map(|f| f.ident.clone());
+            quote! { { #(#names: #bindings),* } }
+        }
+        syn::Fields::Unnamed(_) => quote! { ( #(#bindings),* ) },
+    };
+    (pattern, bindings)
+}
+
+/// [`Visitable`] derives an instance of the trait
+/// `hax_frontend_exporter::visitors::Visitable`. The derived
+/// `drive` (resp. `drive_mut`) method calls the visitor on `self`
+/// with `Event::Enter`, drives every field in order, and finally
+/// calls the visitor again with `Event::Exit`. The derived
+/// `may_contain` explores the types of the fields. Every type
+/// parameter is required to be `Visitable` itself.
+#[proc_macro_derive(Visitable)]
+pub fn visitable(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let dinput = parse_macro_input!(input as DeriveInput);
+    let name = &dinput.ident;
+    let krate = quote! {::hax_frontend_exporter::visitors};
+
+    let mut generics = dinput.generics.clone();
+    {
+        let where_clause = generics.make_where_clause();
+        for param in dinput.generics.type_params() {
+            let ident = &param.ident;
+            where_clause
+                .predicates
+                .push(syn::parse_quote! {#ident: #krate::Visitable});
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let arms: Vec<(proc_macro2::TokenStream, Vec<syn::Ident>)> = match &dinput.data {
+        Data::Union(..) => panic!("Union types are not supported"),
+        Data::Struct(syn::DataStruct { fields, .. }) => {
+            let (pattern, bindings) = fields_pattern(fields);
+            vec![(quote! {Self #pattern}, bindings)]
+        }
+        Data::Enum(syn::DataEnum { variants, .. }) => variants
+            .iter()
+            .map(|variant| {
+                let ident = &variant.ident;
+                let (pattern, bindings) = fields_pattern(&variant.fields);
+                (quote! {Self::#ident #pattern}, bindings)
+            })
+            .collect(),
+    };
+    let make_body = |method: proc_macro2::TokenStream| -> proc_macro2::TokenStream {
+        if arms.is_empty() {
+            // Matching on `self` would not be exhaustive for empty enums
+            return quote! { match *self {} };
+        }
+        let arms = arms.iter().map(|(pattern, bindings)| {
+            quote! {
+                #[allow(unused_variables)]
+                #pattern => { #(#krate::Visitable::#method(#bindings, visitor);)* }
+            }
+        });
+        quote! { match self { #(#arms)* } }
+    };
+    let drive_body = make_body(quote! {drive});
+    let drive_mut_body = make_body(quote! {drive_mut});
+    let field_types: Vec<&syn::Type> = match &dinput.data {
+        Data::Union(..) => unreachable!(),
+        Data::Struct(syn::DataStruct { fields, .. }) => fields.iter().map(|f| &f.ty).collect(),
+        Data::Enum(syn::DataEnum { variants, .. }) => variants
+            .iter()
+            .flat_map(|variant| variant.fields.iter().map(|f| &f.ty))
+            .collect(),
+    };
+
+    quote! {
+        impl #impl_generics #krate::Visitable for #name #ty_generics #where_clause {
+            fn drive<V: #krate::Visitor>(&self, visitor: &mut V) {
+                visitor.visit(self, #krate::Event::Enter);
+                #drive_body
+                visitor.visit(self, #krate::Event::Exit);
+            }
+            fn drive_mut<V: #krate::VisitorMut>(&mut self, visitor: &mut V) {
+                visitor.visit(self, #krate::Event::Enter);
+                #drive_mut_body
+                visitor.visit(self, #krate::Event::Exit);
+            }
+            fn may_contain(
+                target: ::std::any::TypeId,
+                seen: &mut Vec<::std::any::TypeId>,
+            ) -> bool {
+                let this = ::std::any::TypeId::of::<Self>();
+                if target == this {
+                    return true;
+                }
+                if seen.contains(&this) {
+                    return false;
+                }
+                seen.push(this);
+                false #(|| <#field_types as #krate::Visitable>::may_contain(target, seen))*
+            }
+        }
+    }
+    .into()
+}
@@ -74,11 +74,23 @@ impl std::convert::From<String> for Namespace {
 }
 
 impl Namespace {
-    pub fn matches(&self, path: &Vec<String>) -> bool {
-        fn aux(pattern: &[NamespaceChunk], path: &[String]) -> bool {
+    pub fn matches(&self, path: &[String]) -> bool {
+        let path: Vec<_> = path.iter().map(|chunk| Some(chunk.as_str())).collect();
+        self.matches_chunks(&path)
+    }
+
+    /// Whether the path of a definition matches, where the path
+    /// chunks without a name (`None`) are only matched by globs.
+    pub fn matches_def_path(&self, path: &[Option<String>]) -> bool {
+        let path: Vec<_> = path.iter().map(Option::as_deref).collect();
+        self.matches_chunks(&path)
+    }
+
+    fn matches_chunks(&self, path: &[Option<&str>]) -> bool {
+        fn aux(pattern: &[NamespaceChunk], path: &[Option<&str>]) -> bool {
             match (pattern, path) {
                 ([], []) => true,
-                ([NamespaceChunk::Exact(x), pattern @ ..], [y, path @ ..]) => {
+                ([NamespaceChunk::Exact(x), pattern @ ..], [Some(y), path @ ..]) => {
                     x == y && aux(pattern, path)
                 }
                 ([NamespaceChunk::Glob(Glob::One), pattern @ ..], [_, path @ ..]) => {
@@ -91,11 +103,20 @@ impl Namespace {
                 _ => false,
             }
         }
-        aux(self.chunks.as_slice(), path.as_slice())
+        aux(self.chunks.as_slice(), path)
     }
 }
 
-#[derive(Debug, Clone)]
+/// The options of the exporter. New options may be added: build
+/// `Options` with `..Default::default()` to keep compiling when they
+/// are.
+#[derive(Debug, Clone, Default)]
 pub struct Options {
     pub inline_macro_calls: Vec<Namespace>,
+    /// Whether the borrow-checker facts of promoted MIR bodies are
+    /// exported.
+    pub borrowck_facts: bool,
+    /// Whether the layouts of ADTs are computed and exported (see
+    /// `AdtDef::layout`). Computing layouts can be costly.
+    pub layouts: bool,
 }
@@ -30,6 +30,12 @@ mod module {
 
     pub trait IsBody: Sized + Clone + 'static {
         fn body<'tcx, S: UnderOwnerState<'tcx>>(did: RLocalDefId, s: &S) -> Self;
+
+        /// Whether translating bodies requires MIR that Rustc steals
+        /// when it evaluates constants: if so, we must not evaluate
+        /// constants (see [`eval_global_value`]) before every body is
+        /// translated.
+        const STOLEN_BY_CONST_EVAL: bool = false;
     }
 
     pub fn make_fn_def<'tcx, Body: IsBody, S: UnderOwnerState<'tcx>>(
@@ -71,7 +77,7 @@ mod module {
         impl IsBody for ThirBody {
             fn body<'tcx, S: UnderOwnerState<'tcx>>(did: RLocalDefId, s: &S) -> Self {
                 let (thir, expr) = get_thir(did, s);
-                if *CORE_EXTRACTION_MODE {
+                if *CORE_EXTRACTION_MODE || s.base().dropped_bodies.contains(&did) {
                     let expr = &thir.exprs[expr];
                     Decorated {
                         contents: Box::new(ExprKind::Tuple { fields: vec![] }),
@@ -90,6 +96,7 @@ mod module {
             fn body<'tcx, S: UnderOwnerState<'tcx>>(did: RLocalDefId, s: &S) -> Self {
                 (A::body(did, s), B::body(did, s))
             }
+            const STOLEN_BY_CONST_EVAL: bool = A::STOLEN_BY_CONST_EVAL || B::STOLEN_BY_CONST_EVAL;
         }
 
         impl<MirKind: IsMirKind + Clone + 'static> IsBody for MirBody<MirKind> {
@@ -97,15 +104,16 @@ mod module {
                 let (thir, _) = get_thir(did, s);
                 let mir = MirKind::get_mir(s.base().tcx, did, |body| {
                     let body = Rc::new(body.clone());
-                    body.sinto(&with_owner_id(
-                        s.base(),
-                        thir,
-                        body.clone(),
-                        did.to_def_id(),
-                    ))
+                    let s = &with_owner_id(s.base(), thir, body.clone(), did.to_def_id());
+                    let mut mir: Self = body.sinto(s);
+                    if MirKind::BORROW_CHECKED && s.base().options.borrowck_facts {
+                        mir.borrowck_facts = BorrowckFacts::of(s, did);
+                    }
+                    mir
                 });
                 mir.s_unwrap(s)
             }
+            const STOLEN_BY_CONST_EVAL: bool = MirKind::STOLEN_BY_CONST_EVAL;
         }
     }
 
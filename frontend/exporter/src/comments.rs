@@ -30,20 +30,309 @@ pub fn comments_of_file(path: PathBuf) -> std::io::Result<Vec<(Span, String)>> {
         if lo.line != line {
             col = sub.chars().rev().take_while(|c| !matches!(c, '\n')).count();
         } else {
-            col += len;
+            col += sub.chars().count();
         }
 
         if let TokenKind::LineComment { .. } | TokenKind::BlockComment { .. } = token.kind {
             if !sub.starts_with("///") && !sub.starts_with("/**") {
-                let span = Span {
+                let span = Span::detached(SpanContents {
                     lo,
                     hi: Loc { line, col },
                     filename: FileName::Real(RealFileName::LocalPath(path.clone())),
                     rust_span_data: None,
-                };
+                });
                 comments.push((span, clean_comment(sub).to_string()));
             }
         }
     }
     Ok(comments)
 }
+
+/// The position of a span, for sorting spans by file then by start.
+fn position(span: &Span) -> (Option<&std::path::Path>, &Loc) {
+    (span.filename.to_path(), &span.lo)
+}
+
+/// Sorts spans by position, outermost first among spans starting at
+/// the same position.
+fn sort_spans<T>(spans: &mut [(Span, T)]) {
+    spans.sort_by(|(a, _), (b, _)| position(a).cmp(&position(b)).then_with(|| b.hi.cmp(&a.hi)))
+}
+
+/// For each span of `spans`, finds the index of the innermost
+/// candidate containing it, and the index of the first candidate that
+/// follows it within that innermost candidate (or within its file, if
+/// no candidate contains it). Both `spans` and `candidates` must be
+/// sorted with [`sort_spans`], and the spans of `candidates` are
+/// assumed to be well-nested.
+fn locate<T, U>(
+    spans: &[(Span, T)],
+    candidates: &[(Span, U)],
+) -> Vec<(Option<usize>, Option<usize>)> {
+    // The candidates containing the current position, outermost first
+    let mut stack: Vec<usize> = vec![];
+    // The first candidate that was not pushed onto `stack` yet
+    let mut next = 0;
+    spans
+        .iter()
+        .map(|(span, _)| {
+            while next < candidates.len() && position(&candidates[next].0) <= position(span) {
+                let candidate = &candidates[next].0;
+                while stack
+                    .last()
+                    .is_some_and(|&i| !candidates[i].0.contains(candidate))
+                {
+                    stack.pop();
+                }
+                stack.push(next);
+                next += 1;
+            }
+            while stack
+                .last()
+                .is_some_and(|&i| !candidates[i].0.contains(span))
+            {
+                stack.pop();
+            }
+            let container = stack.last().copied();
+            let following = next
+                + candidates[next..]
+                    .partition_point(|(c, _)| position(c) < (span.filename.to_path(), &span.hi));
+            let following = candidates
+                .get(following)
+                .filter(|(c, _)| c.filename.to_path() == span.filename.to_path())
+                .filter(|(c, _)| container.map_or(true, |i| candidates[i].0.contains(c)))
+                .map(|_| following);
+            (container, following)
+        })
+        .collect()
+}
+
+/// The items (including impl and trait items) and statements comments
+/// can be attached to, collected from exported items one by one.
+#[derive(Default)]
+pub struct CommentTargets {
+    owners: Vec<(Span, DefId)>,
+    statements: Vec<(Span, ())>,
+}
+
+impl CommentTargets {
+    /// Collects the items and statements of `item`.
+    pub fn add<Body: IsBody + visitors::Visitable>(&mut self, item: &Item<Body>) {
+        use visitors::Visitable;
+        let Self { owners, statements } = self;
+        owners.push((item.span.clone(), item.owner_id.clone()));
+        item.for_each(|item: &ImplItem<Body>| {
+            owners.push((item.span.clone(), item.owner_id.clone()))
+        });
+        item.for_each(|item: &TraitItem<Body>| {
+            owners.push((item.span.clone(), item.owner_id.clone()))
+        });
+        item.for_each(|stmt: &Stmt| statements.push((stmt.span(), ())));
+        item.for_each(|stmt: &Statement| statements.push((stmt.source_info.span.clone(), ())));
+    }
+
+    /// Associates comments (as given by [`comments_of_file`]) with the
+    /// collected items and, where possible, with their statements. A
+    /// comment is attached to the item that directly follows it within
+    /// the innermost item containing it, or else to that innermost
+    /// item. Comments that neither belong to nor precede any item are
+    /// dropped.
+    pub fn attach(self, comments: &[(Span, String)]) -> Vec<(DefId, Vec<AttachedComment>)> {
+        let Self {
+            mut owners,
+            mut statements,
+        } = self;
+        // Items of modules are visited twice
+        let mut seen = std::collections::HashSet::new();
+        owners.retain(|(_, def_id)| seen.insert(def_id.clone()));
+        sort_spans(&mut owners);
+        sort_spans(&mut statements);
+
+        // The lines of `comments_of_file` are 0-based, while the lines
+        // of rustc's spans are 1-based.
+        let mut comments: Vec<(Span, &String)> = comments
+            .iter()
+            .map(|(span, comment)| {
+                let shift = |loc: &Loc| Loc {
+                    line: loc.line + 1,
+                    col: loc.col,
+                };
+                let span = Span::detached(SpanContents {
+                    lo: shift(&span.lo),
+                    hi: shift(&span.hi),
+                    ..SpanContents::clone(span)
+                });
+                (span, comment)
+            })
+            .collect();
+        sort_spans(&mut comments);
+
+        // The statements of each owner, in order
+        let mut owner_statements: Vec<Vec<(Span, ())>> = vec![vec![]; owners.len()];
+        for ((span, ()), (container, _)) in statements.iter().zip(locate(&statements, &owners)) {
+            if let Some(owner) = container {
+                owner_statements[owner].push((span.clone(), ()));
+            }
+        }
+
+        // The comments attached to each owner, with whether they are
+        // inside of that owner
+        let mut owner_comments: Vec<Vec<((Span, &String), bool)>> = vec![vec![]; owners.len()];
+        for (comment, (container, following)) in comments.iter().zip(locate(&comments, &owners)) {
+            if let Some(owner) = following.or(container) {
+                owner_comments[owner].push((comment.clone(), following.is_none()));
+            }
+        }
+
+        owners
+            .into_iter()
+            .zip(owner_comments)
+            .zip(owner_statements)
+            .filter(|((_, comments), _)| !comments.is_empty())
+            .map(|(((_, def_id), comments), statements)| {
+                let inner: Vec<_> = comments
+                    .iter()
+                    .filter(|(_, inside)| *inside)
+                    .map(|(comment, _)| comment.clone())
+                    .collect();
+                let mut inner_statements = locate(&inner, &statements).into_iter();
+                let comments = comments
+                    .into_iter()
+                    .map(|((span, comment), inside)| {
+                        let statement = if inside {
+                            let (container, following) = inner_statements.next().unwrap();
+                            following.or(container).map(|i| statements[i].0.clone())
+                        } else {
+                            None
+                        };
+                        AttachedComment {
+                            span,
+                            comment: comment.clone(),
+                            statement,
+                        }
+                    })
+                    .collect();
+                (def_id, comments)
+            })
+            .collect()
+    }
+}
+
+/// Associates comments with the items (including impl and trait
+/// items) of `items`, and, where possible, with statements of those
+/// items, see [`CommentTargets::attach`].
+pub fn attach_comments<Body: IsBody + visitors::Visitable>(
+    items: &[Item<Body>],
+    comments: &[(Span, String)],
+) -> Vec<(DefId, Vec<AttachedComment>)> {
+    let mut targets = CommentTargets::default();
+    for item in items {
+        targets.add(item);
+    }
+    targets.attach(comments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(lo: (usize, usize), hi: (usize, usize)) -> Span {
+        Span::detached(SpanContents {
+            lo: Loc {
+                line: lo.0,
+                col: lo.1,
+            },
+            hi: Loc {
+                line: hi.0,
+                col: hi.1,
+            },
+            filename: FileName::Real(RealFileName::LocalPath("lib.rs".into())),
+            rust_span_data: None,
+        })
+    }
+
+    fn def_id(index: u32) -> DefId {
+        DefId {
+            contents: id_table::Node::detached(DefIdContents {
+                krate: "krate".into(),
+                path: vec![],
+                parent: None,
+                index: (0, index),
+                is_local: true,
+            }),
+        }
+    }
+
+    #[test]
+    fn comments_of_file_positions() {
+        let path = std::env::temp_dir().join(format!("hax-comments-{}.rs", std::process::id()));
+        fs::write(&path, "fn f() {}\n// é\n  /* é */ fn g() {}\n").unwrap();
+        let comments = comments_of_file(path.clone()).unwrap();
+        fs::remove_file(path).unwrap();
+        let positions: Vec<_> = comments
+            .iter()
+            .map(|(span, comment)| {
+                (
+                    (span.lo.line, span.lo.col),
+                    (span.hi.line, span.hi.col),
+                    comment.as_str(),
+                )
+            })
+            .collect();
+        // Lines are 0-based, columns count characters
+        assert_eq!(
+            positions,
+            vec![((1, 0), (1, 4), " é"), ((2, 2), (2, 9), " é ")]
+        );
+    }
+
+    #[test]
+    fn attach_to_following_or_enclosing_item() {
+        let mut targets = CommentTargets::default();
+        // mod m { fn f() { s1; s2; } fn g() {} }
+        targets.owners = vec![
+            (span((1, 0), (10, 1)), def_id(0)),
+            (span((3, 4), (6, 5)), def_id(1)),
+            (span((8, 4), (8, 14)), def_id(2)),
+        ];
+        targets.statements = vec![(span((4, 8), (4, 11)), ()), (span((5, 8), (5, 11)), ())];
+        // The lines of comments are 0-based
+        let comments: Vec<_> = [
+            (span((1, 4), (1, 10)), "before f"),
+            (span((2, 12), (2, 20)), "before s1"),
+            (span((5, 0), (5, 4)), "end of f"),
+            (span((6, 4), (6, 10)), "before g"),
+            (span((8, 4), (8, 10)), "end of m"),
+            (span((10, 0), (10, 6)), "after m"),
+        ]
+        .into_iter()
+        .map(|(span, comment)| (span, comment.to_string()))
+        .collect();
+        let attached: Vec<_> = targets
+            .attach(&comments)
+            .into_iter()
+            .map(|(def_id, comments)| {
+                let comments: Vec<_> = comments
+                    .into_iter()
+                    .map(|c| (c.comment, c.statement.map(|s| s.lo.line)))
+                    .collect();
+                (def_id.index.1, comments)
+            })
+            .collect();
+        assert_eq!(
+            attached,
+            vec![
+                (0, vec![("end of m".to_string(), None)]),
+                (
+                    1,
+                    vec![
+                        ("before f".to_string(), None),
+                        ("before s1".to_string(), Some(4)),
+                        ("end of f".to_string(), None),
+                    ]
+                ),
+                (2, vec![("before g".to_string(), None)]),
+            ]
+        );
+    }
+}
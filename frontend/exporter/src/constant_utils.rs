@@ -1,6 +1,6 @@
 use crate::prelude::*;
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ConstantInt {
     Int(
@@ -17,7 +17,7 @@ pub enum ConstantInt {
     ),
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ConstantLiteral {
     Bool(bool),
@@ -29,7 +29,7 @@ pub enum ConstantLiteral {
 }
 
 /// The subset of [Expr] that corresponds to constants.
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ConstantExprKind {
     Literal(ConstantLiteral),
@@ -107,7 +107,7 @@ pub enum ConstantExprKind {
     Todo(String),
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct ConstantFieldExpr {
     pub field: DefId,
@@ -275,6 +275,9 @@ mod rustc {
         ConstantLiteral::Float(string, ty)
     }
 
+    /// Translates a scalar of type `ty`. Scalars we cannot represent
+    /// (e.g. pointers to functions or vtables) are translated as
+    /// `ConstantExprKind::Todo`.
     #[tracing::instrument(level = "trace", skip(s))]
     pub(crate) fn scalar_to_constant_expr<'tcx, S: UnderOwnerState<'tcx>>(
         s: &S,
@@ -284,42 +287,45 @@ mod rustc {
     ) -> ConstantExpr {
         use rustc_middle::mir::Mutability;
         let cspan = span.sinto(s);
+        let todo =
+            |message: String| ConstantExprKind::Todo(message).decorate(ty.sinto(s), cspan.clone());
         // The documentation explicitly says not to match on a scalar.
         // We match on the type and use it to convert the value.
         let kind = match ty.kind() {
             ty::Char | ty::Bool | ty::Int(_) | ty::Uint(_) => {
-                let scalar_int = scalar.try_to_scalar_int().unwrap_or_else(|_| {
-                    fatal!(
-                        s[span],
+                let Ok(scalar_int) = scalar.try_to_scalar_int() else {
+                    return todo(format!(
                         "Type is primitive, but the scalar {:#?} is not an [Int]",
                         scalar
-                    )
-                });
+                    ));
+                };
                 ConstantExprKind::Literal(scalar_int_to_constant_literal(s, scalar_int, ty))
             }
             ty::Float(float_type) => {
-                let scalar_int = scalar.try_to_scalar_int().unwrap_or_else(|_| {
-                    fatal!(
-                        s[span],
+                let Ok(scalar_int) = scalar.try_to_scalar_int() else {
+                    return todo(format!(
                         "Type is [Float], but the scalar {:#?} is not a number",
                         scalar
-                    )
-                });
+                    ));
+                };
                 let data = scalar_int.to_bits_unchecked();
                 let lit = bits_and_type_to_float_constant_literal(data, float_type.sinto(s));
                 ConstantExprKind::Literal(lit)
             }
             ty::Ref(_, inner_ty, Mutability::Not) | ty::RawPtr(inner_ty, Mutability::Mut) => {
                 let tcx = s.base().tcx;
-                let pointer = scalar.to_pointer(&tcx).unwrap_or_else(|_| {
-                    fatal!(
-                        s[span],
+                let Some(provenance) = scalar
+                    .to_pointer(&tcx)
+                    .discard_err()
+                    .and_then(|pointer| pointer.provenance)
+                else {
+                    return todo(format!(
                         "Type is [Ref] or [RawPtr], but the scalar {:#?} is not a [Pointer]",
                         scalar
-                    )
-                });
+                    ));
+                };
                 use rustc_middle::mir::interpret::GlobalAlloc;
-                let contents = match tcx.global_alloc(pointer.provenance.s_unwrap(s).alloc_id()) {
+                let contents = match tcx.global_alloc(provenance.alloc_id()) {
                     GlobalAlloc::Static(did) => ConstantExprKind::GlobalName {
                         id: did.sinto(s),
                         generics: Vec::new(),
@@ -338,12 +344,13 @@ mod rustc {
                             StrStyle::Cooked,
                         ))
                     }
-                    provenance => fatal!(
-                        s[span],
-                        "Expected provenance to be `GlobalAlloc::Static` or \
-                        `GlobalAlloc::Memory`, got {:#?} instead",
-                        provenance
-                    ),
+                    provenance => {
+                        return todo(format!(
+                            "Expected provenance to be `GlobalAlloc::Static` or \
+                            `GlobalAlloc::Memory`, got {:#?} instead",
+                            provenance
+                        ))
+                    }
                 };
                 let contents = contents.decorate(inner_ty.sinto(s), cspan.clone());
                 match ty.kind() {
@@ -358,35 +365,20 @@ mod rustc {
             // A [Scalar] might also be any zero-sized [Adt] or [Tuple] (i.e., unit)
             ty::Tuple(ty) if ty.is_empty() => ConstantExprKind::Tuple { fields: vec![] },
             // It seems we can have ADTs when there is only one variant, and this variant doesn't have any fields.
-            ty::Adt(def, _) => {
-                if let [variant_def] = &def.variants().raw {
-                    if variant_def.fields.is_empty() {
-                        ConstantExprKind::Adt {
-                            info: get_variant_information(def, rustc_target::abi::FIRST_VARIANT, s),
-                            fields: vec![],
-                        }
-                    } else {
-                        fatal!(
-                            s[span],
-                            "Unexpected type `ty` for scalar `scalar`. Case `ty::Adt(def, _)`: \
-                            `variant_def.fields` was not empty";
-                            {ty, scalar, def, variant_def}
-                        )
-                    }
-                } else {
-                    fatal!(
-                        s[span],
-                        "Unexpected type `ty` for scalar `scalar`. Case `ty::Adt(def, _)`: \
-                        `def.variants().raw` was supposed to contain exactly one variant.";
-                        {ty, scalar, def, &def.variants().raw}
-                    )
+            ty::Adt(def, _) => match &def.variants().raw[..] {
+                [variant_def] if variant_def.fields.is_empty() => ConstantExprKind::Adt {
+                    info: get_variant_information(def, rustc_target::abi::FIRST_VARIANT, s),
+                    fields: vec![],
+                },
+                _ => {
+                    return todo(format!(
+                        "Unexpected type {:?} for scalar {:?}: expected a single variant \
+                        without fields",
+                        ty, scalar
+                    ))
                 }
-            }
-            _ => fatal!(
-                s[span],
-                "Unexpected type `ty` for scalar `scalar`";
-                {ty, scalar}
-            ),
+            },
+            _ => return todo(format!("Unexpected type {:?} for scalar {:?}", ty, scalar)),
         };
         kind.decorate(ty.sinto(s), cspan)
     }
@@ -524,6 +516,10 @@ mod rustc {
         #[tracing::instrument(level = "trace", skip(s))]
         fn sinto(&self, s: &S) -> ConstantExpr {
             use rustc_middle::query::Key;
+            let instantiated = instantiate_mono(s, *self);
+            if instantiated != *self {
+                return instantiated.sinto(s);
+            }
             let span = self.default_span(s.base().tcx);
             match self.kind() {
                 ty::ConstKind::Param(p) => {
@@ -647,9 +643,12 @@ mod rustc {
             .copied()
             .map(|(val, ty)| const_value_to_constant_expr(s, ty, val, span));
 
-        // The type should be tuple
+        // The type should be tuple, array or adt
         let hax_ty: Ty = ty.sinto(s);
         match ty.kind() {
+            ty::TyKind::Array(..) => ConstantExprKind::Array {
+                fields: fields.collect(),
+            },
             ty::TyKind::Tuple(_) => {
                 assert!(dc.variant.is_none());
                 let fields = fields.collect();
@@ -671,12 +670,84 @@ mod rustc {
                 }
             }
             _ => {
-                fatal!(s[span], "Expected the type to be tuple or adt: {:?}", val)
+                fatal!(
+                    s[span],
+                    "Expected the type to be tuple, array or adt: {:?}",
+                    val
+                )
             }
         }
         .decorate(hax_ty, span.sinto(s))
     }
 
+    /// Whether `ty` is `&str` or `&[u8]`.
+    fn is_str_or_byte_str_ref(ty: ty::Ty) -> bool {
+        match ty.kind() {
+            ty::Ref(_, inner, _) => match inner.kind() {
+                ty::Str => true,
+                ty::Slice(elem) => matches!(elem.kind(), ty::Uint(ty::UintTy::U8)),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Whether [`const_value_to_constant_expr`] can translate values of
+    /// type `ty`: primitive types, string and byte string references,
+    /// and arrays, tuples, structs and enums made of those.
+    fn is_translatable_const_ty<'tcx>(tcx: ty::TyCtxt<'tcx>, ty: ty::Ty<'tcx>) -> bool {
+        match ty.kind() {
+            ty::Bool | ty::Char | ty::Int(_) | ty::Uint(_) | ty::Float(_) => true,
+            ty::Ref(_, _, rustc_middle::mir::Mutability::Not) => is_str_or_byte_str_ref(ty),
+            ty::Array(elem, _) => is_translatable_const_ty(tcx, *elem),
+            ty::Tuple(tys) => tys.iter().all(|ty| is_translatable_const_ty(tcx, ty)),
+            ty::Adt(def, args) if def.is_struct() || def.is_enum() => def
+                .all_fields()
+                .all(|field| is_translatable_const_ty(tcx, field.ty(tcx, args))),
+            _ => false,
+        }
+    }
+
+    /// The value of the `const` or `static` item `def_id`, as
+    /// evaluated by Rustc. This is `None` when the item is generic,
+    /// when its evaluation fails, or when its type contains values
+    /// hax cannot represent as a [`ConstantExpr`] (e.g. pointers).
+    /// Unexpected parts of a value are translated as
+    /// `ConstantExprKind::Todo` rather than aborting the export.
+    /// This is also `None` when the bodies being exported are MIR
+    /// bodies that the evaluation would steal (see
+    /// [`IsBody::STOLEN_BY_CONST_EVAL`]).
+    pub fn eval_global_value<'tcx, Body: IsBody, S: UnderOwnerState<'tcx>>(
+        s: &S,
+        def_id: RDefId,
+    ) -> Option<ConstantExpr> {
+        use rustc_middle::mir::ConstValue;
+        let tcx = s.base().tcx;
+        let ty = tcx.type_of(def_id).instantiate_identity();
+        if Body::STOLEN_BY_CONST_EVAL
+            || tcx.generics_of(def_id).requires_monomorphization(tcx)
+            || tcx.is_foreign_item(def_id)
+            || !is_translatable_const_ty(tcx, ty)
+        {
+            return None;
+        }
+        let val = if tcx.is_static(def_id) {
+            let alloc = tcx.eval_static_initializer(def_id).ok()?;
+            ConstValue::Indirect {
+                alloc_id: tcx.reserve_and_set_memory_alloc(alloc),
+                offset: rustc_abi::Size::ZERO,
+            }
+        } else {
+            tcx.const_eval_poly(def_id).ok()?
+        };
+        Some(const_value_to_constant_expr(
+            s,
+            ty,
+            val,
+            tcx.def_span(def_id),
+        ))
+    }
+
     pub fn const_value_to_constant_expr<'tcx, S: UnderOwnerState<'tcx>>(
         s: &S,
         ty: rustc_middle::ty::Ty<'tcx>,
@@ -684,8 +755,65 @@ mod rustc {
         span: rustc_span::Span,
     ) -> ConstantExpr {
         use rustc_middle::mir::ConstValue;
+        let is_aggregate = matches!(ty.kind(), ty::Array(..) | ty::Tuple(..) | ty::Adt(..));
         match val {
+            // Aggregates might be represented as scalars, e.g. a
+            // `struct W(u32)` or a C-like enum.
+            ConstValue::Scalar(_) if is_aggregate && !ty.is_unit() => {
+                const_value_reference_to_constant_expr(s, ty, val, span)
+            }
             ConstValue::Scalar(scalar) => scalar_to_constant_expr(s, ty, &scalar, span),
+            ConstValue::Indirect { alloc_id, offset } if ty.is_scalar() => {
+                let tcx = s.base().tcx;
+                let size = tcx.layout_of(s.param_env().and(ty)).s_unwrap(s).size;
+                let range = rustc_middle::mir::interpret::AllocRange {
+                    start: offset,
+                    size,
+                };
+                let scalar = tcx
+                    .global_alloc(alloc_id)
+                    .unwrap_memory()
+                    .inner()
+                    .read_scalar(&tcx, range, false)
+                    .s_unwrap(s);
+                scalar_to_constant_expr(s, ty, &scalar, span)
+            }
+            // A wide reference to a string or a byte string.
+            ConstValue::Indirect { alloc_id, offset } if is_str_or_byte_str_ref(ty) => {
+                let tcx = s.base().tcx;
+                let alloc = tcx.global_alloc(alloc_id).unwrap_memory();
+                let ptr_size = tcx.data_layout.pointer_size;
+                let read = |start, read_provenance| {
+                    let range = rustc_middle::mir::interpret::AllocRange {
+                        start,
+                        size: ptr_size,
+                    };
+                    alloc
+                        .inner()
+                        .read_scalar(&tcx, range, read_provenance)
+                        .s_unwrap(s)
+                };
+                let (provenance, start) = read(offset, true)
+                    .to_pointer(&tcx)
+                    .discard_err()
+                    .s_unwrap(s)
+                    .into_parts();
+                let len = read(offset + ptr_size, false)
+                    .to_target_usize(&tcx)
+                    .discard_err()
+                    .s_unwrap(s);
+                let start = start.bytes_usize();
+                let data = tcx.global_alloc(provenance.s_unwrap(s).alloc_id());
+                let slice: &[u8] = data
+                    .unwrap_memory()
+                    .inner()
+                    .inspect_with_uninit_and_ptr_outside_interpreter(start..start + len as usize);
+                ConstantExprKind::Literal(ConstantLiteral::byte_str(
+                    slice.to_vec(),
+                    StrStyle::Cooked,
+                ))
+                .decorate(ty.sinto(s), span.sinto(s))
+            }
             ConstValue::Indirect { .. } => const_value_reference_to_constant_expr(s, ty, val, span),
             ConstValue::Slice { data, meta } => {
                 let end = meta.try_into().unwrap();
@@ -721,8 +849,7 @@ mod rustc {
                             method_impl,
                         }
                     }
-                    ty::TyKind::Adt(adt_def, ..) => {
-                        assert_eq!(adt_def.variants().len(), 1);
+                    ty::TyKind::Adt(adt_def, ..) if adt_def.variants().len() == 1 => {
                         let variant = rustc_target::abi::FIRST_VARIANT;
                         let variants_info = get_variant_information(adt_def, variant, s);
                         ConstantExprKind::Adt {
@@ -730,13 +857,10 @@ mod rustc {
                             fields: vec![],
                         }
                     }
-                    _ => {
-                        fatal!(
-                            s[span],
-                            "Expected the type to be tuple or arrow";
-                            {val, ty}
-                        )
-                    }
+                    _ => ConstantExprKind::Todo(format!(
+                        "Unexpected type {:?} for a zero-sized value {:?}",
+                        ty, val
+                    )),
                 };
 
                 cv.decorate(hty, span.sinto(s))
@@ -63,3 +63,29 @@ impl<T: Hasher> core::hash::Hasher for DeterministicHasher<T> {
         self.write_usize(i as usize)
     }
 }
+
+/// The 64-bit FNV-1a hash function, specified at
+/// <http://www.isthe.com/chongo/tech/comp/fnv/>. Unlike the algorithm
+/// of `std`'s `DefaultHasher`, which is unspecified and may change
+/// between Rust releases, its output is stable: use it (wrapped in a
+/// [`DeterministicHasher`]) for hashes that are persisted.
+pub struct Fnv1a64(u64);
+
+impl Default for Fnv1a64 {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl core::hash::Hasher for Fnv1a64 {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
@@ -13,17 +13,23 @@
 ///
 /// Serde is not designed for stateful (de)serialization. There is no
 /// way of deriving `serde::de::DeserializeSeed` systematically. This
-/// module thus makes use of global state to achieve serialization and
-/// deserialization. This modules provides an API that hides this
-/// global state.
+/// module thus makes use of thread-local state to achieve
+/// serialization and deserialization. That state is scoped: it is
+/// set for the duration of a (de)serialization of a `WithTable<T>`
+/// only, and restored afterwards. Serde (de)serializes on the calling
+/// thread, thus several tables can be (de)serialized in parallel from
+/// different threads, or nested on one thread. This modules provides
+/// an API that hides this state.
 use crate::prelude::*;
 use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
     hash::{Hash, Hasher},
-    sync::{atomic::Ordering, Arc, LazyLock, Mutex, MutexGuard},
+    sync::Arc,
 };
 
 /// Unique IDs in a ID table.
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Default, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(transparent)]
 pub struct Id {
@@ -34,13 +40,31 @@ pub struct Id {
 #[derive(Default, Debug)]
 pub struct Session {
     next_id: Id,
+    /// The first ID not yet returned by `Session::new_entries`.
+    next_new_entry: Id,
     table: Table,
+    /// The ids of the values added with `Node::interned`, by hash.
+    interned: HashMap<u64, Vec<Id>>,
 }
 
 impl Session {
     pub fn table(&self) -> &Table {
         &self.table
     }
+
+    /// The entries of the table created since the previous call to
+    /// `new_entries`. This allows for serializing a stream of
+    /// `WithTable<T>` chunks that share the table of the session, see
+    /// [`Table::read_chunk`].
+    pub fn new_entries(&mut self) -> Table {
+        let ids = self.next_new_entry.id..self.next_id.id;
+        self.next_new_entry = self.next_id.clone();
+        Table(HeterogeneousMap::from_iter(ids.filter_map(|id| {
+            let id = Id { id };
+            let value = self.table.0.get_raw(&id)?.clone();
+            Some((id, value))
+        })))
+    }
 }
 
 /// The different types of values one can store in an ID table.
@@ -48,34 +72,37 @@ impl Session {
 pub enum Value {
     Ty(Arc<TyKind>),
     DefId(Arc<DefIdContents>),
+    Span(Arc<SpanContents>),
+    ImplExpr(Arc<ImplExprContents>),
+    GenericPredicates(Arc<GenericPredicatesContents>),
 }
 
-impl SupportedType<Value> for TyKind {
-    fn to_types(value: Arc<Self>) -> Value {
-        Value::Ty(value)
-    }
-    fn from_types(t: &Value) -> Option<Arc<Self>> {
-        match t {
-            Value::Ty(value) => Some(value.clone()),
-            _ => None,
+macro_rules! supported_types {
+    ($($variant:ident($ty:ty)),*) => {$(
+        impl SupportedType<Value> for $ty {
+            fn to_types(value: Arc<Self>) -> Value {
+                Value::$variant(value)
+            }
+            fn from_types(t: &Value) -> Option<Arc<Self>> {
+                match t {
+                    Value::$variant(value) => Some(value.clone()),
+                    _ => None,
+                }
+            }
         }
-    }
+    )*};
 }
 
-impl SupportedType<Value> for DefIdContents {
-    fn to_types(value: Arc<Self>) -> Value {
-        Value::DefId(value)
-    }
-    fn from_types(t: &Value) -> Option<Arc<Self>> {
-        match t {
-            Value::DefId(value) => Some(value.clone()),
-            _ => None,
-        }
-    }
-}
+supported_types!(
+    Ty(TyKind),
+    DefId(DefIdContents),
+    Span(SpanContents),
+    ImplExpr(ImplExprContents),
+    GenericPredicates(GenericPredicatesContents)
+);
 
 /// A node is a bundle of an ID with a value.
-#[derive(Deserialize, Serialize, Debug, JsonSchema, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Deserialize, Serialize, Debug, JsonSchema)]
 #[serde(into = "serde_repr::NodeRepr<T>")]
 #[serde(try_from = "serde_repr::NodeRepr<T>")]
 pub struct Node<T: 'static + SupportedType<Value>> {
@@ -99,6 +126,25 @@ impl<T: SupportedType<Value> + Hash> Hash for Node<T> {
     }
 }
 
+/// Likewise, two nodes with equal values are equal, whatever their
+/// identifiers.
+impl<T: SupportedType<Value> + PartialEq> PartialEq for Node<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.value, &other.value) || self.value == other.value
+    }
+}
+impl<T: SupportedType<Value> + Eq> Eq for Node<T> {}
+impl<T: SupportedType<Value> + PartialOrd> PartialOrd for Node<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+impl<T: SupportedType<Value> + Ord> Ord for Node<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
 /// Manual implementation of `Clone` that doesn't require a `Clone`
 /// bound on `T`.
 impl<T: SupportedType<Value>> Clone for Node<T> {
@@ -110,6 +156,22 @@ impl<T: SupportedType<Value>> Clone for Node<T> {
     }
 }
 
+/// Mutable visits of a `Node` clone its value if it is shared and may
+/// contain the target of the visitor (see
+/// [`visitors::VisitorMut::target`]): the rewritten value is not
+/// reflected in any table.
+impl<T: SupportedType<Value> + visitors::Visitable> visitors::Visitable for Node<T> {
+    fn drive<V: visitors::Visitor>(&self, visitor: &mut V) {
+        self.value.drive(visitor)
+    }
+    fn drive_mut<V: visitors::VisitorMut>(&mut self, visitor: &mut V) {
+        self.value.drive_mut(visitor)
+    }
+    fn may_contain(target: std::any::TypeId, seen: &mut Vec<std::any::TypeId>) -> bool {
+        T::may_contain(target, seen)
+    }
+}
+
 /// A table is a map from IDs to `Value`s. When serialized, we
 /// represent a table as a *sorted* vector. Indeed, the values stored
 /// in the table might reference each other, without cycle, so the
@@ -151,6 +213,9 @@ mod heterogeneous_map {
         pub(super) fn from_iter(it: impl Iterator<Item = (Key, Value)>) -> Self {
             Self(HashMap::from_iter(it))
         }
+        pub(super) fn get_raw(&self, key: &Key) -> Option<&Value> {
+            self.0.get(key)
+        }
         pub(super) fn into_iter(self) -> impl Iterator<Item = (Key, Value)> {
             self.0.into_iter()
         }
@@ -171,6 +236,11 @@ mod heterogeneous_map {
 }
 use heterogeneous_map::*;
 
+impl Id {
+    /// The identifier of the nodes that belong to no table.
+    const DETACHED: Id = Id { id: u32::MAX };
+}
+
 impl Session {
     fn fresh_id(&mut self) -> Id {
         let id = self.next_id.id;
@@ -180,6 +250,7 @@ impl Session {
 }
 
 impl<T: Sync + Send + 'static + SupportedType<Value>> Node<T> {
+    /// Creates a node for `value`, with a fresh id.
     pub fn new(value: T, session: &mut Session) -> Self {
         let id = session.fresh_id();
         let value = Arc::new(value);
@@ -187,6 +258,48 @@ impl<T: Sync + Send + 'static + SupportedType<Value>> Node<T> {
         Self { id, value }
     }
 
+    /// Creates a node for `value`, or returns the node of `session`
+    /// that holds a value equal to `value`, if it was created by
+    /// `interned` as well. Use it for values that are not
+    /// deduplicated by a cache of their Rustc counterparts.
+    pub fn interned(value: T, session: &mut Session) -> Self
+    where
+        T: Hash + Eq,
+    {
+        let hash = {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        };
+        for id in session.interned.get(&hash).into_iter().flatten() {
+            if let Some(Some(interned)) = session.table.0.get::<T>(id) {
+                if *interned == value {
+                    return Self {
+                        id: id.clone(),
+                        value: interned,
+                    };
+                }
+            }
+        }
+        let node = Self::new(value, session);
+        session
+            .interned
+            .entry(hash)
+            .or_default()
+            .push(node.id.clone());
+        node
+    }
+
+    /// Creates a node outside of any session, e.g. for values
+    /// computed before the translation starts. Such a node has no
+    /// entry in any table: it is always serialized with its value.
+    pub fn detached(value: T) -> Self {
+        Self {
+            id: Id::DETACHED,
+            value: Arc::new(value),
+        }
+    }
+
     pub fn inner(&self) -> &Arc<T> {
         &self.value
     }
@@ -203,31 +316,56 @@ pub struct WithTable<T> {
     value: T,
 }
 
-/// The state used for deserialization: a table.
-static DESERIALIZATION_STATE: LazyLock<Mutex<Table>> =
-    LazyLock::new(|| Mutex::new(Table::default()));
-static DESERIALIZATION_STATE_LOCK: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+thread_local! {
+    /// The state used for deserialization: the table of the
+    /// `WithTable<T>` being deserialized on this thread.
+    static DESERIALIZATION_STATE: RefCell<Table> = RefCell::new(Table::default());
 
-/// The mode of serialization: should `Node<T>` ship values of type `T` or not?
-static SERIALIZATION_MODE_USE_IDS: std::sync::atomic::AtomicBool =
-    std::sync::atomic::AtomicBool::new(false);
+    /// The mode of serialization on this thread: should `Node<T>`
+    /// ship values of type `T` or not?
+    static SERIALIZATION_MODE_USE_IDS: Cell<bool> = const { Cell::new(false) };
+}
 
 fn serialize_use_id() -> bool {
-    SERIALIZATION_MODE_USE_IDS.load(Ordering::Relaxed)
+    SERIALIZATION_MODE_USE_IDS.get()
+}
+
+/// Runs `f` with the serialization mode set to `use_ids`, restoring
+/// the previous mode afterwards (even if `f` panics).
+fn with_serialization_mode<R>(use_ids: bool, f: impl FnOnce() -> R) -> R {
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SERIALIZATION_MODE_USE_IDS.set(self.0)
+        }
+    }
+    let _restore = Restore(SERIALIZATION_MODE_USE_IDS.replace(use_ids));
+    f()
+}
+
+/// Runs `f` with `table` as deserialization table, restoring the
+/// previous table afterwards (even if `f` panics). Returns the
+/// deserialization table as `f` left it.
+fn with_deserialization_state<R>(table: Table, f: impl FnOnce() -> R) -> (R, Table) {
+    struct Restore(Table);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            DESERIALIZATION_STATE.set(std::mem::take(&mut self.0))
+        }
+    }
+    let _restore = Restore(DESERIALIZATION_STATE.replace(table));
+    let result = f();
+    (result, DESERIALIZATION_STATE.take())
 }
 
 impl<T> WithTable<T> {
     /// Runs `f` with a `WithTable<T>` created out of `map` and
     /// `value`. Any serialization of values of type `Node<_>` will
-    /// skip the field `value`.
+    /// skip the field `value`, as long as it happens on the current
+    /// thread and within `f`.
     pub fn run<R>(map: Table, value: T, f: impl FnOnce(&Self) -> R) -> R {
-        if serialize_use_id() {
-            panic!("CACHE_MAP_LOCK: only one WithTable serialization can occur at a time (nesting is forbidden)")
-        }
-        SERIALIZATION_MODE_USE_IDS.store(true, Ordering::Relaxed);
-        let result = f(&Self { table: map, value });
-        SERIALIZATION_MODE_USE_IDS.store(false, Ordering::Relaxed);
-        result
+        let with_table = Self { table: map, value };
+        with_serialization_mode(true, || f(&with_table))
     }
     pub fn destruct(self) -> (T, Table) {
         let Self { value, table: map } = self;
@@ -235,6 +373,20 @@ impl<T> WithTable<T> {
     }
 }
 
+/// Wrapper for a value `T` whose `Node<_>`s are always serialized
+/// with their values, even under `WithTable::run`. This allows for
+/// bundling, in a `WithTable<_>`, values whose nodes come from
+/// another table.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(transparent)]
+pub struct Inlined<T>(pub T);
+
+impl<T: Serialize> Serialize for Inlined<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        with_serialization_mode(false, || self.0.serialize(serializer))
+    }
+}
+
 impl<T: Serialize> Serialize for WithTable<T> {
     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         let mut ts = serializer.serialize_tuple_struct("WithTable", 2)?;
@@ -247,24 +399,59 @@ impl<T: Serialize> Serialize for WithTable<T> {
 
 /// The deserializer of `WithTable<T>` is special. We first decode the
 /// table in order: each `(Id, Value)` pair of the table populates the
-/// global table state found in `DESERIALIZATION_STATE`. Only then we
-/// can decode the value itself, knowing `DESERIALIZATION_STATE` is
-/// complete.
+/// thread-local table state found in `DESERIALIZATION_STATE`. Only
+/// then we can decode the value itself, knowing
+/// `DESERIALIZATION_STATE` is complete.
 impl<'de, T: Deserialize<'de>> serde::Deserialize<'de> for WithTable<T> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        let _lock: MutexGuard<_> = DESERIALIZATION_STATE_LOCK.try_lock().expect("CACHE_MAP_LOCK: only one WithTable deserialization can occur at a time (nesting is forbidden)");
         use serde_repr::WithTableRepr;
-        let previous = std::mem::take(&mut *DESERIALIZATION_STATE.lock().unwrap());
-        let with_table_repr = WithTableRepr::deserialize(deserializer);
-        *DESERIALIZATION_STATE.lock().unwrap() = previous;
-        let WithTableRepr(table, value) = with_table_repr?;
+        let (result, _) = with_deserialization_state(Table::default(), || {
+            WithTableRepr::deserialize(deserializer)
+        });
+        let WithTableRepr(table, value) = result?;
         Ok(Self { table, value })
     }
 }
 
+/// A chunk of a stream of `WithTable<T>` values that share one table:
+/// the table of each chunk holds only the entries that are new since
+/// the previous chunk (see [`Session::new_entries`]), and its value
+/// may refer to the entries of previous chunks. A chunk is serialized
+/// as a `WithTable<T>`, and must be deserialized by
+/// [`Table::read_chunk`].
+#[derive(Debug)]
+pub struct Chunk<T>(Table, T);
+
+impl<'de, T: Deserialize<'de>> serde::Deserialize<'de> for Chunk<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let serde_repr::WithTableRepr(table, value) =
+            serde_repr::WithTableRepr::deserialize(deserializer)?;
+        Ok(Self(table, value))
+    }
+}
+
+impl Table {
+    /// Reads a chunk with `deserialize`, given the table `self` of
+    /// the previous chunks of the stream. The entries of the chunk
+    /// are added to `self`. Returns the value of the chunk and its
+    /// entries.
+    pub fn read_chunk<T, E>(
+        &mut self,
+        deserialize: impl FnOnce() -> Result<Chunk<T>, E>,
+    ) -> Result<(T, Table), E> {
+        let (result, table) = with_deserialization_state(std::mem::take(self), deserialize);
+        *self = table;
+        let Chunk(entries, value) = result?;
+        Ok((value, entries))
+    }
+}
+
 /// Defines representations for various types when serializing or/and
 /// deserializing via serde
 mod serde_repr {
@@ -285,7 +472,7 @@ mod serde_repr {
 
     impl<T: SupportedType<Value>> Into<NodeRepr<T>> for Node<T> {
         fn into(self) -> NodeRepr<T> {
-            let value = if serialize_use_id() {
+            let value = if serialize_use_id() && self.id != Id::DETACHED {
                 None
             } else {
                 Some(self.value.clone())
@@ -300,14 +487,12 @@ mod serde_repr {
 
         fn try_from(cached: NodeRepr<T>) -> Result<Self, Self::Error> {
             use serde::de::Error;
-            let table = DESERIALIZATION_STATE.lock().unwrap();
             let id = cached.id;
             let kind = if let Some(kind) = cached.value {
                 kind
             } else {
-                table
-                    .0
-                    .get(&id)
+                DESERIALIZATION_STATE
+                    .with_borrow(|table| table.0.get(&id))
                     .ok_or_else(|| {
                         Self::Error::custom(&format!(
                             "Stateful deserialization failed for id {:?}: not found in cache",
@@ -332,10 +517,7 @@ mod serde_repr {
         {
             let (id, v) = <(Id, Value)>::deserialize(deserializer)?;
             DESERIALIZATION_STATE
-                .lock()
-                .unwrap()
-                .0
-                .insert_raw_value(id.clone(), v.clone());
+                .with_borrow_mut(|table| table.0.insert_raw_value(id.clone(), v.clone()));
             Ok(Pair(id, v))
         }
     }
@@ -356,3 +538,140 @@ mod serde_repr {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(line: usize) -> SpanContents {
+        SpanContents {
+            lo: Loc { line, col: 0 },
+            hi: Loc { line, col: 1 },
+            filename: FileName::Anon(0),
+            rust_span_data: None,
+        }
+    }
+
+    /// Nodes of `span(line)` for each line of `lines`, with their
+    /// table.
+    fn nodes(lines: impl Iterator<Item = usize>) -> (Table, Vec<Node<SpanContents>>) {
+        let mut session = Session::default();
+        let nodes = lines
+            .map(|line| Node::new(span(line), &mut session))
+            .collect();
+        (session.table, nodes)
+    }
+
+    fn serialize<T: Serialize>(table: Table, value: T) -> String {
+        WithTable::run(table, value, |with_table| {
+            serde_json::to_string(with_table).unwrap()
+        })
+    }
+
+    fn deserialize<T: serde::de::DeserializeOwned>(json: &str) -> serde_json::Result<T> {
+        let with_table: WithTable<T> = serde_json::from_str(json)?;
+        Ok(with_table.destruct().0)
+    }
+
+    fn lines(nodes: &[Node<SpanContents>]) -> Vec<usize> {
+        nodes.iter().map(|node| node.lo.line).collect()
+    }
+
+    #[test]
+    fn nodes_are_serialized_as_ids() {
+        let (table, nodes) = nodes(0..3);
+        let json = serialize(table, nodes.clone());
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(value[1]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|node| node["value"].is_null()));
+        assert_eq!(
+            deserialize::<Vec<Node<SpanContents>>>(&json).unwrap(),
+            nodes
+        );
+        assert!(!serialize_use_id());
+    }
+
+    #[test]
+    fn interned_values_share_nodes() {
+        let mut session = Session::default();
+        let a = Node::interned(span(1), &mut session);
+        let b = Node::interned(span(1), &mut session);
+        let c = Node::interned(span(2), &mut session);
+        assert_eq!(a.id, b.id);
+        assert!(Arc::ptr_eq(a.inner(), b.inner()));
+        assert_ne!(a.id, c.id);
+        // Values created by `new` are not shared.
+        let d = Node::new(span(1), &mut session);
+        assert_ne!(a.id, d.id);
+        assert_eq!(session.new_entries().0.into_iter().count(), 3);
+    }
+
+    #[test]
+    fn tables_are_serialized_in_parallel() {
+        let barrier = std::sync::Barrier::new(2);
+        std::thread::scope(|scope| {
+            for first_line in [0, 100] {
+                let barrier = &barrier;
+                scope.spawn(move || {
+                    barrier.wait();
+                    for round in 0..50 {
+                        let (table, nodes) = nodes(first_line + round..first_line + round + 5);
+                        let json = serialize(table, nodes.clone());
+                        let nodes_back: Vec<Node<SpanContents>> = deserialize(&json).unwrap();
+                        assert_eq!(lines(&nodes_back), lines(&nodes));
+                    }
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn tables_are_nested() {
+        let (outer_table, outer) = nodes(0..2);
+        let (inner_table, inner) = nodes(10..12);
+        // The inner nodes have the same ids as the outer ones.
+        let value = (
+            outer[0].clone(),
+            Inlined(inner[1].clone()),
+            WithTable {
+                table: inner_table,
+                value: inner.clone(),
+            },
+            outer[1].clone(),
+        );
+        let json = serialize(outer_table, value);
+        type Nested = (
+            Node<SpanContents>,
+            Inlined<Node<SpanContents>>,
+            WithTable<Vec<Node<SpanContents>>>,
+            Node<SpanContents>,
+        );
+        let (first, inlined, with_table, last): Nested = deserialize(&json).unwrap();
+        assert_eq!(lines(&[first, last]), lines(&outer));
+        assert_eq!(inlined.0.lo.line, 11);
+        assert_eq!(lines(&with_table.destruct().0), lines(&inner));
+    }
+
+    #[test]
+    fn state_is_restored_after_errors_and_panics() {
+        let (table, nodes) = nodes(0..2);
+        let json = serialize(table, nodes);
+        // The table of `json` lacks the entry of the second node.
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        value[0].as_array_mut().unwrap().pop();
+        let error = deserialize::<Vec<Node<SpanContents>>>(&value.to_string()).unwrap_err();
+        assert!(error.to_string().contains("not found in cache"));
+        DESERIALIZATION_STATE.with_borrow(|table| {
+            assert!(table.0.get_raw(&Id { id: 0 }).is_none());
+        });
+
+        let panicked = std::panic::catch_unwind(|| {
+            WithTable::run(Table::default(), (), |_| panic!("serialization failed"))
+        });
+        assert!(panicked.is_err());
+        assert!(!serialize_use_id());
+    }
+}
@@ -72,7 +72,7 @@ where
 
 macro_rules! make_idx_wrapper {
     ($($mod:ident)::+, $type:ident) => {
-        #[derive_group(Serializers)]#[derive(Copy, Clone, Eq, Debug, Hash, PartialEq, PartialOrd, Ord, JsonSchema)]
+        #[derive_group(Serializers, Visitors)]#[derive(Copy, Clone, Eq, Debug, Hash, PartialEq, PartialOrd, Ord, JsonSchema)]
         #[serde(untagged)]
         pub enum $type {
             $type(usize),
@@ -5,6 +5,10 @@
 #![cfg_attr(feature = "rustc", feature(trait_alias))]
 #![cfg_attr(feature = "rustc", feature(rustc_private))]
 
+// Allows the code derived by `Visitable` to refer to
+// `::hax_frontend_exporter` from within this crate.
+extern crate self as hax_frontend_exporter;
+
 macro_rules! cfg_feature_rustc {
     ($($item:item)*) => {
         $(
@@ -23,6 +27,7 @@ cfg_feature_rustc! {
     extern crate rustc_ast;
     extern crate rustc_ast_pretty;
     extern crate rustc_attr;
+    extern crate rustc_borrowck;
     extern crate rustc_apfloat;
     extern crate rustc_data_structures;
     extern crate rustc_driver;
@@ -34,6 +39,7 @@ cfg_feature_rustc! {
     extern crate rustc_interface;
     extern crate rustc_middle;
     extern crate rustc_mir_build;
+    extern crate rustc_mir_dataflow;
     extern crate rustc_session;
     extern crate rustc_span;
     extern crate rustc_target;
@@ -44,7 +50,6 @@ cfg_feature_rustc! {
     mod rustc_utils;
     pub mod state;
     mod utils;
-    mod deterministic_hash;
     pub mod comments;
 }
 
@@ -53,6 +58,7 @@ mod constant_utils;
 pub mod id_table;
 mod types;
 
+mod deterministic_hash;
 mod index_vec;
 mod prelude;
 
@@ -61,6 +67,7 @@ pub use prelude::*;
 
 mod sinto;
 mod traits;
+pub mod visitors;
 
 pub use hax_adt_into::AdtInto;
 pub use sinto::SInto;
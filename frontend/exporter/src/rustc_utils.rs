@@ -103,14 +103,17 @@ pub(crate) fn read_span_from_file(span: &Span) -> Result<String, ReadSpanErr> {
 }
 
 #[tracing::instrument(skip(sess))]
-pub fn translate_span(span: rustc_span::Span, sess: &rustc_session::Session) -> Span {
+pub fn translate_span_contents(
+    span: rustc_span::Span,
+    sess: &rustc_session::Session,
+) -> SpanContents {
     let smap: &rustc_span::source_map::SourceMap = sess.psess.source_map();
     let filename = smap.span_to_filename(span);
 
     let lo = smap.lookup_char_pos(span.lo());
     let hi = smap.lookup_char_pos(span.hi());
 
-    Span {
+    SpanContents {
         lo: lo.into(),
         hi: hi.into(),
         filename: filename.sinto(&()),
@@ -118,6 +121,12 @@ pub fn translate_span(span: rustc_span::Span, sess: &rustc_session::Session) ->
     }
 }
 
+/// Translates `span` outside of a translation session: the resulting
+/// span is not shared through the id table.
+pub fn translate_span(span: rustc_span::Span, sess: &rustc_session::Session) -> Span {
+    Span::detached(translate_span_contents(span, sess))
+}
+
 pub trait ParamEnv<'tcx> {
     fn param_env(&self) -> ty::ParamEnv<'tcx>;
 }
@@ -213,14 +222,13 @@ pub(crate) fn attribute_from_scope<'tcx, S: ExprState<'tcx>>(
     (hir_id, attributes)
 }
 
-use itertools::Itertools;
-
-pub fn inline_macro_invocations<'t, S: BaseState<'t>, Body: IsBody>(
-    ids: impl Iterator<Item = rustc_hir::ItemId>,
-    s: &S,
-) -> Vec<Item<Body>> {
-    let tcx: ty::TyCtxt = s.base().tcx;
-
+/// Translates the items `ids`, lazily. Consecutive items that stem
+/// from the same macro invocation are translated as one
+/// `ItemKind::MacroInvokation` item.
+pub fn inline_macro_invocations<'t, 's, S: BaseState<'t>, Body: IsBody>(
+    ids: impl Iterator<Item = rustc_hir::ItemId> + 's,
+    s: &'s S,
+) -> impl Iterator<Item = Item<Body>> + 's {
     struct SpanEq(Option<(DefId, rustc_span::hygiene::ExpnData)>);
     impl core::cmp::PartialEq for SpanEq {
         fn eq(&self, other: &SpanEq) -> bool {
@@ -229,30 +237,79 @@ pub fn inline_macro_invocations<'t, S: BaseState<'t>, Body: IsBody>(
         }
     }
 
-    ids.map(|id| tcx.hir().item(id))
-        .group_by(|item| SpanEq(raw_macro_invocation_of_span(item.span, s)))
-        .into_iter()
-        .flat_map(|(mac, items)| match mac.0 {
+    let mut ids = ids
+        .map(|id| {
+            let span = s.base().tcx.hir().item(id).span;
+            (SpanEq(raw_macro_invocation_of_span(span, s)), id)
+        })
+        .peekable();
+    std::iter::from_fn(move || {
+        let (mac, id) = ids.next()?;
+        if mac.0.is_some() {
+            while ids.next_if(|(next, _)| *next == mac).is_some() {}
+        }
+        let item = s.base().tcx.hir().item(id);
+        Some(match mac.0 {
             Some((macro_ident, expn_data)) => {
-                let owner_id: rustc_hir::hir_id::OwnerId =
-                    items.into_iter().map(|x| x.owner_id).next().s_unwrap(s);
-                let invocation =
-                    macro_invocation_of_raw_mac_invocation(&macro_ident, &expn_data, s);
-                let span = expn_data.call_site.sinto(s);
-                let owner_id: DefId = owner_id.sinto(s);
-                vec![Item {
-                    def_id: None,
-                    owner_id,
-                    kind: ItemKind::MacroInvokation(invocation),
-                    span,
-                    vis_span: rustc_span::DUMMY_SP.sinto(s),
-                    attributes: ItemAttributes::new(),
-                    expn_backtrace: vec![],
-                }]
+                translate_or_error(item.owner_id, expn_data.call_site, s, || {
+                    let invocation =
+                        macro_invocation_of_raw_mac_invocation(&macro_ident, &expn_data, s);
+                    let span = expn_data.call_site.sinto(s);
+                    let owner_id: DefId = item.owner_id.sinto(s);
+                    Item {
+                        def_id: None,
+                        owner_id,
+                        kind: ItemKind::MacroInvokation(invocation),
+                        span,
+                        vis_span: rustc_span::DUMMY_SP.sinto(s),
+                        attributes: ItemAttributes::new(),
+                        expn_backtrace: vec![],
+                        fingerprint: Fingerprint::default(),
+                    }
+                })
             }
-            _ => items.map(|item| item.sinto(s)).collect(),
+            None => translate_or_error(item.owner_id, item.span, s, || item.sinto(s)),
         })
-        .collect()
+    })
+}
+
+/// Runs `translate`, the translation of the item `owner_id`. If it
+/// panics or raises a fatal error, an item of kind [`ItemKind::Error`]
+/// is returned instead, so that one faulty item doesn't prevent the
+/// rest of the crate from being exported.
+fn translate_or_error<'t, S: BaseState<'t>, Body: IsBody>(
+    owner_id: rustc_hir::hir_id::OwnerId,
+    span: rustc_span::Span,
+    s: &S,
+    translate: impl FnOnce() -> Item<Body>,
+) -> Item<Body> {
+    crate::utils::catch_fatal_errors(translate).unwrap_or_else(|message| {
+        // The translation was interrupted, possibly while the trait
+        // solvers (the only caches updated in place) were being
+        // updated: drop them, and drop what was cached for the item.
+        s.with_global_cache(|cache| {
+            for item_cache in cache.per_item.values_mut() {
+                item_cache.predicate_searcher = None;
+            }
+            if let Some(item_cache) = cache.per_item.get_mut(&owner_id.to_def_id()) {
+                *item_cache = crate::state::ItemCache {
+                    thir: item_cache.thir.take(),
+                    ..Default::default()
+                };
+            }
+        });
+        let s = &with_owner_id(s.base(), (), (), owner_id.to_def_id());
+        Item {
+            def_id: None,
+            owner_id: owner_id.sinto(s),
+            kind: ItemKind::Error(format!("the frontend failed: {message}")),
+            span: span.sinto(s),
+            vis_span: span.sinto(s),
+            attributes: ItemAttributes::new(),
+            expn_backtrace: vec![],
+            fingerprint: Fingerprint::default(),
+        }
+    })
 }
 
 /// Gets the closest ancestor of `id` that is the id of a type.
@@ -7,7 +7,7 @@ pub trait SInto<S, To> {
 #[macro_export]
 macro_rules! sinto_todo {
     ($($mod:ident)::+, $type:ident$(<$($lts:lifetime),*$(,)?>)? as $renamed:ident) => {
-        #[derive_group(Serializers)]
+        #[derive_group(Serializers, Visitors)]
         #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
         pub enum $renamed {
             $type {
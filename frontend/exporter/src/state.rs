@@ -99,7 +99,7 @@ macro_rules! mk {
 mod types {
     use crate::prelude::*;
     use rustc_middle::ty;
-    use std::{cell::RefCell, sync::Arc};
+    use std::{cell::RefCell, collections::HashSet, sync::Arc};
 
     pub struct LocalContextS {
         pub vars: HashMap<rustc_middle::thir::LocalVarId, String>,
@@ -170,6 +170,14 @@ mod types {
         /// more details, please see
         /// https://github.com/hacspec/hax/issues/707.
         pub ty_alias_mode: bool,
+        /// When exporting a monomorphized copy of a function (see
+        /// [`MonoItem`]), the function and the generic arguments
+        /// it is instantiated with.
+        pub instantiation: Option<(RDefId, ty::GenericArgsRef<'tcx>)>,
+        /// The bodies of these definitions are not translated: a
+        /// placeholder with the type and the span of the body is
+        /// exported instead.
+        pub dropped_bodies: Rc<HashSet<rustc_span::def_id::LocalDefId>>,
     }
 
     impl<'tcx> Base<'tcx> {
@@ -187,6 +195,8 @@ mod types {
                 opt_def_id: None,
                 local_ctx: Rc::new(RefCell::new(LocalContextS::new())),
                 ty_alias_mode: false,
+                instantiation: None,
+                dropped_bodies: Rc::new(HashSet::new()),
             }
         }
     }
@@ -18,7 +18,7 @@ use rustc_span::def_id::DefId as RDefId;
 
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: resolution::PathChunk<'tcx>, state: S as s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, JsonSchema)]
 pub enum ImplExprPathChunk {
     AssocItem {
@@ -54,7 +54,7 @@ pub enum ImplExprPathChunk {
 /// concrete `impl Trait for Type {}` item, or `LocalBound` for a context-bound `where T: Trait`.
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: resolution::ImplExprAtom<'tcx>, state: S as s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, JsonSchema)]
 pub enum ImplExprAtom {
     /// A concrete `impl Trait for Type {}` item.
@@ -100,10 +100,17 @@ pub enum ImplExprAtom {
 /// need to combine several concrete trait implementation items. For example, `((1u8, 2u8),
 /// "hello").clone()` combines the generic implementation of `Clone` for `(A, B)` with the
 /// concrete implementations for `u8` and `&str`, represented as a tree.
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, JsonSchema)]
+#[serde(transparent)]
+pub struct ImplExpr {
+    pub(crate) contents: id_table::Node<ImplExprContents>,
+}
+
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, JsonSchema, AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: resolution::ImplExpr<'tcx>, state: S as s)]
-pub struct ImplExpr {
+pub struct ImplExprContents {
     /// The trait this is an impl for.
     pub r#trait: Binder<TraitRef>,
     /// The kind of implemention of the root of the tree.
@@ -112,6 +119,37 @@ pub struct ImplExpr {
     pub args: Vec<ImplExpr>,
 }
 
+impl std::ops::Deref for ImplExpr {
+    type Target = ImplExprContents;
+    fn deref(&self) -> &Self::Target {
+        &self.contents
+    }
+}
+
+impl ImplExpr {
+    #[cfg(feature = "rustc")]
+    pub fn new<'tcx, S: BaseState<'tcx>>(s: &S, contents: ImplExprContents) -> Self {
+        s.with_global_cache(|cache| ImplExpr {
+            contents: id_table::Node::interned(contents, &mut cache.id_table_session),
+        })
+    }
+
+    /// An impl expression that is not shared through the id table,
+    /// see [`id_table::Node::detached`].
+    pub fn detached(contents: ImplExprContents) -> Self {
+        ImplExpr {
+            contents: id_table::Node::detached(contents),
+        }
+    }
+}
+
+#[cfg(feature = "rustc")]
+impl<'tcx, S: UnderOwnerState<'tcx>> SInto<S, ImplExpr> for resolution::ImplExpr<'tcx> {
+    fn sinto(&self, s: &S) -> ImplExpr {
+        ImplExpr::new(s, self.sinto(s))
+    }
+}
+
 /// Given a clause `clause` in the context of some impl block `impl_did`, susbts correctly `Self`
 /// from `clause` and (1) derive a `Clause` and (2) resolve an `ImplExpr`.
 #[cfg(feature = "rustc")]
@@ -157,6 +195,7 @@ pub fn solve_trait<'tcx, S: BaseState<'tcx> + HasOwnerId>(
             crate::warning!(s, "{}", msg)
         }
     };
+    let trait_ref = instantiate_mono(s, trait_ref);
     if let Some(impl_expr) = s.with_cache(|cache| cache.impl_exprs.get(&trait_ref).cloned()) {
         return impl_expr;
     }
@@ -166,7 +205,7 @@ pub fn solve_trait<'tcx, S: BaseState<'tcx> + HasOwnerId>(
             .get_or_insert_with(|| PredicateSearcher::new_for_owner(s.base().tcx, s.owner_id()))
             .resolve(&trait_ref, &warn)
     });
-    let impl_expr = match resolved {
+    let impl_expr: ImplExpr = match resolved {
         Ok(x) => x.sinto(s),
         Err(e) => crate::fatal!(s, "{}", e),
     };
@@ -8,7 +8,7 @@
 //!
 //! The feature `extract_names_mode` exists only in the crate
 //! `hax-engine-names-extract`, and is used to turn off the derive
-//! attributes `AdtInto` and `JsonSchema`.
+//! attributes `AdtInto`, `JsonSchema` and the `Visitors` derive group.
 
 use hax_adt_into::derive_group;
 
@@ -31,6 +31,7 @@ impl<'t, S> SInto<S, Symbol> for rustc_span::symbol::Symbol {
 
 /// Reflects [`rustc_hir::def_id::DefId`]
 #[derive_group(Serializers)]
+#[cfg_attr(not(feature = "extract_names_mode"), derive_group(Visitors))]
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(not(feature = "extract_names_mode"), derive(JsonSchema))]
 pub struct DefId {
@@ -38,6 +39,7 @@ pub struct DefId {
 }
 
 #[derive_group(Serializers)]
+#[cfg_attr(not(feature = "extract_names_mode"), derive_group(Visitors))]
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(not(feature = "extract_names_mode"), derive(JsonSchema))]
 pub struct DefIdContents {
@@ -204,6 +206,7 @@ impl<'tcx, S: UnderOwnerState<'tcx>> SInto<S, GlobalIdent> for rustc_hir::def_id
 
 /// Reflects [`rustc_hir::definitions::DefPathData`]
 #[derive_group(Serializers)]
+#[cfg_attr(not(feature = "extract_names_mode"), derive_group(Visitors))]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(not(feature = "extract_names_mode"), derive(AdtInto, JsonSchema))]
 #[cfg_attr(not(feature = "extract_names_mode"), args(<'ctx, S: UnderOwnerState<'ctx>>, from: rustc_hir::definitions::DefPathData, state: S as s))]
@@ -228,6 +231,7 @@ pub enum DefPathItem {
 }
 
 #[derive_group(Serializers)]
+#[cfg_attr(not(feature = "extract_names_mode"), derive_group(Visitors))]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(not(feature = "extract_names_mode"), derive(AdtInto, JsonSchema))]
 #[cfg_attr(not(feature = "extract_names_mode"), args(<'a, S: UnderOwnerState<'a>>, from: rustc_hir::definitions::DisambiguatedDefPathData, state: S as s))]
@@ -13,7 +13,7 @@ use rustc_hir as hir;
 use rustc_middle::ty;
 
 /// Reflects [`hir::hir_id::HirId`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[args(<'tcx, S: BaseState<'tcx>>, from: hir::hir_id::HirId, state: S as gstate)]
 pub struct HirId {
@@ -31,7 +31,7 @@ impl<'tcx, S: BaseState<'tcx>> SInto<S, DefId> for hir::hir_id::OwnerId {
 }
 
 /// Reflects [`ast::LitFloatType`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[args(<'tcx, S: BaseState<'tcx>>, from: ast::LitFloatType, state: S as gstate)]
 pub enum LitFloatType {
@@ -40,7 +40,7 @@ pub enum LitFloatType {
 }
 
 /// Reflects [`hir::Movability`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[args(<'tcx, S>, from: hir::Movability, state: S as _s)]
 pub enum Movability {
@@ -61,7 +61,7 @@ impl<S> SInto<S, Mutability> for hir::Mutability {
 }
 
 /// Reflects [`hir::def::CtorKind`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<S>, from: hir::def::CtorKind, state: S as _s)]
 pub enum CtorKind {
@@ -70,7 +70,7 @@ pub enum CtorKind {
 }
 
 /// Reflects [`hir::def::CtorOf`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<S>, from: hir::def::CtorOf, state: S as _s)]
 pub enum CtorOf {
@@ -81,7 +81,7 @@ pub enum CtorOf {
 /// Reflects [`hir::RangeEnd`]
 #[derive(AdtInto)]
 #[args(<S>, from: hir::RangeEnd, state: S as _s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum RangeEnd {
     Included,
@@ -91,7 +91,7 @@ pub enum RangeEnd {
 /// Reflects [`hir::Safety`]
 #[derive(AdtInto)]
 #[args(<S>, from: hir::Safety, state: S as _s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Safety {
     Unsafe,
@@ -101,7 +101,7 @@ pub enum Safety {
 /// Reflects [`hir::ImplicitSelfKind`]
 #[derive(AdtInto)]
 #[args(<S>, from: hir::ImplicitSelfKind, state: S as _s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum ImplicitSelfKind {
     Imm,
@@ -112,7 +112,7 @@ pub enum ImplicitSelfKind {
 }
 
 /// Reflects [`hir::FnDecl`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: hir::FnDecl<'tcx>, state: S as tcx)]
 pub struct FnDecl {
@@ -124,7 +124,7 @@ pub struct FnDecl {
 }
 
 /// Reflects [`hir::FnSig`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: hir::FnSig<'tcx>, state: S as tcx)]
 pub struct FnSig {
@@ -134,7 +134,7 @@ pub struct FnSig {
 }
 
 /// Reflects [`hir::FnHeader`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: hir::FnHeader, state: S as tcx)]
 pub struct FnHeader {
@@ -145,7 +145,7 @@ pub struct FnHeader {
 }
 
 /// Reflects [`rustc_target::spec::abi::Abi`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[args(<'tcx, S: BaseState<'tcx>>, from: rustc_target::spec::abi::Abi, state: S as s)]
 pub enum Abi {
@@ -158,7 +158,7 @@ pub enum Abi {
 }
 
 /// Function definition
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct FnDef<Body: IsBody> {
     pub header: FnHeader,
@@ -185,7 +185,7 @@ impl<'x: 'tcx, 'tcx, S: UnderOwnerState<'tcx>> SInto<S, Ty> for hir::Ty<'x> {
 /// Reflects [`hir::UseKind`]
 #[derive(AdtInto)]
 #[args(<S>, from: hir::UseKind, state: S as _s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum UseKind {
     Single,
@@ -196,7 +196,7 @@ pub enum UseKind {
 /// Reflects [`hir::IsAuto`]
 #[derive(AdtInto)]
 #[args(<S>, from: hir::IsAuto, state: S as _s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum IsAuto {
     Yes,
@@ -206,7 +206,7 @@ pub enum IsAuto {
 /// Reflects [`hir::Defaultness`]
 #[derive(AdtInto)]
 #[args(<S>, from: hir::Defaultness, state: S as tcx)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum Defaultness {
     Default { has_value: bool },
@@ -216,7 +216,7 @@ pub enum Defaultness {
 /// Reflects [`hir::ImplPolarity`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: hir::ImplPolarity, state: S as tcx)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum ImplPolarity {
     Positive,
@@ -226,7 +226,7 @@ pub enum ImplPolarity {
 /// Reflects [`hir::Constness`]
 #[derive(AdtInto)]
 #[args(<S>, from: hir::Constness, state: S as _s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum Constness {
     Const,
@@ -236,7 +236,7 @@ pub enum Constness {
 /// Reflects [`hir::Generics`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: hir::Generics<'tcx>, state: S as tcx)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct Generics<Body: IsBody> {
     pub params: Vec<GenericParam<Body>>,
@@ -258,7 +258,7 @@ impl<'tcx, S: UnderOwnerState<'tcx>, Body: IsBody> SInto<S, ImplItem<Body>> for
 }
 
 /// Reflects [`hir::ParamName`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum ParamName {
     Plain(LocalIdent),
@@ -269,7 +269,7 @@ pub enum ParamName {
 /// Reflects [`hir::LifetimeParamKind`]
 #[derive(AdtInto)]
 #[args(<S>, from: hir::LifetimeParamKind, state: S as _s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum LifetimeParamKind {
     Explicit,
@@ -278,7 +278,7 @@ pub enum LifetimeParamKind {
 }
 
 /// Reflects [`hir::AnonConst`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: hir::AnonConst, state: S as s)]
 pub struct AnonConst<Body: IsBody> {
@@ -291,7 +291,7 @@ pub struct AnonConst<Body: IsBody> {
 }
 
 /// Reflects [`hir::ConstArg`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: hir::ConstArg<'tcx>, state: S as s)]
 pub struct ConstArg<Body: IsBody> {
@@ -301,7 +301,7 @@ pub struct ConstArg<Body: IsBody> {
 }
 
 /// Reflects [`hir::ConstArgKind`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: hir::ConstArgKind<'tcx>, state: S as s)]
 pub enum ConstArgKind<Body: IsBody> {
@@ -310,7 +310,7 @@ pub enum ConstArgKind<Body: IsBody> {
 }
 
 /// Reflects [`hir::GenericParamKind`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: hir::GenericParamKind<'tcx>, state: S as tcx)]
 pub enum GenericParamKind<Body: IsBody> {
@@ -337,7 +337,7 @@ pub enum GenericParamKind<Body: IsBody> {
 }
 
 /// Reflects [`hir::GenericParam`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: hir::GenericParam<'tcx>, state: S as s)]
 pub struct GenericParam<Body: IsBody> {
@@ -364,7 +364,7 @@ pub struct GenericParam<Body: IsBody> {
 }
 
 /// Reflects [`hir::ImplItem`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: hir::ImplItem<'tcx>, state: S as s)]
 pub struct ImplItem<Body: IsBody> {
@@ -383,10 +383,19 @@ pub struct ImplItem<Body: IsBody> {
 /// Reflects [`hir::ImplItemKind`], inlining the body of the items.
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: hir::ImplItemKind<'tcx>, state: S as s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum ImplItemKind<Body: IsBody> {
-    Const(Ty, Body),
+    /// The last component is the value of the constant, as evaluated
+    /// by Rustc (see [`eval_global_value`]).
+    #[custom_arm(
+        hir::ImplItemKind::Const(ty, body) => ImplItemKind::Const(
+            ty.sinto(s),
+            body.sinto(s),
+            eval_global_value::<Body, _>(s, s.owner_id()),
+        ),
+    )]
+    Const(Ty, Body, Option<ConstantExpr>),
     #[custom_arm(hir::ImplItemKind::Fn(sig, body) => {
                 ImplItemKind::Fn(make_fn_def::<Body, _>(sig, body, s))
         },)]
@@ -418,7 +427,7 @@ pub enum ImplItemKind<Body: IsBody> {
 /// Reflects [`hir::AssocItemKind`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: hir::AssocItemKind, state: S as tcx)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum AssocItemKind {
     Const,
@@ -429,7 +438,7 @@ pub enum AssocItemKind {
 /// Reflects [`hir::Impl`].
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: hir::Impl<'tcx>, state: S as s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct Impl<Body: IsBody> {
     pub safety: Safety,
@@ -466,7 +475,7 @@ pub struct Impl<Body: IsBody> {
 /// Reflects [`hir::IsAsync`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: hir::IsAsync, state: S as _s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum IsAsync {
     Async(Span),
@@ -476,7 +485,7 @@ pub enum IsAsync {
 /// Reflects [`hir::FnRetTy`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: hir::FnRetTy<'tcx>, state: S as tcx)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum FnRetTy {
     DefaultReturn(Span),
@@ -484,7 +493,7 @@ pub enum FnRetTy {
 }
 
 /// Reflects [`hir::VariantData`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: hir::VariantData<'tcx>, state: S as tcx)]
 pub enum VariantData {
@@ -509,7 +518,7 @@ impl<S> SInto<S, bool> for ast::Recovered {
 /// Reflects [`hir::FieldDef`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: hir::FieldDef<'tcx>, state: S as s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct HirFieldDef {
     pub span: Span,
@@ -525,7 +534,7 @@ pub struct HirFieldDef {
 /// Reflects [`hir::Variant`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: hir::Variant<'tcx>, state: S as s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct Variant<Body: IsBody> {
     pub ident: Ident,
@@ -552,7 +561,7 @@ pub struct Variant<Body: IsBody> {
 /// Reflects [`hir::UsePath`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: hir::UsePath<'tcx>, state: S as s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct UsePath {
     pub span: Span,
@@ -575,7 +584,7 @@ pub struct UsePath {
 /// Reflects [`hir::def::Res`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: hir::def::Res, state: S as s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum Res {
     Def(DefKind, DefId),
@@ -598,7 +607,7 @@ pub enum Res {
 /// Reflects [`hir::PrimTy`]
 #[derive(AdtInto)]
 #[args(<S>, from: hir::PrimTy, state: S as s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum PrimTy {
     Int(IntTy),
@@ -612,7 +621,7 @@ pub enum PrimTy {
 /// Reflects [`hir::def::NonMacroAttrKind`]
 #[derive(AdtInto)]
 #[args(<S>, from: hir::def::NonMacroAttrKind, state: S as s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum NonMacroAttrKind {
     Builtin(Symbol),
@@ -624,7 +633,7 @@ pub enum NonMacroAttrKind {
 /// Reflects [`hir::PathSegment`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: hir::PathSegment<'tcx>, state: S as s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct PathSegment {
     pub ident: Ident,
@@ -638,15 +647,35 @@ pub struct PathSegment {
 /// Reflects [`hir::ItemKind`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: hir::ItemKind<'tcx>, state: S as s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum ItemKind<Body: IsBody> {
     #[disable_mapping]
     MacroInvokation(MacroInvokation),
     ExternCrate(Option<Symbol>),
     Use(UsePath, UseKind),
-    Static(Ty, Mutability, Body),
-    Const(Ty, Generics<Body>, Body),
+    /// The last component is the initial value of the static, as
+    /// evaluated by Rustc (see [`eval_global_value`]).
+    #[custom_arm(
+        hir::ItemKind::Static(ty, mutability, body) => ItemKind::Static(
+            ty.sinto(s),
+            mutability.sinto(s),
+            body.sinto(s),
+            eval_global_value::<Body, _>(s, s.owner_id()),
+        ),
+    )]
+    Static(Ty, Mutability, Body, Option<ConstantExpr>),
+    /// The last component is the value of the constant, as evaluated
+    /// by Rustc (see [`eval_global_value`]).
+    #[custom_arm(
+        hir::ItemKind::Const(ty, generics, body) => ItemKind::Const(
+            ty.sinto(s),
+            generics.sinto(s),
+            body.sinto(s),
+            eval_global_value::<Body, _>(s, s.owner_id()),
+        ),
+    )]
+    Const(Ty, Generics<Body>, Body, Option<ConstantExpr>),
     #[custom_arm(
             hir::ItemKind::Fn(sig, generics, body) => {
                 ItemKind::Fn(generics.sinto(s), make_fn_def::<Body, _>(sig, body, s))
@@ -694,6 +723,10 @@ pub enum ItemKind<Body: IsBody> {
     ),
     TraitAlias(Generics<Body>, GenericBounds),
     Impl(Impl<Body>),
+    /// The frontend failed to translate this item: this is a
+    /// placeholder carrying the error message.
+    #[disable_mapping]
+    Error(String),
 }
 
 pub type EnumDef<Body> = Vec<Variant<Body>>;
@@ -702,7 +735,7 @@ pub type EnumDef<Body> = Vec<Variant<Body>>;
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: hir::TraitItemKind<'tcx>, state: S as tcx)]
 #[derive(Clone, Debug, JsonSchema)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 pub enum TraitItemKind<Body: IsBody> {
     Const(Ty, Option<Body>),
     #[custom_arm(
@@ -730,7 +763,7 @@ pub enum TraitItemKind<Body: IsBody> {
 /// Reflects [`hir::TraitItem`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: hir::TraitItem<'tcx>, state: S as s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct TraitItem<Body: IsBody> {
     pub ident: Ident,
@@ -763,7 +796,7 @@ impl<'a, S: UnderOwnerState<'a>, Body: IsBody> SInto<S, TraitItem<Body>> for hir
 #[cfg(feature = "rustc")]
 impl<'a, 'tcx, S: UnderOwnerState<'tcx>, Body: IsBody> SInto<S, Vec<Item<Body>>> for hir::Mod<'a> {
     fn sinto(&self, s: &S) -> Vec<Item<Body>> {
-        inline_macro_invocations(self.item_ids.iter().copied(), s)
+        inline_macro_invocations(self.item_ids.iter().copied(), s).collect()
         // .iter()
         // .map(|item_id| item_id.sinto(s))
         // .collect()
@@ -774,7 +807,7 @@ impl<'a, 'tcx, S: UnderOwnerState<'tcx>, Body: IsBody> SInto<S, Vec<Item<Body>>>
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: hir::ForeignItemKind<'tcx>, state: S as tcx)]
 #[derive(Clone, Debug, JsonSchema)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 pub enum ForeignItemKind<Body: IsBody> {
     Fn(FnSig, Vec<Ident>, Generics<Body>),
     Static(Ty, Mutability, Safety),
@@ -784,7 +817,7 @@ pub enum ForeignItemKind<Body: IsBody> {
 /// Reflects [`hir::ForeignItem`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: hir::ForeignItem<'tcx>, state: S as tcx)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct ForeignItem<Body: IsBody> {
     pub ident: Ident,
@@ -805,7 +838,7 @@ impl<'a, S: UnderOwnerState<'a>, Body: IsBody> SInto<S, ForeignItem<Body>> for h
 /// Reflects [`hir::OpaqueTy`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: hir::OpaqueTy<'tcx>, state: S as tcx)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct OpaqueTy<Body: IsBody> {
     pub generics: Generics<Body>,
@@ -866,7 +899,7 @@ impl<'tcx, S: UnderOwnerState<'tcx>> SInto<S, GenericBounds> for hir::GenericBou
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: hir::OpaqueTyOrigin, state: S as tcx)]
 #[derive(Clone, Debug, JsonSchema)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 pub enum OpaqueTyOrigin {
     FnReturn {
         parent: GlobalIdent,
@@ -895,7 +928,7 @@ impl<'t, S> SInto<S, TokenStream> for rustc_ast::tokenstream::TokenStream {
 /// Reflects [`rustc_ast::token::Delimiter`]
 #[derive(AdtInto)]
 #[args(<S>, from: rustc_ast::token::Delimiter, state: S as _s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Delimiter {
     Parenthesis,
@@ -907,7 +940,7 @@ pub enum Delimiter {
 /// Reflects [`rustc_ast::ast::DelimArgs`]
 #[derive(AdtInto)]
 #[args(<S>, from: rustc_ast::ast::DelimArgs, state: S as gstate)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct DelimArgs {
     pub dspan: DelimSpan,
@@ -920,7 +953,7 @@ sinto_todo!(rustc_ast::tokenstream, DelimSpan);
 /// Reflects [`ast::MacroDef`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: BaseState<'tcx>>, from: ast::MacroDef, state: S as tcx)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct MacroDef {
     pub body: DelimArgs,
@@ -928,7 +961,7 @@ pub struct MacroDef {
 }
 
 /// Reflects [`hir::Item`] (and [`hir::ItemId`])
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct Item<Body: IsBody> {
     pub def_id: Option<GlobalIdent>,
@@ -938,6 +971,9 @@ pub struct Item<Body: IsBody> {
     pub kind: ItemKind<Body>,
     pub attributes: ItemAttributes,
     pub expn_backtrace: Vec<ExpnData>,
+    /// Computed out of every item of the crate when reading a
+    /// `haxmeta` file, see `fingerprint_items`.
+    pub fingerprint: Fingerprint,
 }
 
 #[cfg(feature = "rustc")]
@@ -957,6 +993,7 @@ impl<'tcx, S: BaseState<'tcx>, Body: IsBody> SInto<S, Item<Body>> for hir::Item<
             kind: self.kind.sinto(s),
             attributes: ItemAttributes::from_owner_id(s, self.owner_id),
             expn_backtrace: self.span.macro_backtrace().map(|o| o.sinto(s)).collect(),
+            fingerprint: Fingerprint::default(),
         }
     }
 }
@@ -980,7 +1017,7 @@ impl<'tcx, S: UnderOwnerState<'tcx>> SInto<S, Ident> for rustc_span::symbol::Ide
 }
 
 /// Reflects [`rustc_ast::ast::AttrStyle`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[args(<S>, from: rustc_ast::ast::AttrStyle, state: S as _s)]
 pub enum AttrStyle {
@@ -989,7 +1026,7 @@ pub enum AttrStyle {
 }
 
 /// Reflects [`rustc_ast::ast::Attribute`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[args(<'tcx, S: BaseState<'tcx>>, from: rustc_ast::ast::Attribute, state: S as gstate)]
 pub struct Attribute {
@@ -1001,7 +1038,7 @@ pub struct Attribute {
 }
 
 /// Reflects [`rustc_attr::InlineAttr`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[args(<'tcx, S: BaseState<'tcx>>, from: rustc_attr::InlineAttr, state: S as _s)]
 pub enum InlineAttr {
@@ -1014,7 +1051,7 @@ pub enum InlineAttr {
 /// Reflects [`rustc_ast::ast::BindingMode`]
 #[derive(AdtInto)]
 #[args(<S>, from: rustc_ast::ast::BindingMode, state: S as s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct BindingMode {
     #[value(self.0.sinto(s))]
@@ -1026,7 +1063,7 @@ pub struct BindingMode {
 /// Reflects [`rustc_ast::ast::ByRef`]
 #[derive(AdtInto)]
 #[args(<S>, from: rustc_ast::ast::ByRef, state: S as s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum ByRef {
     Yes(Mutability),
@@ -1036,7 +1073,7 @@ pub enum ByRef {
 /// Reflects [`rustc_ast::ast::StrStyle`]
 #[derive(AdtInto)]
 #[args(<S>, from: rustc_ast::ast::StrStyle, state: S as gstate)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum StrStyle {
     Cooked,
@@ -1046,7 +1083,7 @@ pub enum StrStyle {
 /// Reflects [`rustc_ast::ast::LitKind`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: BaseState<'tcx>>, from: rustc_ast::ast::LitKind, state: S as gstate)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum LitKind {
     Str(Symbol, StrStyle),
@@ -1076,7 +1113,7 @@ impl<S> SInto<S, u128> for rustc_data_structures::packed::Pu128 {
 #[allow(rustdoc::private_intra_doc_links)]
 /// Describe a macro invocation, using
 /// [`macro_invocation_of_raw_mac_invocation`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct MacroInvokation {
     pub macro_ident: DefId,
@@ -1087,7 +1124,7 @@ pub struct MacroInvokation {
 /// Reflects [`rustc_ast::token::CommentKind`]
 #[derive(AdtInto)]
 #[args(<S>, from: rustc_ast::token::CommentKind, state: S as _s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum CommentKind {
     Line,
@@ -1097,7 +1134,7 @@ pub enum CommentKind {
 /// Reflects [`rustc_ast::ast::AttrArgs`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: BaseState<'tcx>>, from: rustc_ast::ast::AttrArgs, state: S as tcx)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum AttrArgs {
     Empty,
@@ -1111,7 +1148,7 @@ pub enum AttrArgs {
 /// Reflects [`rustc_ast::ast::AttrArgsEq`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: BaseState<'tcx>>, from: rustc_ast::ast::AttrArgsEq, state: S as tcx)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum AttrArgsEq {
     Hir(MetaItemLit),
@@ -1123,7 +1160,7 @@ pub enum AttrArgsEq {
 /// Reflects [`rustc_ast::ast::MetaItemLit`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: BaseState<'tcx>>, from: rustc_ast::ast::MetaItemLit, state: S as tcx)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct MetaItemLit {
     pub symbol: Symbol,
@@ -1135,7 +1172,7 @@ pub struct MetaItemLit {
 /// Reflects [`rustc_ast::ast::AttrItem`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: BaseState<'tcx>>, from: rustc_ast::ast::AttrItem, state: S as tcx)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct AttrItem {
     #[map(rustc_ast_pretty::pprust::path_to_string(x))]
@@ -1155,7 +1192,7 @@ impl<S> SInto<S, String> for rustc_ast::tokenstream::LazyAttrTokenStream {
 /// Reflects [`rustc_ast::ast::NormalAttr`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: BaseState<'tcx>>, from: rustc_ast::ast::NormalAttr, state: S as tcx)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct NormalAttr {
     pub item: AttrItem,
@@ -1165,7 +1202,7 @@ pub struct NormalAttr {
 /// Reflects [`rustc_ast::AttrKind`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: BaseState<'tcx>>, from: rustc_ast::AttrKind, state: S as tcx)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum AttrKind {
     Normal(NormalAttr),
@@ -7,7 +7,7 @@ use crate::sinto_as_usize;
 #[cfg(feature = "rustc")]
 use tracing::trace;
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<S>, from: rustc_middle::mir::MirPhase, state: S as s)]
 pub enum MirPhase {
@@ -16,7 +16,7 @@ pub enum MirPhase {
     Runtime(RuntimePhase),
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: rustc_middle::mir::SourceInfo, state: S as s)]
 pub struct SourceInfo {
@@ -24,7 +24,7 @@ pub struct SourceInfo {
     pub scope: SourceScope,
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: rustc_middle::mir::LocalDecl<'tcx>, state: S as s)]
 pub struct LocalDecl {
@@ -37,7 +37,7 @@ pub struct LocalDecl {
     pub name: Option<String>, // This information is contextual, thus the SInto instance initializes it to None, and then we fill it while `SInto`ing MirBody
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum ClearCrossCrate<T> {
     Clear,
@@ -56,7 +56,7 @@ impl<S, TT, T: SInto<S, TT>> SInto<S, ClearCrossCrate<TT>>
     }
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<S>, from: rustc_middle::mir::RuntimePhase, state: S as _s)]
 pub enum RuntimePhase {
@@ -65,7 +65,7 @@ pub enum RuntimePhase {
     Optimized,
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<S>, from: rustc_middle::mir::AnalysisPhase, state: S as _s)]
 pub enum AnalysisPhase {
@@ -97,23 +97,23 @@ fn name_of_local(
 pub mod mir_kinds {
     use crate::prelude::{derive_group, JsonSchema};
 
-    #[derive_group(Serializers)]
+    #[derive_group(Serializers, Visitors)]
     #[derive(Clone, Copy, Debug, JsonSchema)]
     pub struct Built;
 
-    #[derive_group(Serializers)]
+    #[derive_group(Serializers, Visitors)]
     #[derive(Clone, Copy, Debug, JsonSchema)]
     pub struct Promoted;
 
-    #[derive_group(Serializers)]
+    #[derive_group(Serializers, Visitors)]
     #[derive(Clone, Copy, Debug, JsonSchema)]
     pub struct Elaborated;
 
-    #[derive_group(Serializers)]
+    #[derive_group(Serializers, Visitors)]
     #[derive(Clone, Copy, Debug, JsonSchema)]
     pub struct Optimized;
 
-    #[derive_group(Serializers)]
+    #[derive_group(Serializers, Visitors)]
     #[derive(Clone, Copy, Debug, JsonSchema)]
     pub struct CTFE;
 
@@ -133,9 +133,18 @@ pub mod mir_kinds {
                 id: LocalDefId,
                 f: impl FnOnce(&Body<'tcx>) -> T,
             ) -> Option<T>;
+
+            /// Whether evaluating a constant steals the MIR of this
+            /// kind of the functions it calls.
+            const STOLEN_BY_CONST_EVAL: bool = false;
+
+            /// Whether this is the MIR the borrow checker runs on, i.e.
+            /// whether borrow-checker facts can be computed for it.
+            const BORROW_CHECKED: bool = false;
         }
 
         impl IsMirKind for Built {
+            const STOLEN_BY_CONST_EVAL: bool = true;
             fn get_mir<'tcx, T>(
                 tcx: TyCtxt<'tcx>,
                 id: LocalDefId,
@@ -151,6 +160,8 @@ pub mod mir_kinds {
         }
 
         impl IsMirKind for Promoted {
+            const STOLEN_BY_CONST_EVAL: bool = true;
+            const BORROW_CHECKED: bool = true;
             fn get_mir<'tcx, T>(
                 tcx: TyCtxt<'tcx>,
                 id: LocalDefId,
@@ -205,7 +216,7 @@ pub mod mir_kinds {
 #[cfg(feature = "rustc")]
 pub use mir_kinds::IsMirKind;
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: rustc_middle::mir::ConstOperand<'tcx>, state: S as s)]
 pub struct Constant {
@@ -214,7 +225,7 @@ pub struct Constant {
     pub const_: TypedConstantKind,
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> + HasMir<'tcx>>, from: rustc_middle::mir::Body<'tcx>, state: S as s)]
 pub struct MirBody<KIND> {
@@ -244,11 +255,16 @@ pub struct MirBody<KIND> {
     pub is_polymorphic: bool,
     pub injection_phase: Option<MirPhase>,
     pub tainted_by_errors: Option<ErrorGuaranteed>,
+    /// The facts computed by the borrow checker for this body. Only
+    /// exported for bodies of kind [`mir_kinds::Promoted`], when the
+    /// option `borrowck_facts` is set.
+    #[value(None)]
+    pub borrowck_facts: Option<BorrowckFacts>,
     #[value(std::marker::PhantomData)]
     pub _kind: std::marker::PhantomData<KIND>,
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: rustc_middle::mir::SourceScopeData<'tcx>, state: S as s)]
 pub struct SourceScopeData {
@@ -259,7 +275,7 @@ pub struct SourceScopeData {
     pub local_data: ClearCrossCrate<SourceScopeLocalData>,
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: rustc_middle::ty::Instance<'tcx>, state: S as s)]
 pub struct Instance {
@@ -267,14 +283,14 @@ pub struct Instance {
     pub args: Vec<GenericArg>,
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: rustc_middle::mir::SourceScopeLocalData, state: S as s)]
 pub struct SourceScopeLocalData {
     pub lint_root: HirId,
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> + HasMir<'tcx>>, from: rustc_middle::mir::Operand<'tcx>, state: S as s)]
 pub enum Operand {
@@ -293,7 +309,7 @@ impl Operand {
     }
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> + HasMir<'tcx>>, from: rustc_middle::mir::Terminator<'tcx>, state: S as s)]
 pub struct Terminator {
@@ -483,14 +499,14 @@ fn translate_terminator_kind_call<'tcx, S: BaseState<'tcx> + HasMir<'tcx> + HasO
 }
 
 // We don't use the LitIntType on purpose (we don't want the "unsuffixed" case)
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Copy, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum IntUintTy {
     Int(IntTy),
     Uint(UintTy),
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct ScalarInt {
     /// Little-endian representation of the integer
@@ -551,7 +567,7 @@ fn translate_switch_targets<'tcx, S: UnderOwnerState<'tcx>>(
     }
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum SwitchTargets {
     /// Gives the `if` block and the `else` block
@@ -562,7 +578,7 @@ pub enum SwitchTargets {
     SwitchInt(IntUintTy, Vec<(ScalarInt, BasicBlock)>, BasicBlock),
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum FunOperand {
     /// Call to a top-level function designated by its id
@@ -571,7 +587,7 @@ pub enum FunOperand {
     Move(Place),
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> + HasMir<'tcx>>, from: rustc_middle::mir::TerminatorKind<'tcx>, state: S as s)]
 pub enum TerminatorKind {
@@ -659,7 +675,7 @@ pub enum TerminatorKind {
     },
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> + HasMir<'tcx>>, from: rustc_middle::mir::Statement<'tcx>, state: S as s)]
 pub struct Statement {
@@ -668,7 +684,7 @@ pub struct Statement {
     pub kind: Box<StatementKind>,
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> + HasMir<'tcx>>, from: rustc_middle::mir::StatementKind<'tcx>, state: S as s)]
 pub enum StatementKind {
@@ -690,7 +706,7 @@ pub enum StatementKind {
     Nop,
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> + HasMir<'tcx>>, from: rustc_middle::mir::NonDivergingIntrinsic<'tcx>, state: S as s)]
 pub enum NonDivergingIntrinsic {
@@ -698,7 +714,7 @@ pub enum NonDivergingIntrinsic {
     CopyNonOverlapping(CopyNonOverlapping),
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> + HasMir<'tcx>>, from: rustc_middle::mir::CopyNonOverlapping<'tcx>, state: S as s)]
 pub struct CopyNonOverlapping {
@@ -707,7 +723,7 @@ pub struct CopyNonOverlapping {
     pub count: Operand,
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct Place {
     /// The type of the element on which we apply the projection given by `kind`
@@ -715,7 +731,7 @@ pub struct Place {
     pub kind: PlaceKind,
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum PlaceKind {
     Local(Local),
@@ -725,7 +741,7 @@ pub enum PlaceKind {
     },
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum ProjectionElemFieldKind {
     Tuple(FieldIdx),
@@ -738,7 +754,7 @@ pub enum ProjectionElemFieldKind {
     ClosureState(FieldIdx),
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum ProjectionElem {
     Deref,
@@ -907,7 +923,7 @@ impl<'tcx, S: UnderOwnerState<'tcx> + HasMir<'tcx>> SInto<S, Place>
     }
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> + HasMir<'tcx>>, from: rustc_middle::mir::AggregateKind<'tcx>, state: S as s)]
 pub enum AggregateKind {
@@ -959,7 +975,7 @@ pub enum AggregateKind {
     RawPtr(Ty, Mutability),
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S>, from: rustc_middle::mir::CastKind, state: S as _s)]
 pub enum CastKind {
@@ -975,7 +991,7 @@ pub enum CastKind {
     Transmute,
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S>, from: rustc_middle::mir::CoercionSource, state: S as _s)]
 pub enum CoercionSource {
@@ -983,7 +999,7 @@ pub enum CoercionSource {
     Implicit,
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> + HasMir<'tcx>>, from: rustc_middle::mir::NullOp<'tcx>, state: S as s)]
 pub enum NullOp {
@@ -993,7 +1009,7 @@ pub enum NullOp {
     UbChecks,
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> + HasMir<'tcx>>, from: rustc_middle::mir::Rvalue<'tcx>, state: S as s)]
 pub enum Rvalue {
@@ -1019,7 +1035,7 @@ pub enum Rvalue {
     CopyForDeref(Place),
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> + HasMir<'tcx>>, from: rustc_middle::mir::BasicBlockData<'tcx>, state: S as s)]
 pub struct BasicBlockData {
@@ -1028,6 +1044,17 @@ pub struct BasicBlockData {
     pub is_cleanup: bool,
 }
 
+/// Reflects [`rustc_middle::mir::Location`]: the statement
+/// `statement_index` of the basic block `block`, or its terminator
+/// when `statement_index` is the number of statements of the block.
+#[derive_group(Serializers, Visitors)]
+#[derive(AdtInto, Copy, Clone, Debug, JsonSchema, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[args(<S>, from: rustc_middle::mir::Location, state: S as _s)]
+pub struct Location {
+    pub block: BasicBlock,
+    pub statement_index: usize,
+}
+
 pub type CanonicalUserTypeAnnotations =
     IndexVec<UserTypeAnnotationIndex, CanonicalUserTypeAnnotation>;
 
@@ -1038,7 +1065,7 @@ make_idx_wrapper!(rustc_middle::ty, UserTypeAnnotationIndex);
 make_idx_wrapper!(rustc_target::abi, FieldIdx);
 
 /// Reflects [`rustc_middle::mir::UnOp`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Copy, Clone, Debug, JsonSchema)]
 #[args(<'slt, S: UnderOwnerState<'slt>>, from: rustc_middle::mir::UnOp, state: S as _s)]
 pub enum UnOp {
@@ -1048,7 +1075,7 @@ pub enum UnOp {
 }
 
 /// Reflects [`rustc_middle::mir::BinOp`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Copy, Clone, Debug, JsonSchema)]
 #[args(<'slt, S: UnderOwnerState<'slt>>, from: rustc_middle::mir::BinOp, state: S as _s)]
 pub enum BinOp {
@@ -1092,7 +1119,7 @@ pub enum BinOp {
 }
 
 /// Reflects [`rustc_middle::mir::ScopeData`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> + HasThir<'tcx>>, from: rustc_middle::middle::region::ScopeData, state: S as gstate)]
 pub enum ScopeData {
@@ -1108,7 +1135,7 @@ pub enum ScopeData {
 sinto_as_usize!(rustc_middle::middle::region, FirstStatementIndex);
 
 /// Reflects [`rustc_middle::mir::BinOp`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> + HasThir<'tcx>>, from: rustc_middle::middle::region::Scope, state: S as gstate)]
 pub struct Scope {
@@ -1160,7 +1187,7 @@ impl<S> SInto<S, u64> for rustc_middle::mir::interpret::AllocId {
 /// Reflects [`rustc_middle::mir::BorrowKind`]
 #[derive(AdtInto)]
 #[args(<S>, from: rustc_middle::mir::BorrowKind, state: S as gstate)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Copy, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum BorrowKind {
     Shared,
@@ -1171,7 +1198,7 @@ pub enum BorrowKind {
 /// Reflects [`rustc_middle::mir::MutBorrowKind`]
 #[derive(AdtInto)]
 #[args(<S>, from: rustc_middle::mir::MutBorrowKind, state: S as _s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Copy, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum MutBorrowKind {
     Default,
@@ -1182,7 +1209,7 @@ pub enum MutBorrowKind {
 /// Reflects [`rustc_middle::mir::FakeBorrowKind`]
 #[derive(AdtInto)]
 #[args(<S>, from: rustc_middle::mir::FakeBorrowKind, state: S as _s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Copy, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum FakeBorrowKind {
     /// A shared (deep) borrow. Data must be immutable and is aliasable.
@@ -0,0 +1,20 @@
+use crate::prelude::*;
+
+/// A (non-doc) comment attached to an item. A comment is attached to
+/// the first item that follows it within the innermost item
+/// containing it (or within its file), and otherwise to that
+/// innermost item.
+#[derive_group(Serializers, Visitors)]
+#[derive(Clone, Debug, JsonSchema)]
+pub struct AttachedComment {
+    /// Unlike the spans of `HaxMeta::comments`, this span has 1-based
+    /// lines, just as the spans of items and statements.
+    pub span: Span,
+    pub comment: String,
+    /// The statement the comment belongs to, if any: the first
+    /// statement that follows the comment within the innermost
+    /// statement containing it, and otherwise that innermost
+    /// statement. This is a statement of the body of the item the
+    /// comment is attached to.
+    pub statement: Option<Span>,
+}
@@ -0,0 +1,215 @@
+use crate::prelude::*;
+use crate::sinto_as_usize;
+
+sinto_as_usize!(rustc_borrowck::consumers, BorrowIndex);
+
+/// A program point of a MIR body, as understood by the borrow
+/// checker: each statement and terminator has a start point, and a
+/// mid point at which it takes effect.
+#[derive_group(Serializers, Visitors)]
+#[derive(Copy, Clone, Debug, JsonSchema, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub location: Location,
+    pub mid: bool,
+}
+
+/// A loan, i.e. a borrow expression `&'region place` of the body.
+#[derive_group(Serializers, Visitors)]
+#[derive(Clone, Debug, JsonSchema)]
+pub struct Loan {
+    /// The location of the assignment `assigned_place = &borrowed_place`
+    /// that creates the loan.
+    pub reserve_location: Location,
+    pub kind: BorrowKind,
+    pub region: RegionVid,
+    pub borrowed_place: Place,
+    pub assigned_place: Place,
+}
+
+/// The facts computed by the borrow checker for a MIR body. Apart
+/// from `loans` and `live_locals`, these are the input facts of the
+/// Polonius borrow checker (see
+/// <https://rust-lang.github.io/polonius/rules/relations.html>).
+/// Regions are region inference variables: they do not appear in the
+/// types of the exported body, but are related to its locals by
+/// `use_of_var_derefs_origin` and `drop_of_var_derefs_origin`.
+#[derive_group(Serializers, Visitors)]
+#[derive(Clone, Debug, JsonSchema)]
+pub struct BorrowckFacts {
+    /// The loans of the body, indexed by [`BorrowIndex`].
+    pub loans: Vec<Loan>,
+    /// The locals live on entry of each statement and terminator.
+    pub live_locals: Vec<(Location, Vec<Local>)>,
+    /// `(region, loan, point)`: the loan `loan` with region `region`
+    /// is created at `point`.
+    pub loan_issued_at: Vec<(RegionVid, BorrowIndex, Point)>,
+    /// `(loan, point)`: the path borrowed by `loan` is overwritten at
+    /// `point`, which ends the loan.
+    pub loan_killed_at: Vec<(BorrowIndex, Point)>,
+    /// `(point, loan)`: `point` performs an access that conflicts
+    /// with `loan`.
+    pub loan_invalidated_at: Vec<(Point, BorrowIndex)>,
+    /// `(sub, sup, point)`: the region `sup` outlives the region `sub`
+    /// at `point`.
+    pub subset_base: Vec<(RegionVid, RegionVid, Point)>,
+    /// The regions that are universally quantified, i.e. the regions
+    /// of the signature of the body.
+    pub universal_region: Vec<RegionVid>,
+    /// `(sub, sup)`: the universal region `sup` is known to outlive
+    /// the universal region `sub`.
+    pub known_placeholder_subset: Vec<(RegionVid, RegionVid)>,
+    /// `(from, to)`: the control-flow graph of the body.
+    pub cfg_edge: Vec<(Point, Point)>,
+    /// `(local, point)`: `local` is used at `point`.
+    pub var_used_at: Vec<(Local, Point)>,
+    /// `(local, point)`: `local` is overwritten at `point`.
+    pub var_defined_at: Vec<(Local, Point)>,
+    /// `(local, point)`: `local` is dropped at `point`.
+    pub var_dropped_at: Vec<(Local, Point)>,
+    /// `(local, region)`: `region` appears in the type of `local`.
+    pub use_of_var_derefs_origin: Vec<(Local, RegionVid)>,
+    /// `(local, region)`: dropping `local` may access data of region
+    /// `region`.
+    pub drop_of_var_derefs_origin: Vec<(Local, RegionVid)>,
+}
+
+#[cfg(feature = "rustc")]
+mod rustc {
+    use super::*;
+    use rustc_borrowck::consumers::{get_body_with_borrowck_facts, ConsumerOptions, RichLocation};
+    use rustc_middle::{mir, ty};
+    use rustc_mir_dataflow::{impls::MaybeLiveLocals, Analysis};
+
+    impl Point {
+        fn new(location: RichLocation) -> Self {
+            let (location, mid) = match location {
+                RichLocation::Start(location) => (location, false),
+                RichLocation::Mid(location) => (location, true),
+            };
+            Point {
+                location: location.sinto(&()),
+                mid,
+            }
+        }
+    }
+
+    fn live_locals<'tcx>(
+        tcx: ty::TyCtxt<'tcx>,
+        body: &mir::Body<'tcx>,
+    ) -> Vec<(Location, Vec<Local>)> {
+        let mut cursor = MaybeLiveLocals
+            .into_engine(tcx, body)
+            .iterate_to_fixpoint()
+            .into_results_cursor(body);
+        body.basic_blocks
+            .iter_enumerated()
+            .flat_map(|(block, data)| {
+                (0..=data.statements.len()).map(move |statement_index| mir::Location {
+                    block,
+                    statement_index,
+                })
+            })
+            .map(|location| {
+                // `MaybeLiveLocals` is a backward analysis: the state
+                // after the primary effect of a statement is the state
+                // on entry of the statement.
+                cursor.seek_after_primary_effect(location);
+                let live = cursor.get().iter().map(|local| local.sinto(&())).collect();
+                (location.sinto(&()), live)
+            })
+            .collect()
+    }
+
+    impl BorrowckFacts {
+        /// Runs the borrow checker on the promoted MIR of `did` and
+        /// collects its facts. `s` must carry that MIR body. Returns
+        /// `None` if the promoted MIR (or the MIR of its promoted
+        /// constants) was stolen.
+        pub fn of<'tcx, S: UnderOwnerState<'tcx> + HasMir<'tcx>>(
+            s: &S,
+            did: rustc_span::def_id::LocalDefId,
+        ) -> Option<Self> {
+            let tcx = s.base().tcx;
+            let (body, promoted) = tcx.mir_promoted(did);
+            if body.is_stolen() || promoted.is_stolen() {
+                return None;
+            }
+            let facts = get_body_with_borrowck_facts(tcx, did, ConsumerOptions::PoloniusInputFacts);
+            let table = facts.location_table.as_ref().unwrap();
+            let input = facts.input_facts.as_ref().unwrap();
+            let p = |index| Point::new(table.to_location(index));
+            let r = |origin| ty::RegionVid::from(origin).sinto(s);
+            Some(BorrowckFacts {
+                loans: facts
+                    .borrow_set
+                    .location_map
+                    .values()
+                    .map(|data| Loan {
+                        reserve_location: data.reserve_location.sinto(s),
+                        kind: data.kind.sinto(s),
+                        region: data.region.sinto(s),
+                        borrowed_place: data.borrowed_place.sinto(s),
+                        assigned_place: data.assigned_place.sinto(s),
+                    })
+                    .collect(),
+                live_locals: live_locals(tcx, &s.mir()),
+                loan_issued_at: input
+                    .loan_issued_at
+                    .iter()
+                    .map(|(o, l, pt)| (r(*o), l.sinto(s), p(*pt)))
+                    .collect(),
+                loan_killed_at: input
+                    .loan_killed_at
+                    .iter()
+                    .map(|(l, pt)| (l.sinto(s), p(*pt)))
+                    .collect(),
+                loan_invalidated_at: input
+                    .loan_invalidated_at
+                    .iter()
+                    .map(|(pt, l)| (p(*pt), l.sinto(s)))
+                    .collect(),
+                subset_base: input
+                    .subset_base
+                    .iter()
+                    .map(|(o1, o2, pt)| (r(*o1), r(*o2), p(*pt)))
+                    .collect(),
+                universal_region: input.universal_region.iter().map(|o| r(*o)).collect(),
+                known_placeholder_subset: input
+                    .known_placeholder_subset
+                    .iter()
+                    .map(|(o1, o2)| (r(*o1), r(*o2)))
+                    .collect(),
+                cfg_edge: input
+                    .cfg_edge
+                    .iter()
+                    .map(|(p1, p2)| (p(*p1), p(*p2)))
+                    .collect(),
+                var_used_at: input
+                    .var_used_at
+                    .iter()
+                    .map(|(v, pt)| (v.sinto(s), p(*pt)))
+                    .collect(),
+                var_defined_at: input
+                    .var_defined_at
+                    .iter()
+                    .map(|(v, pt)| (v.sinto(s), p(*pt)))
+                    .collect(),
+                var_dropped_at: input
+                    .var_dropped_at
+                    .iter()
+                    .map(|(v, pt)| (v.sinto(s), p(*pt)))
+                    .collect(),
+                use_of_var_derefs_origin: input
+                    .use_of_var_derefs_origin
+                    .iter()
+                    .map(|(v, o)| (v.sinto(s), r(*o)))
+                    .collect(),
+                drop_of_var_derefs_origin: input
+                    .drop_of_var_derefs_origin
+                    .iter()
+                    .map(|(v, o)| (v.sinto(s), r(*o)))
+                    .collect(),
+            })
+        }
+    }
+}
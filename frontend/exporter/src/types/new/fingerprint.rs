@@ -0,0 +1,725 @@
+use crate::prelude::*;
+
+/// A `Fingerprint` summarizes the content of an item: its signature,
+/// its body, and the content of every item it (transitively) depends
+/// on. Two exports of an item have the same fingerprint when none of
+/// those changed. Fingerprints are independent of span positions,
+/// of ids local to a Rustc session or to an id table, and of the
+/// random `ItemUid`s hax-lib attaches to items.
+#[derive_group(Serializers, Visitors)]
+#[derive(Copy, Clone, Debug, Default, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(transparent)]
+pub struct Fingerprint(u64);
+
+impl<Body: IsBody + visitors::Visitable> Item<Body> {
+    /// Sets the fingerprints of this item and of its nested items to
+    /// the ones found in `fingerprints`.
+    pub fn set_fingerprints(&mut self, fingerprints: &HashMap<DefId, Fingerprint>) {
+        use visitors::Visitable;
+        self.rewrite(|item: &mut Item<Body>| {
+            if let Some(fingerprint) = fingerprints.get(&item.owner_id) {
+                item.fingerprint = *fingerprint;
+            }
+        });
+    }
+}
+
+use crate::visitors::{Event, Visitable, Visitor};
+use serde::ser::{self, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::hash::Hasher;
+
+type DeterministicHasher =
+    crate::deterministic_hash::DeterministicHasher<crate::deterministic_hash::Fnv1a64>;
+
+/// The content of an item, as seen by fingerprinting.
+struct Content {
+    hash: u64,
+    /// The `DefId`s the item mentions (but not the parents of
+    /// those `DefId`s).
+    dependencies: Vec<DefId>,
+    /// The `ItemUid`s the item mentions: items sharing an uid are
+    /// linked by a hax-lib attribute (e.g. a function and its
+    /// precondition).
+    uids: BTreeSet<String>,
+}
+
+/// Collects the outermost `DefId`s of a value.
+struct DefIdCollector(usize, Vec<DefId>);
+impl Visitor for DefIdCollector {
+    fn visit(&mut self, node: &dyn std::any::Any, event: Event) {
+        let Some(def_id) = node.downcast_ref::<DefId>() else {
+            return;
+        };
+        match event {
+            Event::Enter if self.0 == 0 => {
+                self.1.push(def_id.clone());
+                self.0 += 1
+            }
+            Event::Enter => self.0 += 1,
+            Event::Exit => self.0 -= 1,
+        }
+    }
+}
+
+/// The fields left out of fingerprints, with the struct they belong
+/// to.
+const SKIPPED_FIELDS: &[(&str, &str)] = &[
+    ("Item", "fingerprint"),
+    // The ids of `id_table::Node`s.
+    ("NodeRepr", "id"),
+    // Rustc's `CrateNum` and `DefIndex`.
+    ("DefIdContents", "index"),
+    // Rustc's `AttrId`.
+    ("Attribute", "id"),
+    // `(owner index, local id)` pairs.
+    ("Decorated", "hir_id"),
+    ("HirId", "local_id"),
+    ("SpanContents", "lo"),
+    ("SpanContents", "hi"),
+    ("SpanContents", "filename"),
+];
+
+/// The payload `PAYLOAD` of the arguments `args` of a hax-lib
+/// attribute `#[_hax::json(PAYLOAD)]`.
+fn hax_payload(args: &Value) -> Option<Value> {
+    let tokens = args.get("Delimited")?.get("tokens")?.as_str()?;
+    let payload: String = serde_json::from_str(tokens).ok()?;
+    serde_json::from_str(&payload).ok()
+}
+
+/// Replaces the `ItemUid`s (`{"uid": ...}` objects) of a hax-lib
+/// payload with a placeholder, collecting them in `uids`.
+fn mask_uids(payload: &mut Value, uids: &mut BTreeSet<String>) {
+    match payload {
+        Value::Object(object) => {
+            if let (1, Some(Value::String(uid))) = (object.len(), object.get_mut("uid")) {
+                uids.insert(std::mem::replace(uid, "<uid>".into()));
+                return;
+            }
+            for value in object.values_mut() {
+                mask_uids(value, uids)
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                mask_uids(value, uids)
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Hashes a value through its `Serialize` implementation, leaving
+/// out the [`SKIPPED_FIELDS`]. The `ItemUid`s of hax-lib attributes
+/// are masked and collected.
+#[derive(Default)]
+struct ContentHasher {
+    hasher: DeterministicHasher,
+    uids: BTreeSet<String>,
+    /// Whether the last `AttrItem` hashed is a hax-lib attribute.
+    hax_attribute: bool,
+}
+
+/// Tags the kinds of serialized values, so that the hashes of
+/// different values cannot collide by construction.
+#[repr(u8)]
+enum Tag {
+    Bool,
+    Signed,
+    Unsigned,
+    Float,
+    Char,
+    Str,
+    Bytes,
+    None,
+    Some,
+    Unit,
+    Variant,
+    Seq,
+    Map,
+    Struct,
+    End,
+}
+
+impl ContentHasher {
+    fn tag(&mut self, tag: Tag) {
+        self.hasher.write_u8(tag as u8)
+    }
+
+    fn str(&mut self, s: &str) {
+        self.hasher.write_usize(s.len());
+        self.hasher.write(s.as_bytes())
+    }
+
+    fn variant(&mut self, variant: &str) -> &mut Self {
+        self.tag(Tag::Variant);
+        self.str(variant);
+        self
+    }
+}
+
+/// Hashes the fields of the struct `name`.
+struct StructHasher<'a> {
+    content: &'a mut ContentHasher,
+    name: &'static str,
+}
+
+impl ser::SerializeStruct for StructHasher<'_> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        let content = &mut *self.content;
+        let hax_tokens = content.hax_attribute
+            && key == "tokens"
+            && matches!(self.name, "AttrItem" | "NormalAttr");
+        if hax_tokens || SKIPPED_FIELDS.contains(&(self.name, key)) {
+            return Ok(());
+        }
+        content.str(key);
+        match (self.name, key) {
+            ("AttrItem", "path") => {
+                content.hax_attribute = serde_json::to_value(value)? == "_hax::json";
+            }
+            ("AttrItem", "args") if content.hax_attribute => {
+                if let Some(mut payload) = hax_payload(&serde_json::to_value(value)?) {
+                    mask_uids(&mut payload, &mut content.uids);
+                    return payload.serialize(content);
+                }
+            }
+            _ => (),
+        }
+        value.serialize(content)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        self.content.tag(Tag::End);
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for StructHasher<'_> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        ser::SerializeStruct::end(self)
+    }
+}
+
+macro_rules! impl_compound {
+    ($($trait:ident::$method:ident),*) => {$(
+        impl ser::$trait for &mut ContentHasher {
+            type Ok = ();
+            type Error = serde_json::Error;
+
+            fn $method<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+                value.serialize(&mut **self)
+            }
+
+            fn end(self) -> Result<(), Self::Error> {
+                self.tag(Tag::End);
+                Ok(())
+            }
+        }
+    )*};
+}
+impl_compound!(
+    SerializeSeq::serialize_element,
+    SerializeTuple::serialize_element,
+    SerializeTupleStruct::serialize_field,
+    SerializeTupleVariant::serialize_field
+);
+
+impl ser::SerializeMap for &mut ContentHasher {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Self::Error> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Self::Error> {
+        self.tag(Tag::End);
+        Ok(())
+    }
+}
+
+impl<'a> ser::Serializer for &'a mut ContentHasher {
+    type Ok = ();
+    type Error = serde_json::Error;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = StructHasher<'a>;
+    type SerializeStructVariant = StructHasher<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Self::Error> {
+        self.tag(Tag::Bool);
+        self.hasher.write_u8(v as u8);
+        Ok(())
+    }
+    fn serialize_i8(self, v: i8) -> Result<(), Self::Error> {
+        self.serialize_i128(v as i128)
+    }
+    fn serialize_i16(self, v: i16) -> Result<(), Self::Error> {
+        self.serialize_i128(v as i128)
+    }
+    fn serialize_i32(self, v: i32) -> Result<(), Self::Error> {
+        self.serialize_i128(v as i128)
+    }
+    fn serialize_i64(self, v: i64) -> Result<(), Self::Error> {
+        self.serialize_i128(v as i128)
+    }
+    fn serialize_i128(self, v: i128) -> Result<(), Self::Error> {
+        self.tag(Tag::Signed);
+        self.hasher.write_i128(v);
+        Ok(())
+    }
+    fn serialize_u8(self, v: u8) -> Result<(), Self::Error> {
+        self.serialize_u128(v as u128)
+    }
+    fn serialize_u16(self, v: u16) -> Result<(), Self::Error> {
+        self.serialize_u128(v as u128)
+    }
+    fn serialize_u32(self, v: u32) -> Result<(), Self::Error> {
+        self.serialize_u128(v as u128)
+    }
+    fn serialize_u64(self, v: u64) -> Result<(), Self::Error> {
+        self.serialize_u128(v as u128)
+    }
+    fn serialize_u128(self, v: u128) -> Result<(), Self::Error> {
+        self.tag(Tag::Unsigned);
+        self.hasher.write_u128(v);
+        Ok(())
+    }
+    fn serialize_f32(self, v: f32) -> Result<(), Self::Error> {
+        self.serialize_f64(v as f64)
+    }
+    fn serialize_f64(self, v: f64) -> Result<(), Self::Error> {
+        self.tag(Tag::Float);
+        self.hasher.write_u64(v.to_bits());
+        Ok(())
+    }
+    fn serialize_char(self, v: char) -> Result<(), Self::Error> {
+        self.tag(Tag::Char);
+        self.hasher.write_u32(v as u32);
+        Ok(())
+    }
+    fn serialize_str(self, v: &str) -> Result<(), Self::Error> {
+        self.tag(Tag::Str);
+        self.str(v);
+        Ok(())
+    }
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Self::Error> {
+        self.tag(Tag::Bytes);
+        self.hasher.write_usize(v.len());
+        self.hasher.write(v);
+        Ok(())
+    }
+    fn serialize_none(self) -> Result<(), Self::Error> {
+        self.tag(Tag::None);
+        Ok(())
+    }
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), Self::Error> {
+        self.tag(Tag::Some);
+        value.serialize(self)
+    }
+    fn serialize_unit(self) -> Result<(), Self::Error> {
+        self.tag(Tag::Unit);
+        Ok(())
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Self::Error> {
+        self.serialize_unit()
+    }
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<(), Self::Error> {
+        self.variant(variant).serialize_unit()
+    }
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        value.serialize(self.variant(variant))
+    }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self, Self::Error> {
+        self.tag(Tag::Seq);
+        Ok(self)
+    }
+    fn serialize_tuple(self, len: usize) -> Result<Self, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Self, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self, Self::Error> {
+        self.variant(variant).serialize_seq(Some(len))
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self, Self::Error> {
+        self.tag(Tag::Map);
+        Ok(self)
+    }
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        _len: usize,
+    ) -> Result<StructHasher<'a>, Self::Error> {
+        self.tag(Tag::Struct);
+        Ok(StructHasher {
+            content: self,
+            name,
+        })
+    }
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<StructHasher<'a>, Self::Error> {
+        self.variant(variant).serialize_struct(name, len)
+    }
+}
+
+fn dependencies<T: Visitable>(value: &T) -> Vec<DefId> {
+    let mut collector = DefIdCollector(0, vec![]);
+    value.drive(&mut collector);
+    collector.1
+}
+
+/// Hashes `value`, returning its hash and its `ItemUid`s.
+fn hash<T: Serialize>(value: &T) -> (u64, BTreeSet<String>) {
+    let mut hasher = ContentHasher::default();
+    value
+        .serialize(&mut hasher)
+        .expect("hashing a value cannot fail");
+    (hasher.hasher.finish(), hasher.uids)
+}
+
+fn content<T: Serialize + Visitable>(value: &T) -> Content {
+    let (hash, uids) = hash(value);
+    Content {
+        hash,
+        dependencies: dependencies(value),
+        uids,
+    }
+}
+
+/// Hashes the content hash of an item, then the (sorted) hashes of
+/// its dependencies.
+fn combine(own: u64, dependencies: BTreeSet<u64>) -> Fingerprint {
+    let mut hasher = DeterministicHasher::default();
+    hasher.write_u64(own);
+    for hash in dependencies {
+        hasher.write_u64(hash)
+    }
+    Fingerprint(hasher.finish())
+}
+
+/// The strongly connected components of the graph whose node `i`
+/// has the successors `edges[i]`, in reverse topological order:
+/// the successors of a component come before it (Tarjan's
+/// algorithm, without recursion).
+fn sccs(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    const UNVISITED: usize = usize::MAX;
+    let mut index = vec![UNVISITED; edges.len()];
+    let mut low = vec![0; edges.len()];
+    let mut on_stack = vec![false; edges.len()];
+    let mut stack = vec![];
+    let mut sccs = vec![];
+    let mut next_index = 0;
+    for root in 0..edges.len() {
+        if index[root] != UNVISITED {
+            continue;
+        }
+        // The nodes being visited, with the position of the next
+        // successor to visit.
+        let mut calls = vec![(root, 0)];
+        index[root] = next_index;
+        low[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+        while let Some(&(node, position)) = calls.last() {
+            if let Some(&next) = edges[node].get(position) {
+                calls.last_mut().unwrap().1 += 1;
+                if index[next] == UNVISITED {
+                    index[next] = next_index;
+                    low[next] = next_index;
+                    next_index += 1;
+                    stack.push(next);
+                    on_stack[next] = true;
+                    calls.push((next, 0));
+                } else if on_stack[next] {
+                    low[node] = low[node].min(index[next]);
+                }
+                continue;
+            }
+            calls.pop();
+            if let Some(&(caller, _)) = calls.last() {
+                low[caller] = low[caller].min(low[node]);
+            }
+            if low[node] == index[node] {
+                let mut scc = vec![];
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    scc.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                sccs.push(scc);
+            }
+        }
+    }
+    sccs
+}
+
+/// Fingerprints the nodes of a dependency graph: node `i` has the
+/// content hash `own[i]`, depends on the nodes `edges[i]` and on
+/// external definitions with content hashes `external[i]`. Each
+/// strongly connected component is hashed once, from the hashes of
+/// its members and of its dependencies; a node combines its own
+/// hash with the hash of its component.
+fn fingerprint_graph(
+    own: &[u64],
+    external: &[BTreeSet<u64>],
+    edges: &[Vec<usize>],
+) -> Vec<Fingerprint> {
+    let mut component = vec![0; own.len()];
+    let mut component_hashes = vec![];
+    for (i, scc) in sccs(edges).into_iter().enumerate() {
+        for &node in &scc {
+            component[node] = i;
+        }
+        let members: BTreeSet<u64> = scc.iter().map(|&node| own[node]).collect();
+        let dependencies: BTreeSet<u64> = scc
+            .iter()
+            .flat_map(|&node| {
+                let successors = edges[node]
+                    .iter()
+                    .filter(|&&next| component[next] != i)
+                    .map(|&next| component_hashes[component[next]]);
+                external[node].iter().copied().chain(successors)
+            })
+            .collect();
+        let mut hasher = DeterministicHasher::default();
+        hasher.write_usize(members.len());
+        for hash in members.into_iter().chain(dependencies) {
+            hasher.write_u64(hash)
+        }
+        component_hashes.push(hasher.finish());
+    }
+    (0..own.len())
+        .map(|node| {
+            combine(
+                own[node],
+                BTreeSet::from([component_hashes[component[node]]]),
+            )
+        })
+        .collect()
+}
+
+/// Computes the fingerprints of items exported one by one: items
+/// are added with [`Fingerprinter::add`], and their fingerprints
+/// are computed by [`Fingerprinter::finish`], once every item was
+/// added. Only the content of the items is retained in the
+/// meantime.
+#[derive(Default)]
+pub struct Fingerprinter {
+    contents: HashMap<DefId, Content>,
+}
+
+impl Fingerprinter {
+    /// Adds the item `item` (and its nested items).
+    pub fn add<Body: IsBody + Visitable + serde::Serialize>(&mut self, item: &Item<Body>) {
+        item.for_each(|item: &Item<Body>| {
+            if !self.contents.contains_key(&item.owner_id) {
+                self.contents.insert(item.owner_id.clone(), content(item));
+            }
+        });
+    }
+
+    /// Computes the fingerprint of every added item from the added
+    /// items only: the dependencies of an item are the added items
+    /// it mentions, and the items linked to it by an `ItemUid`.
+    /// Other `DefId`s contribute only their path.
+    pub fn finish(self) -> HashMap<DefId, Fingerprint> {
+        let (owner_ids, contents): (Vec<DefId>, Vec<Content>) = self.contents.into_iter().unzip();
+        let nodes: HashMap<&DefId, usize> = owner_ids.iter().zip(0..).collect();
+        // The exported item a `DefId` belongs to.
+        let owner = |def_id: &DefId| {
+            std::iter::successors(Some(def_id), |def_id| def_id.parent.as_ref())
+                .find_map(|def_id| nodes.get(def_id).copied())
+        };
+        let mut by_uid: HashMap<&String, Vec<usize>> = HashMap::new();
+        for (node, content) in contents.iter().enumerate() {
+            for uid in &content.uids {
+                by_uid.entry(uid).or_default().push(node)
+            }
+        }
+        let mut external = vec![BTreeSet::new(); contents.len()];
+        let mut edges = vec![vec![]; contents.len()];
+        for (node, item) in contents.iter().enumerate() {
+            for dependency in &item.dependencies {
+                match owner(dependency) {
+                    Some(next) => edges[node].push(next),
+                    None => {
+                        external[node].insert(content(dependency).hash);
+                    }
+                }
+            }
+            for uid in &item.uids {
+                edges[node].extend(&by_uid[uid])
+            }
+        }
+        let own: Vec<u64> = contents.iter().map(|content| content.hash).collect();
+        owner_ids
+            .into_iter()
+            .zip(fingerprint_graph(&own, &external, &edges))
+            .collect()
+    }
+}
+
+/// Computes the fingerprint of every item in `items` (including
+/// nested items), see [`Fingerprinter`].
+pub fn fingerprint_items<Body: IsBody + Visitable + serde::Serialize>(items: &mut [Item<Body>]) {
+    let mut fingerprinter = Fingerprinter::default();
+    for item in items.iter() {
+        fingerprinter.add(item);
+    }
+    let fingerprints = fingerprinter.finish();
+    for item in items.iter_mut() {
+        item.set_fingerprints(&fingerprints);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A hax-lib attribute `#[_hax::json(payload)]`.
+    fn hax_attribute(payload: Value) -> AttrItem {
+        let tokens = Value::String(payload.to_string()).to_string();
+        AttrItem {
+            path: "_hax::json".into(),
+            args: AttrArgs::Delimited(DelimArgs {
+                dspan: DelimSpan::DelimSpan { todo: "".into() },
+                delim: Delimiter::Parenthesis,
+                tokens: tokens.clone(),
+            }),
+            tokens: Some(tokens),
+        }
+    }
+
+    #[test]
+    fn only_item_uids_are_masked() {
+        let uid_a = "0123456789abcdef0123456789abcdef";
+        let uid_b = "fedcba9876543210fedcba9876543210";
+        let (a, uids) = hash(&hax_attribute(serde_json::json!({"Uid": {"uid": uid_a}})));
+        let (b, _) = hash(&hax_attribute(serde_json::json!({"Uid": {"uid": uid_b}})));
+        assert_eq!(a, b);
+        assert_eq!(uids, BTreeSet::from([uid_a.to_string()]));
+        // Hexadecimal strings outside of `ItemUid`s are data.
+        let (a, uids) = hash(&serde_json::json!({"Str": uid_a}));
+        let (b, _) = hash(&serde_json::json!({"Str": uid_b}));
+        assert_ne!(a, b);
+        assert!(uids.is_empty());
+    }
+
+    #[test]
+    fn spans_are_masked() {
+        let span = |line| {
+            Span::detached(SpanContents {
+                lo: Loc { line, col: 0 },
+                hi: Loc { line, col: 1 },
+                filename: FileName::Anon(line as u64),
+                rust_span_data: None,
+            })
+        };
+        assert_eq!(hash(&span(1)), hash(&span(2)));
+        // The other fields of a struct are hashed.
+        assert_ne!(
+            hash(&Loc { line: 1, col: 0 }),
+            hash(&Loc { line: 2, col: 0 })
+        );
+    }
+
+    #[test]
+    fn the_hash_function_is_fnv_1a() {
+        let mut hasher = crate::deterministic_hash::Fnv1a64::default();
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn sccs_are_in_reverse_topological_order() {
+        // 0 -> 1 <-> 2 -> 3
+        let edges = vec![vec![1], vec![2], vec![1, 3], vec![]];
+        let mut sccs = sccs(&edges);
+        sccs.iter_mut().for_each(|scc| scc.sort());
+        assert_eq!(sccs, vec![vec![3], vec![1, 2], vec![0]]);
+    }
+
+    #[test]
+    fn fingerprints_follow_dependencies() {
+        let none = || vec![BTreeSet::new(); 2];
+        let [a, b] = [1, 2];
+        let a_to_b = fingerprint_graph(&[a, b], &none(), &[vec![1], vec![]]);
+        let b_to_a = fingerprint_graph(&[a, b], &none(), &[vec![], vec![0]]);
+        assert_ne!(a_to_b[0], b_to_a[0]);
+        assert_ne!(a_to_b[1], b_to_a[1]);
+        // A change in `b` changes the fingerprint of `a`.
+        let a_to_c = fingerprint_graph(&[a, 3], &none(), &[vec![1], vec![]]);
+        assert_ne!(a_to_b[0], a_to_c[0]);
+        // Items of a cycle depend on each other.
+        let cycle = fingerprint_graph(&[a, b], &none(), &[vec![1], vec![0]]);
+        let cycle_c = fingerprint_graph(&[a, 3], &none(), &[vec![1], vec![0]]);
+        assert_ne!(cycle[0], cycle_c[0]);
+        assert_ne!(cycle[0], cycle[1]);
+        // So do external dependencies.
+        let external = vec![BTreeSet::new(), BTreeSet::from([4])];
+        let a_to_b_ext = fingerprint_graph(&[a, b], &external, &[vec![1], vec![]]);
+        assert_ne!(a_to_b[0], a_to_b_ext[0]);
+    }
+}
@@ -9,7 +9,7 @@ use rustc_middle::ty;
 use rustc_span::def_id::DefId as RDefId;
 
 /// Gathers a lot of definition information about a [`rustc_hir::def_id::DefId`].
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct FullDef<Body = ()> {
     pub def_id: DefId,
@@ -89,7 +89,7 @@ where
 /// Important: the `owner_id()` must be the id of this definition.
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: rustc_hir::def::DefKind, state: S as s, where Body: IsBody + TypeMappable)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum FullDefKind<Body> {
     // Types
@@ -289,6 +289,10 @@ pub enum FullDefKind<Body> {
         ty: Ty,
         #[value(s.owner_id().as_local().map(|ldid| Body::body(ldid, s)))]
         body: Option<Body>,
+        /// The value of the constant, evaluated by Rustc (see
+        /// [`eval_global_value`]).
+        #[value(eval_global_value::<Body, _>(s, s.owner_id()))]
+        value: Option<ConstantExpr>,
     },
     /// Associated constant: `trait MyTrait { const ASSOC: usize; }`
     AssocConst {
@@ -304,6 +308,11 @@ pub enum FullDefKind<Body> {
         ty: Ty,
         #[value(s.owner_id().as_local().map(|ldid| Body::body(ldid, s)))]
         body: Option<Body>,
+        /// The value of the constant, evaluated by Rustc (see
+        /// [`eval_global_value`]). This is `None` for the constants of
+        /// traits and of generic implementations.
+        #[value(eval_global_value::<Body, _>(s, s.owner_id()))]
+        value: Option<ConstantExpr>,
     },
     /// Anonymous constant, e.g. the `1 + 2` in `[u8; 1 + 2]`
     AnonConst,
@@ -324,6 +333,10 @@ pub enum FullDefKind<Body> {
         ty: Ty,
         #[value(s.owner_id().as_local().map(|ldid| Body::body(ldid, s)))]
         body: Option<Body>,
+        /// The initial value of the static, evaluated by Rustc (see
+        /// [`eval_global_value`]).
+        #[value(eval_global_value::<Body, _>(s, s.owner_id()))]
+        value: Option<ConstantExpr>,
     },
 
     // Crates and modules
@@ -376,7 +389,7 @@ pub enum FullDefKind<Body> {
 
 /// An associated item in a trait impl. This can be an item provided by the trait impl, or an item
 /// that reuses the trait decl default value.
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct ImplAssocItem<Body> {
     pub name: Symbol,
@@ -398,7 +411,7 @@ pub struct ImplAssocItem<Body> {
     pub value: ImplAssocItemValue<Body>,
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum ImplAssocItemValue<Body> {
     /// The item is provided by the trait impl.
@@ -832,10 +845,13 @@ fn get_generic_predicates<'tcx, S: UnderOwnerState<'tcx>>(
 ) -> GenericPredicates {
     let predicates = predicates_defined_on(s.base().tcx, def_id);
     let pred_list = normalize_trait_clauses(s, predicates.predicates);
-    GenericPredicates {
-        parent: predicates.parent.sinto(s),
-        predicates: pred_list,
-    }
+    GenericPredicates::new(
+        s,
+        GenericPredicatesContents {
+            parent: predicates.parent.sinto(s),
+            predicates: pred_list,
+        },
+    )
 }
 
 /// Gets the predicates defined on the given associated type and processes them with
@@ -858,8 +874,11 @@ fn get_item_predicates<'tcx, S: UnderOwnerState<'tcx>>(s: &S, def_id: RDefId) ->
         _ => Vec::new(),
     };
     let predicates = normalize_trait_clauses(s, predicates.as_slice());
-    GenericPredicates {
-        parent: Some(parent_id.sinto(s)),
-        predicates,
-    }
+    GenericPredicates::new(
+        s,
+        GenericPredicatesContents {
+            parent: Some(parent_id.sinto(s)),
+            predicates,
+        },
+    )
 }
@@ -2,7 +2,7 @@ use crate::prelude::*;
 
 /// Meta-informations about an `impl<GENERICS[: PREDICATES]> TRAIT for
 /// TYPE where PREDICATES {}`
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct ImplInfos {
     pub generics: TyGenerics,
@@ -1,6 +1,6 @@
 use crate::prelude::*;
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct ItemAttributes {
     attributes: Vec<Attribute>,
@@ -0,0 +1,164 @@
+use crate::prelude::*;
+
+/// The memory layout of a type, as computed by Rustc. Sizes,
+/// alignments and offsets are in bytes.
+#[derive_group(Serializers, Visitors)]
+#[derive(Clone, Debug, JsonSchema)]
+pub struct Layout {
+    pub size: u64,
+    /// The ABI alignment.
+    pub align: u64,
+    pub uninhabited: bool,
+    /// The offsets of the fields, in declaration order. For an enum
+    /// with several variants, this is the offset of the tag only; the
+    /// offsets of the fields of each variant are found in `variants`.
+    pub field_offsets: Vec<u64>,
+    pub variants: LayoutVariants,
+}
+
+/// Reflects [`rustc_abi::Variants`]
+#[derive_group(Serializers, Visitors)]
+#[derive(Clone, Debug, JsonSchema)]
+pub enum LayoutVariants {
+    /// A struct, a union, or an enum with one inhabited variant
+    /// `index` (no tag is stored).
+    Single { index: VariantIdx },
+    /// An enum whose variant is encoded by a tag, found at
+    /// `field_offsets[0]`.
+    Multiple {
+        tag: TagLayout,
+        encoding: TagEncoding,
+        variants: Vec<VariantLayout>,
+    },
+}
+
+/// The integer storing the tag of an enum.
+#[derive_group(Serializers, Visitors)]
+#[derive(Clone, Debug, JsonSchema)]
+pub struct TagLayout {
+    pub size: u64,
+    pub signed: bool,
+}
+
+/// Reflects [`rustc_abi::TagEncoding`]
+#[derive_group(Serializers, Visitors)]
+#[derive(Clone, Debug, JsonSchema)]
+pub enum TagEncoding {
+    /// The tag is the discriminant of the variant.
+    Direct,
+    /// The tag is stored in the niche of a field of the variant
+    /// `untagged_variant`, i.e. in values that field cannot take. The
+    /// variants `niche_variants_start..=niche_variants_end` are
+    /// encoded as `niche_start + (index - niche_variants_start)`
+    /// (wrapping); any other tag value means `untagged_variant`.
+    Niche {
+        untagged_variant: VariantIdx,
+        niche_variants_start: VariantIdx,
+        niche_variants_end: VariantIdx,
+        #[serde(with = "serialize_int::unsigned")]
+        #[schemars(with = "String")]
+        niche_start: u128,
+    },
+}
+
+/// The layout of one variant of an enum.
+#[derive_group(Serializers, Visitors)]
+#[derive(Clone, Debug, JsonSchema)]
+pub struct VariantLayout {
+    pub size: u64,
+    pub align: u64,
+    pub uninhabited: bool,
+    /// The offsets of the fields of the variant, in declaration order.
+    pub field_offsets: Vec<u64>,
+}
+
+#[cfg(feature = "rustc")]
+mod rustc {
+    use super::*;
+    use rustc_abi::{FieldIdx, LayoutS, Primitive, Variants};
+    use rustc_middle::ty;
+    use rustc_target::abi::VariantIdx as RVariantIdx;
+
+    fn field_offsets(layout: &LayoutS<FieldIdx, RVariantIdx>) -> Vec<u64> {
+        (0..layout.fields.count())
+            .map(|i| layout.fields.offset(i).bytes())
+            .collect()
+    }
+
+    impl Layout {
+        /// The layout of the type `ty`, if it is known: `None` if
+        /// `ty` is generic or too big.
+        pub fn of<'tcx, S: BaseState<'tcx>>(s: &S, ty: ty::Ty<'tcx>) -> Option<Self> {
+            let tcx = s.base().tcx;
+            let layout = tcx.layout_of(ty::ParamEnv::reveal_all().and(ty)).ok()?;
+            let layout = layout.layout.0 .0;
+            let variants = match &layout.variants {
+                Variants::Single { index } => LayoutVariants::Single {
+                    index: index.as_usize(),
+                },
+                Variants::Multiple {
+                    tag,
+                    tag_encoding,
+                    variants,
+                    ..
+                } => LayoutVariants::Multiple {
+                    tag: TagLayout {
+                        size: tag.size(&tcx).bytes(),
+                        signed: matches!(tag.primitive(), Primitive::Int(_, true)),
+                    },
+                    encoding: match tag_encoding {
+                        rustc_abi::TagEncoding::Direct => TagEncoding::Direct,
+                        rustc_abi::TagEncoding::Niche {
+                            untagged_variant,
+                            niche_variants,
+                            niche_start,
+                        } => TagEncoding::Niche {
+                            untagged_variant: untagged_variant.as_usize(),
+                            niche_variants_start: niche_variants.start().as_usize(),
+                            niche_variants_end: niche_variants.end().as_usize(),
+                            niche_start: *niche_start,
+                        },
+                    },
+                    variants: variants
+                        .iter()
+                        .map(|variant| VariantLayout {
+                            size: variant.size.bytes(),
+                            align: variant.align.abi.bytes(),
+                            uninhabited: variant.abi.is_uninhabited(),
+                            field_offsets: field_offsets(variant),
+                        })
+                        .collect(),
+                },
+            };
+            Some(Layout {
+                size: layout.size.bytes(),
+                align: layout.align.abi.bytes(),
+                uninhabited: layout.abi.is_uninhabited(),
+                field_offsets: field_offsets(layout),
+                variants,
+            })
+        }
+    }
+}
+
+/// The definitions of the structs, enums and unions of the crate.
+/// Their layouts are computed when the option `layouts` is set.
+#[cfg(feature = "rustc")]
+pub fn adt_defs<'tcx, S: BaseState<'tcx>>(s: &S) -> Vec<AdtDef> {
+    use rustc_hir::def::DefKind;
+    let tcx = s.base().tcx;
+    tcx.hir_crate_items(())
+        .definitions()
+        .map(|ldid| ldid.to_def_id())
+        .filter(|def_id| {
+            matches!(
+                tcx.def_kind(def_id),
+                DefKind::Struct | DefKind::Enum | DefKind::Union
+            )
+        })
+        .map(|def_id| {
+            let s = &with_owner_id(s.base(), (), (), def_id);
+            tcx.adt_def(def_id).sinto(s)
+        })
+        .collect()
+}
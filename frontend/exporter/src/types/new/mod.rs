@@ -1,16 +1,26 @@
 //! This module contains type definitions that have no equivalent in
 //! Rustc.
 
+mod attached_comment;
+mod borrowck_facts;
+mod fingerprint;
 mod full_def;
 mod impl_infos;
 mod item_attributes;
+mod layout;
+mod mono_item;
 mod predicate_id;
 mod typed_constant_kind;
 mod variant_infos;
 
+pub use attached_comment::*;
+pub use borrowck_facts::*;
+pub use fingerprint::*;
 pub use full_def::*;
 pub use impl_infos::*;
 pub use item_attributes::*;
+pub use layout::*;
+pub use mono_item::*;
 pub use predicate_id::*;
 pub use typed_constant_kind::*;
 pub use variant_infos::*;
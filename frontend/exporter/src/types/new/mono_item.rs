@@ -0,0 +1,176 @@
+use crate::prelude::*;
+
+#[cfg(feature = "rustc")]
+use rustc_middle::ty;
+#[cfg(feature = "rustc")]
+use rustc_span::def_id::DefId as RDefId;
+
+/// A monomorphized copy of a function: the function `def_id`
+/// instantiated with `generic_args`. Every type in `def` is concrete,
+/// and every trait reference is resolved to an
+/// [`ImplExprAtom::Concrete`] or [`ImplExprAtom::Builtin`]
+/// implementation. Calls to trait methods are resolved to the methods
+/// of the implementations: the function called by an expression
+/// `Call { fun, generic_args, .. }` in `def` is the monomorphized
+/// copy of `fun` for `generic_args`, unless `fun` is not a local
+/// function.
+#[derive_group(Serializers, Visitors)]
+#[derive(Clone, Debug, JsonSchema)]
+pub struct MonoItem<Body: IsBody> {
+    pub def_id: DefId,
+    pub generic_args: Vec<GenericArg>,
+    pub span: Span,
+    pub def: FnDef<Body>,
+}
+
+/// Instantiates `value` with `args`, normalizing it and erasing its
+/// regions.
+#[cfg(feature = "rustc")]
+fn instantiate<'tcx, T: ty::TypeFoldable<ty::TyCtxt<'tcx>> + Clone>(
+    tcx: ty::TyCtxt<'tcx>,
+    args: ty::GenericArgsRef<'tcx>,
+    value: T,
+) -> T {
+    let value = ty::EarlyBinder::bind(value).instantiate(tcx, args);
+    tcx.try_normalize_erasing_regions(ty::ParamEnv::reveal_all(), value.clone())
+        .unwrap_or(value)
+}
+
+/// The generic arguments of the function being monomorphized, if `s`
+/// is under it (or one of its closures).
+#[cfg(feature = "rustc")]
+fn mono_args<'tcx, S: UnderOwnerState<'tcx>>(s: &S) -> Option<ty::GenericArgsRef<'tcx>> {
+    let (def_id, args) = s.base().instantiation?;
+    (s.base().tcx.typeck_root_def_id(s.owner_id()) == def_id).then_some(args)
+}
+
+/// If `s` is under the function being monomorphized (or one of its
+/// closures), instantiates `value` with the generic arguments of the
+/// function. Otherwise, returns `value` as is.
+#[cfg(feature = "rustc")]
+pub(crate) fn instantiate_mono<'tcx, S, T>(s: &S, value: T) -> T
+where
+    S: UnderOwnerState<'tcx>,
+    T: ty::TypeFoldable<ty::TyCtxt<'tcx>> + Clone,
+{
+    use ty::TypeVisitableExt;
+    // Outside of monomorphized copies, `instantiation` is `None`.
+    if s.base().instantiation.is_none() || !value.has_param() {
+        return value;
+    }
+    match mono_args(s) {
+        Some(args) => instantiate(s.base().tcx, args, value),
+        None => value,
+    }
+}
+
+/// In a monomorphized copy, resolves a call to the function `def_id`
+/// with `generics` to the function actually called: calls to trait
+/// methods are resolved to the methods of the implementations.
+/// Otherwise, returns `def_id` and `generics` as is.
+#[cfg(feature = "rustc")]
+pub(crate) fn resolve_mono_call<'tcx, S: UnderOwnerState<'tcx>>(
+    s: &S,
+    def_id: RDefId,
+    generics: ty::GenericArgsRef<'tcx>,
+) -> (RDefId, ty::GenericArgsRef<'tcx>) {
+    if mono_args(s).is_none() {
+        return (def_id, generics);
+    }
+    let tcx = s.base().tcx;
+    let args = instantiate_mono(s, generics);
+    match ty::Instance::try_resolve(tcx, ty::ParamEnv::reveal_all(), def_id, args) {
+        Ok(Some(instance)) if matches!(instance.def, ty::InstanceKind::Item(_)) => {
+            (instance.def_id(), instance.args)
+        }
+        _ => (def_id, generics),
+    }
+}
+
+/// The instances of local functions `instance` refers to, resolving
+/// trait methods.
+#[cfg(feature = "rustc")]
+fn callees<'tcx, S: BaseState<'tcx>>(
+    s: &S,
+    instance: ty::Instance<'tcx>,
+) -> Vec<ty::Instance<'tcx>> {
+    use rustc_middle::thir::ExprKind;
+    let tcx = s.base().tcx;
+    // The body of `instance` and the bodies of its closures.
+    let mut bodies = vec![instance.def_id()];
+    let mut callees = vec![];
+    while let Some(def_id) = bodies.pop() {
+        let Some((thir, _)) = s.with_item_cache(def_id, |cache| cache.thir.clone()) else {
+            continue;
+        };
+        for expr in thir.exprs.iter() {
+            if let ExprKind::Closure(closure) = &expr.kind {
+                bodies.push(closure.closure_id.to_def_id());
+            }
+            let ty::FnDef(def_id, args) = *expr.ty.kind() else {
+                continue;
+            };
+            let args = instantiate(tcx, instance.args, args);
+            if let Ok(Some(callee)) =
+                ty::Instance::try_resolve(tcx, ty::ParamEnv::reveal_all(), def_id, args)
+            {
+                if matches!(callee.def, ty::InstanceKind::Item(_)) && callee.def_id().is_local() {
+                    callees.push(callee)
+                }
+            }
+        }
+    }
+    callees
+}
+
+/// Exports a monomorphized copy of every local function reachable
+/// from the non-generic functions whose path matches one of
+/// `entry_points`.
+#[cfg(feature = "rustc")]
+pub fn mono_items<'tcx, S: BaseState<'tcx>, Body: IsBody>(
+    s: &S,
+    entry_points: &[hax_frontend_exporter_options::Namespace],
+) -> Vec<MonoItem<Body>> {
+    use rustc_hir::def::DefKind;
+    let tcx = s.base().tcx;
+    let mut todo: Vec<ty::Instance<'tcx>> = tcx
+        .hir()
+        .body_owners()
+        .map(|ldid| ldid.to_def_id())
+        .filter(|def_id| matches!(tcx.def_kind(def_id), DefKind::Fn | DefKind::AssocFn))
+        .filter(|def_id| !tcx.generics_of(*def_id).requires_monomorphization(tcx))
+        .filter(|def_id| {
+            let def_id: DefId = def_id.sinto(s);
+            let path = Path::from(def_id);
+            entry_points.iter().any(|pattern| pattern.matches(&path))
+        })
+        .map(|def_id| ty::Instance::mono(tcx, def_id))
+        .collect();
+
+    let mut seen = std::collections::HashSet::new();
+    let mut mono_items = vec![];
+    while let Some(instance) = todo.pop() {
+        if !seen.insert(instance) {
+            continue;
+        }
+        todo.extend(callees(s, instance));
+
+        let def_id: RDefId = instance.def_id();
+        let node = tcx.hir_node_by_def_id(def_id.expect_local());
+        let (Some(sig), Some(body_id)) = (node.fn_sig(), node.body_id()) else {
+            continue;
+        };
+        let base = Base {
+            instantiation: Some((def_id, instance.args)),
+            ..s.base()
+        };
+        let s = &with_owner_id(base, (), (), def_id);
+        mono_items.push(MonoItem {
+            def_id: def_id.sinto(s),
+            generic_args: instance.args.sinto(s),
+            span: tcx.def_span(def_id).sinto(s),
+            def: make_fn_def(sig, &body_id, s),
+        })
+    }
+    mono_items
+}
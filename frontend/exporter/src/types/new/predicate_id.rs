@@ -1,6 +1,6 @@
 use crate::prelude::*;
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Copy, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(transparent)]
 /// A `PredicateId` is a unique identifier for a clause or a
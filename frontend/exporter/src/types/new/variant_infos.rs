@@ -2,7 +2,7 @@ use crate::prelude::*;
 use crate::sinto_as_usize;
 
 /// Describe the kind of a variant
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum VariantKind {
     /// The variant is the only variant of a `struct` type
@@ -24,7 +24,7 @@ pub enum VariantKind {
 sinto_as_usize!(rustc_target::abi, VariantIdx);
 
 /// Describe a variant
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct VariantInformations {
     pub type_namespace: DefId,
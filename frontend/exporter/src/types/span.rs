@@ -2,7 +2,7 @@ use crate::prelude::*;
 use crate::sinto_todo;
 
 /// Reflects [`rustc_span::Loc`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Loc {
     pub line: usize,
@@ -10,7 +10,7 @@ pub struct Loc {
 }
 
 /// Reflects [`rustc_span::hygiene::DesugaringKind`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<S>, from: rustc_span::hygiene::DesugaringKind, state: S as _s)]
 pub enum DesugaringKind {
@@ -27,7 +27,7 @@ pub enum DesugaringKind {
 }
 
 /// Reflects [`rustc_span::hygiene::AstPass`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<S>, from: rustc_span::hygiene::AstPass, state: S as _s)]
 pub enum AstPass {
@@ -37,7 +37,7 @@ pub enum AstPass {
 }
 
 /// Reflects [`rustc_span::hygiene::MacroKind`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<S>, from: rustc_span::hygiene::MacroKind, state: S as _s)]
 pub enum MacroKind {
@@ -49,7 +49,7 @@ pub enum MacroKind {
 /// Reflects [`rustc_span::hygiene::ExpnKind`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: BaseState<'tcx>>, from: rustc_span::hygiene::ExpnKind, state: S as gstate)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum ExpnKind {
     Root,
@@ -61,7 +61,7 @@ pub enum ExpnKind {
 /// Reflects [`rustc_span::edition::Edition`]
 #[derive(AdtInto)]
 #[args(<S>, from: rustc_span::edition::Edition, state: S as _s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum Edition {
     Edition2015,
@@ -73,7 +73,7 @@ pub enum Edition {
 /// Reflects [`rustc_span::hygiene::ExpnData`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: BaseState<'tcx>>, from: rustc_span::hygiene::ExpnData, state: S as state)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct ExpnData {
     pub kind: ExpnKind,
@@ -89,8 +89,25 @@ pub struct ExpnData {
 }
 
 /// Reflects [`rustc_span::Span`]
-#[derive(::serde::Serialize, ::serde::Deserialize, Clone, Debug, JsonSchema, Eq, Ord)]
+#[derive(
+    ::serde::Serialize,
+    ::serde::Deserialize,
+    Clone,
+    Debug,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+#[serde(transparent)]
 pub struct Span {
+    pub(crate) contents: id_table::Node<SpanContents>,
+}
+
+#[derive(::serde::Serialize, ::serde::Deserialize, Clone, Debug, JsonSchema, Eq, Ord)]
+pub struct SpanContents {
     pub lo: Loc,
     pub hi: Loc,
     pub filename: FileName,
@@ -105,24 +122,79 @@ pub struct Span {
     // expn_backtrace: Vec<ExpnData>,
 }
 
+/// Spans are leaves for visitors.
+impl visitors::Visitable for Span {
+    fn drive<V: visitors::Visitor>(&self, visitor: &mut V) {
+        visitor.visit(self, visitors::Event::Enter);
+        visitor.visit(self, visitors::Event::Exit);
+    }
+    fn drive_mut<V: visitors::VisitorMut>(&mut self, visitor: &mut V) {
+        visitor.visit(self, visitors::Event::Enter);
+        visitor.visit(self, visitors::Event::Exit);
+    }
+    fn may_contain(target: std::any::TypeId, _: &mut Vec<std::any::TypeId>) -> bool {
+        target == std::any::TypeId::of::<Self>()
+    }
+}
+
+impl std::ops::Deref for Span {
+    type Target = SpanContents;
+    fn deref(&self) -> &Self::Target {
+        &self.contents
+    }
+}
+
+impl Span {
+    #[cfg(feature = "rustc")]
+    pub fn new<'tcx, S: BaseState<'tcx>>(s: &S, contents: SpanContents) -> Self {
+        s.with_global_cache(|cache| Span {
+            contents: id_table::Node::new(contents, &mut cache.id_table_session),
+        })
+    }
+
+    /// A span that is not shared through the id table, see
+    /// [`id_table::Node::detached`].
+    pub fn detached(contents: SpanContents) -> Self {
+        Span {
+            contents: id_table::Node::detached(contents),
+        }
+    }
+
+    /// Whether `other` is included in `self`.
+    pub fn contains(&self, other: &Span) -> bool {
+        self.filename.to_path() == other.filename.to_path()
+            && self.lo <= other.lo
+            && other.hi <= self.hi
+    }
+    /// The smallest span containing both `self` and `other`, assuming
+    /// they belong to the same file.
+    pub fn union(&self, other: &Span) -> Span {
+        Span::detached(SpanContents {
+            lo: std::cmp::min(&self.lo, &other.lo).clone(),
+            hi: std::cmp::max(&self.hi, &other.hi).clone(),
+            ..SpanContents::clone(self)
+        })
+    }
+}
+
 const _: () = {
     // `rust_span_data` is a metadata that should *not* be taken into
     // account while hashing or comparing
 
-    impl std::hash::Hash for Span {
+    impl std::hash::Hash for SpanContents {
         fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
             self.lo.hash(state);
             self.hi.hash(state);
             self.filename.hash(state);
         }
     }
-    impl PartialEq for Span {
+    impl PartialEq for SpanContents {
         fn eq(&self, other: &Self) -> bool {
             self.lo == other.lo && self.hi == other.hi && self.filename == other.filename
         }
     }
 
-    impl PartialOrd for Span {
+    impl PartialOrd for SpanContents {
         fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
             Some(
                 self.lo.partial_cmp(&other.lo)?.then(
@@ -151,14 +223,14 @@ impl<'tcx, S: BaseState<'tcx>> SInto<S, Span> for rustc_span::Span {
         if let Some(span) = s.with_global_cache(|cache| cache.spans.get(self).cloned()) {
             return span;
         }
-        let span = translate_span(*self, s.base().tcx.sess);
+        let span = Span::new(s, translate_span_contents(*self, s.base().tcx.sess));
         s.with_global_cache(|cache| cache.spans.insert(*self, span.clone()));
         span
     }
 }
 
 /// Reflects [`rustc_span::source_map::Spanned`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct Spanned<T> {
     pub node: T,
@@ -183,7 +255,7 @@ impl<'tcx, S> SInto<S, PathBuf> for PathBuf {
 }
 
 /// Reflects [`rustc_span::RealFileName`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[args(<S>, from: rustc_span::RealFileName, state: S as _s)]
 pub enum RealFileName {
@@ -204,7 +276,7 @@ impl<S> SInto<S, u64> for rustc_data_structures::stable_hasher::Hash64 {
 /// Reflects [`rustc_span::FileName`]
 #[derive(AdtInto)]
 #[args(<S>, from: rustc_span::FileName, state: S as gstate)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum FileName {
     Real(RealFileName),
@@ -6,7 +6,7 @@ use crate::prelude::*;
 use rustc_middle::thir;
 
 /// Reflects [`thir::LogicalOp`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[args(<'a, S>, from: thir::LogicalOp, state: S as _s)]
 pub enum LogicalOp {
@@ -15,7 +15,7 @@ pub enum LogicalOp {
 }
 
 /// Reflects [`thir::LintLevel`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[args(<'slt, S: UnderOwnerState<'slt> + HasThir<'slt>>, from: thir::LintLevel, state: S as gstate)]
 pub enum LintLevel {
@@ -23,7 +23,7 @@ pub enum LintLevel {
     Explicit(HirId),
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: ExprState<'tcx>>, from: thir::FruInfo<'tcx>, state: S as gstate)]
 /// Field Record Update (FRU) informations, this reflects [`thir::FruInfo`]
@@ -34,7 +34,7 @@ pub struct FruInfo {
 }
 
 /// A field expression: a field name along with a value
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct FieldExpr {
     pub field: DefId,
@@ -42,7 +42,7 @@ pub struct FieldExpr {
 }
 
 /// Reflects [`thir::AdtExpr`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct AdtExpr {
     pub info: VariantInformations,
@@ -73,7 +73,7 @@ impl<'tcx, S: ExprState<'tcx>> SInto<S, AdtExpr> for thir::AdtExpr<'tcx> {
 }
 
 /// Reflects [`thir::LocalVarId`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct LocalIdent {
     pub name: String,
@@ -98,7 +98,7 @@ impl<'tcx, S: UnderOwnerState<'tcx>> SInto<S, LocalIdent> for thir::LocalVarId {
 }
 
 /// Reflects [`thir::BlockSafety`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S>, from: thir::BlockSafety, state: S as _s)]
 pub enum BlockSafety {
@@ -109,7 +109,7 @@ pub enum BlockSafety {
 }
 
 /// Reflects [`thir::Block`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: ExprState<'tcx>>, from: thir::Block, state: S as gstate)]
 pub struct Block {
@@ -124,12 +124,34 @@ pub struct Block {
 /// Reflects [`thir::Stmt`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: ExprState<'tcx>>, from: thir::Stmt<'tcx>, state: S as s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct Stmt {
     pub kind: StmtKind,
 }
 
+impl Stmt {
+    /// Approximates the span of a statement: THIR statements carry no
+    /// span.
+    pub fn span(&self) -> Span {
+        match &self.kind {
+            StmtKind::Expr { expr, .. } => expr.span.clone(),
+            StmtKind::Let {
+                pattern,
+                initializer,
+                else_block,
+                ..
+            } => [
+                initializer.as_ref().map(|e| &e.span),
+                else_block.as_ref().map(|b| &b.span),
+            ]
+            .into_iter()
+            .flatten()
+            .fold(pattern.span.clone(), |span, other| span.union(other)),
+        }
+    }
+}
+
 #[cfg(feature = "rustc")]
 impl<'tcx, S: ExprState<'tcx>> SInto<S, Block> for thir::BlockId {
     fn sinto(&self, s: &S) -> Block {
@@ -358,7 +380,7 @@ impl<'tcx, S: ExprState<'tcx>> SInto<S, Arm> for thir::ArmId {
 /// Reflects [`thir::StmtKind`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: ExprState<'tcx>>, from: thir::StmtKind<'tcx>, state: S as gstate)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum StmtKind {
     Expr {
@@ -381,7 +403,7 @@ pub enum StmtKind {
 /// Reflects [`thir::Ascription`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> + HasThir<'tcx>>, from: thir::Ascription<'tcx>, state: S as gstate)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct Ascription {
     pub annotation: CanonicalUserTypeAnnotation,
@@ -391,7 +413,7 @@ pub struct Ascription {
 /// Reflects [`thir::PatRange`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> + HasThir<'tcx>>, from: thir::PatRange<'tcx>, state: S as state)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct PatRange {
     pub lo: PatRangeBoundary,
@@ -402,7 +424,7 @@ pub struct PatRange {
 /// Reflects [`thir::PatRangeBoundary`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> + HasThir<'tcx>>, from: thir::PatRangeBoundary<'tcx>, state: S as state)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum PatRangeBoundary {
     Finite(ConstantExpr),
@@ -411,7 +433,7 @@ pub enum PatRangeBoundary {
 }
 
 /// A field pattern: a field name along with a pattern
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct FieldPat {
     pub field: DefId,
@@ -423,7 +445,7 @@ pub type Pat = Decorated<PatKind>;
 /// Reflects [`thir::PatKind`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: ExprState<'tcx>>, from: thir::PatKind<'tcx>, state: S as gstate)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 #[append(thir::PatKind::Leaf {..} => fatal!(gstate, "PatKind::Leaf: should never come up"),)]
 pub enum PatKind {
@@ -512,7 +534,7 @@ pub enum PatKind {
 /// Reflects [`thir::Arm`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: ExprState<'tcx>>, from: thir::Arm<'tcx>, state: S as gstate)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct Arm {
     pub pattern: Pat,
@@ -528,7 +550,7 @@ pub struct Arm {
 /// Reflects [`thir::Param`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: ExprState<'tcx>>, from: thir::Param<'tcx>, state: S as s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct Param {
     pub pat: Option<Pat>,
@@ -549,7 +571,7 @@ pub type Expr = Decorated<ExprKind>;
 /// Reflects [`thir::ExprKind`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: ExprState<'tcx>>, from: thir::ExprKind<'tcx>, state: S as gstate)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 #[append(
     thir::ExprKind::Scope {..} => {
@@ -581,6 +603,8 @@ pub enum ExprKind {
         let (generic_args, r#trait, bounds_impls);
         let fun = match e.ty.kind() {
             rustc_middle::ty::TyKind::FnDef(def_id, generics) => {
+                let tcx = gstate.base().tcx;
+                let (def_id, generics) = resolve_mono_call(gstate, *def_id, generics);
                 let (hir_id, attributes) = e.hir_id_and_attributes(gstate);
                 let hir_id = hir_id.map(|hir_id| hir_id.index());
                 let contents = Box::new(ExprKind::GlobalName {
@@ -588,20 +612,19 @@ pub enum ExprKind {
                     constructor: None
                 });
                 let mut translated_generics = generics.sinto(gstate);
-                let tcx = gstate.base().tcx;
                 r#trait = (|| {
-                    let assoc_item = tcx.opt_associated_item(*def_id)?;
+                    let assoc_item = tcx.opt_associated_item(def_id)?;
                     let impl_expr = self_clause_for_item(gstate, &assoc_item, generics)?;
                     let assoc_generics = tcx.generics_of(assoc_item.def_id);
                     let assoc_generics = translated_generics.drain(0..assoc_generics.parent_count).collect();
                     Some((impl_expr, assoc_generics))
                 })();
                 generic_args = translated_generics;
-                bounds_impls = solve_item_required_traits(gstate, *def_id, generics);
+                bounds_impls = solve_item_required_traits(gstate, def_id, generics);
                 Expr {
                     contents,
                     span: e.span.sinto(gstate),
-                    ty: e.ty.sinto(gstate),
+                    ty: rustc_middle::ty::Ty::new_fn_def(tcx, def_id, generics).sinto(gstate),
                     hir_id,
                     attributes,
                 }
@@ -865,6 +888,9 @@ pub enum ExprKind {
     Yield {
         value: Expr,
     },
+    /// Inline assembly, which is not translated.
+    #[custom_arm(FROM_TYPE::InlineAsm(..) => TO_TYPE::InlineAsm,)]
+    InlineAsm,
     #[todo]
     Todo(String),
 }
@@ -10,7 +10,7 @@ use rustc_middle::ty;
 
 /// Generic container for decorating items with a type, a span,
 /// attributes and other meta-data.
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Decorated<T> {
     pub ty: Ty,
@@ -21,7 +21,7 @@ pub struct Decorated<T> {
 }
 
 /// Reflects [`rustc_middle::infer::canonical::CanonicalTyVarKind`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: rustc_middle::infer::canonical::CanonicalTyVarKind, state: S as gstate)]
 pub enum CanonicalTyVarKind {
@@ -33,7 +33,7 @@ pub enum CanonicalTyVarKind {
 sinto_as_usize!(rustc_middle::ty, UniverseIndex);
 
 /// Reflects [`ty::ParamTy`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::ParamTy, state: S as gstate)]
 pub struct ParamTy {
@@ -42,7 +42,7 @@ pub struct ParamTy {
 }
 
 /// Reflects [`ty::ParamConst`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[args(<S>, from: ty::ParamConst, state: S as gstate)]
 pub struct ParamConst {
@@ -55,7 +55,7 @@ pub struct ParamConst {
 /// Reflects [`ty::ExistentialPredicate`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::ExistentialPredicate<'tcx>, state: S as state)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ExistentialPredicate {
     /// E.g. `From<u64>`. Note that this isn't `T: From<u64>` with a given `T`, this is just
@@ -70,7 +70,7 @@ pub enum ExistentialPredicate {
 /// Reflects [`rustc_type_ir::ExistentialTraitRef`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: rustc_type_ir::ExistentialTraitRef<ty::TyCtxt<'tcx>>, state: S as state)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct ExistentialTraitRef {
     pub def_id: DefId,
@@ -80,7 +80,7 @@ pub struct ExistentialTraitRef {
 /// Reflects [`rustc_type_ir::ExistentialProjection`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: rustc_type_ir::ExistentialProjection<ty::TyCtxt<'tcx>>, state: S as state)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct ExistentialProjection {
     pub def_id: DefId,
@@ -89,7 +89,7 @@ pub struct ExistentialProjection {
 }
 
 /// Reflects [`ty::DynKind`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[args(<S>, from: ty::DynKind, state: S as _s)]
 pub enum DynKind {
@@ -98,7 +98,7 @@ pub enum DynKind {
 }
 
 /// Reflects [`ty::BoundTyKind`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::BoundTyKind, state: S as gstate)]
 pub enum BoundTyKind {
@@ -107,7 +107,7 @@ pub enum BoundTyKind {
 }
 
 /// Reflects [`ty::BoundTy`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::BoundTy, state: S as gstate)]
 pub struct BoundTy {
@@ -118,7 +118,7 @@ pub struct BoundTy {
 sinto_as_usize!(rustc_middle::ty, BoundVar);
 
 /// Reflects [`ty::BoundRegionKind`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::BoundRegionKind, state: S as gstate)]
 pub enum BoundRegionKind {
@@ -128,7 +128,7 @@ pub enum BoundRegionKind {
 }
 
 /// Reflects [`ty::BoundRegion`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::BoundRegion, state: S as gstate)]
 pub struct BoundRegion {
@@ -144,7 +144,7 @@ pub type PlaceholderConst = Placeholder<BoundVar>;
 pub type PlaceholderType = Placeholder<BoundTy>;
 
 /// Reflects [`ty::Placeholder`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Placeholder<T> {
     pub universe: UniverseIndex,
@@ -164,7 +164,7 @@ impl<'tcx, S: UnderOwnerState<'tcx>, T: SInto<S, U>, U> SInto<S, Placeholder<U>>
 }
 
 /// Reflects [`rustc_middle::infer::canonical::Canonical`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct Canonical<T> {
     pub max_universe: UniverseIndex,
@@ -188,7 +188,7 @@ impl<'tcx, S: UnderOwnerState<'tcx>, T: SInto<S, U>, U> SInto<S, Canonical<U>>
 }
 
 /// Reflects [`rustc_middle::infer::canonical::CanonicalVarKind`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: rustc_middle::infer::canonical::CanonicalVarKind<ty::TyCtxt<'tcx>>, state: S as gstate)]
 pub enum CanonicalVarInfo {
@@ -202,7 +202,7 @@ pub enum CanonicalVarInfo {
 }
 
 /// Reflects [`ty::UserSelfTy`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::UserSelfTy<'tcx>, state: S as gstate)]
 pub struct UserSelfTy {
@@ -211,7 +211,7 @@ pub struct UserSelfTy {
 }
 
 /// Reflects [`ty::UserArgs`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::UserArgs<'tcx>, state: S as gstate)]
 pub struct UserArgs {
@@ -222,7 +222,7 @@ pub struct UserArgs {
 /// Reflects [`ty::UserType`]: this is currently
 /// disabled, and everything is printed as debug in the
 /// [`UserType::Todo`] variant.
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::UserType<'tcx>, state: S as _s)]
 pub enum UserType {
@@ -253,7 +253,7 @@ pub enum UserType {
 }
 
 /// Reflects [`ty::VariantDiscr`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::VariantDiscr, state: S as gstate)]
 pub enum DiscriminantDefinition {
@@ -262,7 +262,7 @@ pub enum DiscriminantDefinition {
 }
 
 /// Reflects [`ty::util::Discr`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::util::Discr<'tcx>, state: S as gstate)]
 pub struct DiscriminantValue {
@@ -271,7 +271,7 @@ pub struct DiscriminantValue {
 }
 
 /// Reflects [`ty::Visibility`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum Visibility<Id> {
     Public,
@@ -290,7 +290,7 @@ impl<S, T: SInto<S, U>, U> SInto<S, Visibility<U>> for ty::Visibility<T> {
 }
 
 /// Reflects [`ty::FieldDef`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct FieldDef {
     pub did: DefId,
@@ -333,7 +333,7 @@ impl<'tcx, S: UnderOwnerState<'tcx>> SInto<S, FieldDef> for ty::FieldDef {
 }
 
 /// Reflects [`ty::VariantDef`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct VariantDef {
     pub def_id: DefId,
@@ -369,7 +369,7 @@ impl VariantDef {
 }
 
 /// Reflects [`ty::EarlyParamRegion`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::EarlyParamRegion, state: S as gstate)]
 pub struct EarlyParamRegion {
@@ -378,7 +378,7 @@ pub struct EarlyParamRegion {
 }
 
 /// Reflects [`ty::LateParamRegion`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::LateParamRegion, state: S as gstate)]
 pub struct LateParamRegion {
@@ -387,7 +387,7 @@ pub struct LateParamRegion {
 }
 
 /// Reflects [`ty::RegionKind`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::RegionKind<'tcx>, state: S as gstate)]
 pub enum RegionKind {
@@ -405,7 +405,7 @@ sinto_as_usize!(rustc_middle::ty, DebruijnIndex);
 sinto_as_usize!(rustc_middle::ty, RegionVid);
 
 /// Reflects [`ty::Region`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::Region<'tcx>, state: S as s)]
 pub struct Region {
@@ -414,7 +414,7 @@ pub struct Region {
 }
 
 /// Reflects both [`ty::GenericArg`] and [`ty::GenericArgKind`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::GenericArgKind<'tcx>, state: S as s)]
 pub enum GenericArg {
@@ -440,7 +440,7 @@ impl<'tcx, S: UnderOwnerState<'tcx>> SInto<S, Vec<GenericArg>> for ty::GenericAr
 /// Reflects both [`ty::GenericArg`] and [`ty::GenericArgKind`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: BaseState<'tcx>>, from: rustc_ast::ast::LitIntType, state: S as gstate)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum LitIntType {
     Signed(IntTy),
@@ -449,7 +449,7 @@ pub enum LitIntType {
 }
 
 /// Reflects partially [`ty::InferTy`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[args(<'tcx, S>, from: ty::InferTy, state: S as gstate)]
 pub enum InferTy {
@@ -467,7 +467,7 @@ pub enum InferTy {
 /// Reflects [`rustc_type_ir::IntTy`]
 #[derive(AdtInto)]
 #[args(<S>, from: rustc_type_ir::IntTy, state: S as _s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Copy, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum IntTy {
     Isize,
@@ -481,7 +481,7 @@ pub enum IntTy {
 /// Reflects [`rustc_type_ir::FloatTy`]
 #[derive(AdtInto)]
 #[args(<S>, from: rustc_type_ir::FloatTy, state: S as _s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Copy, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum FloatTy {
     F16,
@@ -535,7 +535,7 @@ impl<'tcx, S> SInto<S, UintTy> for rustc_ast::ast::UintTy {
 /// Reflects [`rustc_type_ir::UintTy`]
 #[derive(AdtInto)]
 #[args(<S>, from: rustc_type_ir::UintTy, state: S as _s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Copy, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum UintTy {
     Usize,
@@ -577,7 +577,7 @@ impl ToString for UintTy {
 /// Reflects [`ty::TypeAndMut`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::TypeAndMut<'tcx>, state: S as gstate)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct TypeAndMut {
     pub ty: Box<Ty>,
@@ -594,7 +594,7 @@ impl<S, U, T: SInto<S, U>> SInto<S, Vec<U>> for ty::List<T> {
 /// Reflects [`ty::GenericParamDef`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::GenericParamDef, state: S as s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct GenericParamDef {
     pub name: Symbol,
@@ -615,7 +615,7 @@ pub struct GenericParamDef {
 }
 
 /// Reflects [`ty::GenericParamDefKind`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum GenericParamDefKind {
     Lifetime,
@@ -633,7 +633,7 @@ pub enum GenericParamDefKind {
 /// Reflects [`ty::Generics`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::Generics, state: S as state)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct TyGenerics {
     pub parent: Option<DefId>,
@@ -647,7 +647,7 @@ pub struct TyGenerics {
 
 /// This type merges the information from
 /// `rustc_type_ir::AliasKind` and `ty::AliasTy`
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Alias {
     pub kind: AliasKind,
@@ -656,7 +656,7 @@ pub struct Alias {
 }
 
 /// Reflects [`ty::AliasKind`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum AliasKind {
     /// The projection of a trait type: `<Ty as Trait<...>>::Type<...>`
@@ -734,7 +734,7 @@ impl<'tcx, S: UnderOwnerState<'tcx>> SInto<S, Box<Ty>> for ty::Ty<'tcx> {
 }
 
 /// Reflects [`rustc_middle::ty::Ty`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(transparent)]
 pub struct Ty {
@@ -763,13 +763,14 @@ impl Ty {
 #[cfg(feature = "rustc")]
 impl<'tcx, S: UnderOwnerState<'tcx>> SInto<S, Ty> for rustc_middle::ty::Ty<'tcx> {
     fn sinto(&self, s: &S) -> Ty {
-        if let Some(ty) = s.with_cache(|cache| cache.tys.get(self).cloned()) {
+        let rty = instantiate_mono(s, *self);
+        if let Some(ty) = s.with_cache(|cache| cache.tys.get(&rty).cloned()) {
             return ty;
         }
-        let kind: TyKind = self.kind().sinto(s);
+        let kind: TyKind = rty.kind().sinto(s);
         let ty = Ty::new(s, kind);
         s.with_cache(|cache| {
-            cache.tys.insert(*self, ty.clone());
+            cache.tys.insert(rty, ty.clone());
         });
         ty
     }
@@ -778,7 +779,7 @@ impl<'tcx, S: UnderOwnerState<'tcx>> SInto<S, Ty> for rustc_middle::ty::Ty<'tcx>
 /// Reflects [`ty::TyKind`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::TyKind<'tcx>, state: S as s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum TyKind {
     Bool,
@@ -856,7 +857,7 @@ pub enum TyKind {
 /// Reflects [`ty::Variance`]
 #[derive(AdtInto)]
 #[args(<S>, from: ty::Variance, state: S as _s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum Variance {
     Covariant,
@@ -868,7 +869,7 @@ pub enum Variance {
 /// Reflects [`ty::CanonicalUserTypeAnnotation`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::CanonicalUserTypeAnnotation<'tcx>, state: S as gstate)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct CanonicalUserTypeAnnotation {
     pub user_ty: CanonicalUserType,
@@ -877,7 +878,7 @@ pub struct CanonicalUserTypeAnnotation {
 }
 
 /// Reflects [`ty::AdtKind`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Copy, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::AdtKind, state: S as _s)]
 pub enum AdtKind {
@@ -889,7 +890,7 @@ pub enum AdtKind {
 // This comes from MIR
 // TODO: add the generics and the predicates
 /// Reflects [`ty::AdtDef`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub struct AdtDef {
     pub did: DefId,
@@ -897,12 +898,15 @@ pub struct AdtDef {
     pub variants: IndexVec<VariantIdx, VariantDef>,
     pub flags: AdtFlags,
     pub repr: ReprOptions,
+    /// The layout of the type, if it is not generic. Layouts are only
+    /// computed when the option `layouts` is set.
+    pub layout: Option<Layout>,
 }
 
 sinto_todo!(rustc_middle::ty, AdtFlags);
 
 /// Reflects [`ty::ReprOptions`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::ReprOptions, state: S as s)]
 pub struct ReprOptions {
@@ -948,6 +952,13 @@ impl<'tcx, S: UnderOwnerState<'tcx>> SInto<S, AdtDef> for ty::AdtDef<'tcx> {
             variants,
             flags: self.flags().sinto(s),
             repr: self.repr().sinto(s),
+            layout: {
+                let tcx = s.base().tcx;
+                (s.base().options.layouts
+                    && !tcx.generics_of(self.did()).requires_monomorphization(tcx))
+                .then(|| Layout::of(s, tcx.type_of(self.did()).instantiate_identity()))
+                .flatten()
+            },
         }
     }
 }
@@ -955,7 +966,7 @@ impl<'tcx, S: UnderOwnerState<'tcx>> SInto<S, AdtDef> for ty::AdtDef<'tcx> {
 /// Reflects [`ty::adjustment::PointerCoercion`]
 #[derive(AdtInto)]
 #[args(<S>, from: ty::adjustment::PointerCoercion, state: S as gstate)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema)]
 pub enum PointerCoercion {
     ReifyFnPointer,
@@ -970,7 +981,7 @@ pub enum PointerCoercion {
 sinto_todo!(rustc_middle::ty, ScalarInt);
 
 /// Reflects [`ty::FnSig`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::FnSig<'tcx>, state: S as s)]
 pub struct TyFnSig {
@@ -987,7 +998,7 @@ pub struct TyFnSig {
 pub type PolyFnSig = Binder<TyFnSig>;
 
 /// Reflects [`ty::TraitRef`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::TraitRef<'tcx>, state: S as tcx)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
@@ -1001,7 +1012,7 @@ pub struct TraitRef {
 /// Reflects [`ty::TraitPredicate`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::TraitPredicate<'tcx>, state: S as tcx)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct TraitPredicate {
     pub trait_ref: TraitRef,
@@ -1014,7 +1025,7 @@ pub struct TraitPredicate {
 /// instead of a tuple struct. This is because the script converting
 /// JSONSchema types to OCaml doesn't support tuple structs, and this
 /// is the only tuple struct in the whole AST.
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct OutlivesPredicate<T> {
     pub lhs: T,
@@ -1041,7 +1052,7 @@ pub type RegionOutlivesPredicate = OutlivesPredicate<Region>;
 pub type TypeOutlivesPredicate = OutlivesPredicate<Ty>;
 
 /// Reflects [`ty::Term`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Term {
     Ty(Ty),
@@ -1067,7 +1078,7 @@ impl<'tcx, S: UnderOwnerState<'tcx>> SInto<S, Term> for ty::Term<'tcx> {
 ///              ^^^^^^^^^^
 /// ```
 /// (provided the trait `Foo` has an associated type `S`).
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct ProjectionPredicate {
     /// The `impl Trait for Ty` in `Ty: Trait<..., Type = U>`.
@@ -1100,7 +1111,7 @@ impl<'tcx, S: UnderBinderState<'tcx>> SInto<S, ProjectionPredicate>
 /// Reflects [`ty::ClauseKind`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderBinderState<'tcx>>, from: ty::ClauseKind<'tcx>, state: S as tcx)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ClauseKind {
     Trait(TraitPredicate),
@@ -1113,7 +1124,7 @@ pub enum ClauseKind {
 }
 
 /// Reflects [`ty::Clause`] and adds a hash-consed predicate identifier.
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Clause {
     pub kind: Binder<ClauseKind>,
@@ -1140,7 +1151,7 @@ impl<'tcx, S: UnderOwnerState<'tcx>> SInto<S, Clause> for ty::PolyTraitPredicate
 }
 
 /// Reflects [`ty::Predicate`] and adds a hash-consed predicate identifier.
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Predicate {
     pub kind: Binder<PredicateKind>,
@@ -1159,7 +1170,7 @@ impl<'tcx, S: UnderOwnerState<'tcx>> SInto<S, Predicate> for ty::Predicate<'tcx>
 /// Reflects [`ty::BoundVariableKind`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::BoundVariableKind, state: S as tcx)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum BoundVariableKind {
     Ty(BoundTyKind),
@@ -1168,7 +1179,7 @@ pub enum BoundVariableKind {
 }
 
 /// Reflects [`ty::Binder`]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Binder<T> {
     pub value: T,
@@ -1210,15 +1221,53 @@ impl<T> Binder<T> {
 /// Reflects [`ty::GenericPredicates`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::GenericPredicates<'tcx>, state: S as s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
-pub struct GenericPredicates {
+pub struct GenericPredicatesContents {
     pub parent: Option<DefId>,
     // FIXME: Switch from `Predicate` to `Clause` (will require correct handling of binders).
     #[value(self.predicates.iter().map(|(clause, span)| (clause.as_predicate().sinto(s), span.sinto(s))).collect())]
     pub predicates: Vec<(Predicate, Span)>,
 }
 
+#[derive_group(Serializers, Visitors)]
+#[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(transparent)]
+pub struct GenericPredicates {
+    pub(crate) contents: id_table::Node<GenericPredicatesContents>,
+}
+
+impl std::ops::Deref for GenericPredicates {
+    type Target = GenericPredicatesContents;
+    fn deref(&self) -> &Self::Target {
+        &self.contents
+    }
+}
+
+impl GenericPredicates {
+    #[cfg(feature = "rustc")]
+    pub fn new<'tcx, S: BaseState<'tcx>>(s: &S, contents: GenericPredicatesContents) -> Self {
+        s.with_global_cache(|cache| GenericPredicates {
+            contents: id_table::Node::interned(contents, &mut cache.id_table_session),
+        })
+    }
+
+    /// Generic predicates that are not shared through the id table,
+    /// see [`id_table::Node::detached`].
+    pub fn detached(contents: GenericPredicatesContents) -> Self {
+        GenericPredicates {
+            contents: id_table::Node::detached(contents),
+        }
+    }
+}
+
+#[cfg(feature = "rustc")]
+impl<'tcx, S: UnderOwnerState<'tcx>> SInto<S, GenericPredicates> for ty::GenericPredicates<'tcx> {
+    fn sinto(&self, s: &S) -> GenericPredicates {
+        GenericPredicates::new(s, self.sinto(s))
+    }
+}
+
 #[cfg(feature = "rustc")]
 impl<'tcx, S: UnderOwnerState<'tcx>, T1, T2> SInto<S, Binder<T2>> for ty::Binder<'tcx, T1>
 where
@@ -1243,7 +1292,7 @@ where
 /// Reflects [`ty::SubtypePredicate`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::SubtypePredicate<'tcx>, state: S as tcx)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct SubtypePredicate {
     pub a_is_expected: bool,
@@ -1254,7 +1303,7 @@ pub struct SubtypePredicate {
 /// Reflects [`ty::CoercePredicate`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::CoercePredicate<'tcx>, state: S as tcx)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct CoercePredicate {
     pub a: Ty,
@@ -1264,7 +1313,7 @@ pub struct CoercePredicate {
 /// Reflects [`ty::AliasRelationDirection`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::AliasRelationDirection, state: S as _tcx)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum AliasRelationDirection {
     Equate,
@@ -1275,7 +1324,7 @@ pub enum AliasRelationDirection {
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx> >, from: ty::ClosureArgs<ty::TyCtxt<'tcx>>, state: S as s)]
 #[derive(Clone, Debug, JsonSchema)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 pub struct ClosureArgs {
     #[value(self.kind().sinto(s))]
     pub kind: ClosureKind,
@@ -1290,7 +1339,7 @@ pub struct ClosureArgs {
 /// Reflects [`ty::ClosureKind`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderOwnerState<'tcx>>, from: ty::ClosureKind, state: S as _tcx)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ClosureKind {
     Fn,
@@ -1303,7 +1352,7 @@ sinto_todo!(rustc_middle::ty, NormalizesTo<'tcx>);
 /// Reflects [`ty::PredicateKind`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: UnderBinderState<'tcx>>, from: ty::PredicateKind<'tcx>, state: S as tcx)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum PredicateKind {
     Clause(ClauseKind),
@@ -1319,7 +1368,7 @@ pub enum PredicateKind {
 /// Reflects [`ty::AssocItem`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: BaseState<'tcx>>, from: ty::AssocItem, state: S as s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct AssocItem {
     pub def_id: DefId,
@@ -1338,7 +1387,7 @@ pub struct AssocItem {
 /// Reflects [`ty::AssocKind`]
 #[derive(AdtInto)]
 #[args(<S>, from: ty::AssocKind, state: S as _tcx)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum AssocKind {
     Const,
@@ -1346,7 +1395,7 @@ pub enum AssocKind {
     Type,
 }
 
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum AssocItemContainer {
     TraitContainer {
@@ -1415,7 +1464,7 @@ fn get_container_for_assoc_item<'tcx, S: BaseState<'tcx>>(
 /// Reflects [`ty::ImplTraitInTraitData`]
 #[derive(AdtInto)]
 #[args(<'tcx, S: BaseState<'tcx>>, from: ty::ImplTraitInTraitData, state: S as _s)]
-#[derive_group(Serializers)]
+#[derive_group(Serializers, Visitors)]
 #[derive(Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ImplTraitInTraitData {
     Trait {
@@ -25,7 +25,7 @@ mod internal_helpers {
         };
     }
     macro_rules! _span_verb_base {
-        ($verb:ident, $s:ident, $span:expr, $message:expr) => {{
+        (@emit $verb:ident, $s:ident, $span:expr, $message:expr) => {{
             let backtrace = std::backtrace::Backtrace::capture();
             eprintln!("{}", backtrace);
             let mut builder = $crate::utils::_verb!($verb, $s.base().tcx.dcx(), $message);
@@ -39,12 +39,55 @@ Please report this error to https://github.com/hacspec/hax/issues with some cont
             );
             builder.emit()
         }};
+        (fatal, $s:ident, $span:expr, $message:expr) => {{
+            let message = $message;
+            if $crate::utils::fatal_errors_are_caught() {
+                std::panic::panic_any($crate::utils::CaughtFatalError(message))
+            }
+            $crate::utils::_span_verb_base!(@emit fatal, $s, $span, message)
+        }};
+        ($verb:ident, $s:ident, $span:expr, $message:expr) => {
+            $crate::utils::_span_verb_base!(@emit $verb, $s, $span, $message)
+        };
     }
 
     pub(crate) use _span_verb_base;
     pub(crate) use _verb;
 }
 
+thread_local! {
+    static CATCH_FATAL_ERRORS: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// The payload of the panic raised by `fatal!` under
+/// [`catch_fatal_errors`], instead of reporting the error to rustc.
+pub struct CaughtFatalError(pub String);
+
+/// Whether `fatal!` is running under [`catch_fatal_errors`].
+pub fn fatal_errors_are_caught() -> bool {
+    CATCH_FATAL_ERRORS.get()
+}
+
+/// Runs `f`, catching its panics. The fatal errors raised by `f` are
+/// returned as an error message instead of being reported to rustc,
+/// which would make the compilation of the crate fail.
+pub fn catch_fatal_errors<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    let caught = CATCH_FATAL_ERRORS.replace(true);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+    CATCH_FATAL_ERRORS.set(caught);
+    result.map_err(|payload| {
+        if let Some(CaughtFatalError(message)) = payload.downcast_ref() {
+            message.clone()
+        } else if let Some(message) = payload.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "unknown panic".to_string()
+        }
+    })
+}
+
 macro_rules! report {
     ($verb:ident, $s:ident [$span:expr], $($tt:tt)*) => {
         $crate::utils::_span_verb_base!($verb, $s, Some($span), $crate::utils::format_with_context!($($tt)*))
@@ -156,3 +199,24 @@ macro_rules! s_assert {
     }};
 }
 pub(crate) use s_assert;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catch_fatal_errors_returns_messages() {
+        assert!(!fatal_errors_are_caught());
+        assert_eq!(catch_fatal_errors(fatal_errors_are_caught), Ok(true));
+        assert!(!fatal_errors_are_caught());
+        assert_eq!(
+            catch_fatal_errors(|| std::panic::panic_any(CaughtFatalError("fatal".into()))),
+            Err::<(), _>("fatal".into())
+        );
+        assert_eq!(
+            catch_fatal_errors(|| panic!("panic {}", 42)),
+            Err::<(), _>("panic 42".into())
+        );
+        assert!(!fatal_errors_are_caught());
+    }
+}
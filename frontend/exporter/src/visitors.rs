@@ -0,0 +1,338 @@
+//! This module provides generic visitors for the types exported by
+//! this crate. Every exported type implements [`Visitable`] (via the
+//! `Visitors` derive group), which drives a [`Visitor`] (or a
+//! [`VisitorMut`]) through the whole tree: the visitor is called on
+//! every node of a derived type, once when entering it and once when
+//! exiting it. A visitor inspects nodes by downcasting them, e.g.:
+//!
+//! ```ignore
+//! let mut calls = 0;
+//! item.for_each(|expr: &Expr| {
+//!     if let ExprKind::Call { .. } = expr.contents.as_ref() {
+//!         calls += 1
+//!     }
+//! });
+//! ```
+use crate::prelude::*;
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// Whether a visitor is entering or exiting a node.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum Event {
+    Enter,
+    Exit,
+}
+
+/// A visitor over shared references.
+pub trait Visitor {
+    fn visit(&mut self, node: &dyn Any, event: Event);
+}
+
+/// A visitor over mutable references.
+pub trait VisitorMut {
+    fn visit(&mut self, node: &mut dyn Any, event: Event);
+
+    /// The type of the only nodes `visit` acts upon, if any. When a
+    /// visitor has a target, sub-trees that contain no node of that
+    /// type are not visited: in particular, shared nodes (behind an
+    /// `Arc`) are cloned only if they may contain such a node.
+    fn target(&self) -> Option<TypeId> {
+        None
+    }
+}
+
+/// Whether values of type `T` may contain a node of type `target`.
+/// The answer only depends on types: it is computed once per pair of
+/// types and cached.
+fn may_contain<T: Visitable>(target: TypeId) -> bool {
+    thread_local! {
+        static CACHE: RefCell<HashMap<(TypeId, TypeId), bool>> = RefCell::new(HashMap::new());
+    }
+    let key = (TypeId::of::<T>(), target);
+    if let Some(answer) = CACHE.with_borrow(|cache| cache.get(&key).copied()) {
+        return answer;
+    }
+    let answer = T::may_contain(target, &mut vec![]);
+    CACHE.with_borrow_mut(|cache| cache.insert(key, answer));
+    answer
+}
+
+/// A type whose values can be walked through by a visitor. Mutable
+/// visits clone shared nodes (see `Arc::make_mut`), hence the `Clone`
+/// bound.
+pub trait Visitable: Any + Clone {
+    /// Drives `visitor` through `self` and all its sub-nodes.
+    fn drive<V: Visitor>(&self, visitor: &mut V);
+    /// Drives `visitor` through `self` and all its sub-nodes,
+    /// allowing in-place rewriting.
+    fn drive_mut<V: VisitorMut>(&mut self, visitor: &mut V);
+
+    /// Whether values of this type may contain a node of type
+    /// `target` (including the value itself). `seen` holds the types
+    /// being explored, so that recursive types terminate. The default
+    /// answer, `true`, is always sound.
+    #[doc(hidden)]
+    fn may_contain(target: TypeId, seen: &mut Vec<TypeId>) -> bool {
+        let _ = (target, seen);
+        true
+    }
+
+    /// Calls `f` on every node of type `T`, in pre-order.
+    fn for_each<T: Any>(&self, f: impl FnMut(&T)) {
+        self.drive(&mut ForEach(f, PhantomData))
+    }
+
+    /// Folds every node of type `T` in pre-order, starting with the
+    /// accumulator `init`.
+    fn fold<T: Any, A>(&self, init: A, mut f: impl FnMut(A, &T) -> A) -> A {
+        let mut acc = Some(init);
+        self.for_each(|node: &T| acc = acc.take().map(|acc| f(acc, node)));
+        acc.unwrap()
+    }
+
+    /// Rewrites every node of type `T` with `f` in post-order: when
+    /// `f` is called on a node, its sub-nodes were already rewritten.
+    fn rewrite<T: Any>(&mut self, f: impl FnMut(&mut T)) {
+        self.drive_mut(&mut Rewrite(f, PhantomData))
+    }
+}
+
+/// See [`Visitable::for_each`].
+struct ForEach<T, F>(F, PhantomData<fn(&T)>);
+impl<T: Any, F: FnMut(&T)> Visitor for ForEach<T, F> {
+    fn visit(&mut self, node: &dyn Any, event: Event) {
+        if let (Event::Enter, Some(node)) = (event, node.downcast_ref()) {
+            (self.0)(node)
+        }
+    }
+}
+
+/// See [`Visitable::rewrite`].
+struct Rewrite<T, F>(F, PhantomData<fn(&mut T)>);
+impl<T: Any, F: FnMut(&mut T)> VisitorMut for Rewrite<T, F> {
+    fn visit(&mut self, node: &mut dyn Any, event: Event) {
+        if let (Event::Exit, Some(node)) = (event, node.downcast_mut()) {
+            (self.0)(node)
+        }
+    }
+    fn target(&self) -> Option<TypeId> {
+        Some(TypeId::of::<T>())
+    }
+}
+
+/// Implements `Visitable` for types that have no sub-nodes: the
+/// visitor is only called on the value itself.
+macro_rules! visitable_leaf {
+    ($($t:ty),*$(,)?) => {
+        $(impl Visitable for $t {
+            fn drive<V: Visitor>(&self, visitor: &mut V) {
+                visitor.visit(self, Event::Enter);
+                visitor.visit(self, Event::Exit);
+            }
+            fn drive_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+                visitor.visit(self, Event::Enter);
+                visitor.visit(self, Event::Exit);
+            }
+            fn may_contain(target: TypeId, _: &mut Vec<TypeId>) -> bool {
+                target == TypeId::of::<Self>()
+            }
+        })*
+    };
+}
+
+visitable_leaf!(bool, char, String, PathBuf, ());
+visitable_leaf!(u8, u16, u32, u64, u128, usize);
+visitable_leaf!(i8, i16, i32, i64, i128, isize);
+
+impl<T: 'static> Visitable for PhantomData<T> {
+    fn drive<V: Visitor>(&self, _: &mut V) {}
+    fn drive_mut<V: VisitorMut>(&mut self, _: &mut V) {}
+    fn may_contain(_: TypeId, _: &mut Vec<TypeId>) -> bool {
+        false
+    }
+}
+
+impl<T: Visitable> Visitable for Box<T> {
+    fn drive<V: Visitor>(&self, visitor: &mut V) {
+        (**self).drive(visitor)
+    }
+    fn drive_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        (**self).drive_mut(visitor)
+    }
+    fn may_contain(target: TypeId, seen: &mut Vec<TypeId>) -> bool {
+        T::may_contain(target, seen)
+    }
+}
+
+impl<T: Visitable> Visitable for Arc<T> {
+    fn drive<V: Visitor>(&self, visitor: &mut V) {
+        (**self).drive(visitor)
+    }
+    fn drive_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        if let Some(target) = visitor.target() {
+            if !may_contain::<T>(target) {
+                return;
+            }
+        }
+        Arc::make_mut(self).drive_mut(visitor)
+    }
+    fn may_contain(target: TypeId, seen: &mut Vec<TypeId>) -> bool {
+        T::may_contain(target, seen)
+    }
+}
+
+impl<T: Visitable> Visitable for Option<T> {
+    fn drive<V: Visitor>(&self, visitor: &mut V) {
+        if let Some(x) = self {
+            x.drive(visitor)
+        }
+    }
+    fn drive_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        if let Some(x) = self {
+            x.drive_mut(visitor)
+        }
+    }
+    fn may_contain(target: TypeId, seen: &mut Vec<TypeId>) -> bool {
+        T::may_contain(target, seen)
+    }
+}
+
+impl<T: Visitable> Visitable for Vec<T> {
+    fn drive<V: Visitor>(&self, visitor: &mut V) {
+        self.iter().for_each(|x| x.drive(visitor))
+    }
+    fn drive_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        self.iter_mut().for_each(|x| x.drive_mut(visitor))
+    }
+    fn may_contain(target: TypeId, seen: &mut Vec<TypeId>) -> bool {
+        T::may_contain(target, seen)
+    }
+}
+
+impl<I: 'static + Clone, T: Visitable> Visitable for IndexVec<I, T> {
+    fn drive<V: Visitor>(&self, visitor: &mut V) {
+        self.raw.drive(visitor)
+    }
+    fn drive_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        self.raw.drive_mut(visitor)
+    }
+    fn may_contain(target: TypeId, seen: &mut Vec<TypeId>) -> bool {
+        T::may_contain(target, seen)
+    }
+}
+
+impl<T: Visitable, const N: usize> Visitable for [T; N] {
+    fn drive<V: Visitor>(&self, visitor: &mut V) {
+        self.iter().for_each(|x| x.drive(visitor))
+    }
+    fn drive_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+        self.iter_mut().for_each(|x| x.drive_mut(visitor))
+    }
+    fn may_contain(target: TypeId, seen: &mut Vec<TypeId>) -> bool {
+        T::may_contain(target, seen)
+    }
+}
+
+macro_rules! visitable_tuple {
+    ($($n:tt: $t:ident),*) => {
+        impl<$($t: Visitable),*> Visitable for ($($t,)*) {
+            fn drive<V: Visitor>(&self, visitor: &mut V) {
+                $(self.$n.drive(visitor);)*
+            }
+            fn drive_mut<V: VisitorMut>(&mut self, visitor: &mut V) {
+                $(self.$n.drive_mut(visitor);)*
+            }
+            fn may_contain(target: TypeId, seen: &mut Vec<TypeId>) -> bool {
+                false $(|| $t::may_contain(target, seen))*
+            }
+        }
+    };
+}
+
+visitable_tuple!(0: A, 1: B);
+visitable_tuple!(0: A, 1: B, 2: C);
+visitable_tuple!(0: A, 1: B, 2: C, 3: D);
+
+#[cfg(test)]
+mod tests {
+    use super::{Arc, Visitable};
+
+    #[derive(Clone, Debug, PartialEq, hax_adt_into::Visitable)]
+    enum Term {
+        Lit(u32),
+        Var(String),
+        Add(Box<Term>, Box<Term>),
+        Shared(Arc<Term>),
+    }
+    use Term::*;
+
+    fn add(lhs: Term, rhs: Term) -> Term {
+        Add(Box::new(lhs), Box::new(rhs))
+    }
+
+    #[test]
+    fn for_each_visits_in_pre_order() {
+        let e = add(Var("x".into()), add(Lit(1), Var("y".into())));
+        let mut vars = vec![];
+        e.for_each(|s: &String| vars.push(s.clone()));
+        assert_eq!(vars, ["x", "y"]);
+        let mut nodes = 0;
+        e.for_each(|_: &Term| nodes += 1);
+        assert_eq!(nodes, 5);
+    }
+
+    #[test]
+    fn fold_leaves() {
+        let e = add(Lit(1), add(Lit(2), Shared(Arc::new(Lit(3)))));
+        assert_eq!(e.fold(0, |acc, n: &u32| acc + n), 6);
+    }
+
+    #[test]
+    fn rewrite_is_post_order() {
+        let mut e = add(add(Lit(1), Lit(2)), Lit(3));
+        e.rewrite(|e: &mut Term| {
+            if let Add(lhs, rhs) = e {
+                if let (Lit(l), Lit(r)) = (&**lhs, &**rhs) {
+                    *e = Lit(l + r)
+                }
+            }
+        });
+        assert_eq!(e, Lit(6));
+    }
+
+    #[test]
+    fn drive_mut_clones_shared_nodes_only_when_needed() {
+        let shared = Arc::new(add(Lit(1), Var("x".into())));
+        let mut e = add(Shared(shared.clone()), Shared(shared.clone()));
+        e.rewrite(|_: &mut bool| ());
+        e.for_each(|t: &Term| {
+            if let Shared(e) = t {
+                assert!(Arc::ptr_eq(e, &shared))
+            }
+        });
+        e.rewrite(|s: &mut String| s.push('\''));
+        e.for_each(|t: &Term| {
+            if let Shared(e) = t {
+                assert!(!Arc::ptr_eq(e, &shared))
+            }
+        });
+        assert_eq!(*shared, add(Lit(1), Var("x".into())));
+        assert_eq!(e.fold(String::new(), |acc, s: &String| acc + s), "x'x'");
+    }
+
+    #[test]
+    fn rewrite_reaches_nested_shared_nodes() {
+        let inner = Arc::new(Shared(Arc::new(Lit(1))));
+        let mut e = add(Shared(inner.clone()), Var("x".into()));
+        e.rewrite(|n: &mut u32| *n += 1);
+        assert_eq!(
+            e,
+            add(Shared(Arc::new(Shared(Arc::new(Lit(2))))), Var("x".into()))
+        );
+        assert_eq!(*inner, Shared(Arc::new(Lit(1))));
+    }
+}
@@ -325,6 +325,57 @@ mk_bounded!(
     BoundedUsize(usize),
 );
 
+/// Implements `Deserialize` and `Arbitrary` for bounded integers,
+/// enforcing their bounds.
+macro_rules! derive_checked_traits_for_bounded {
+    ($($bounded_t:ident($t:ident)),*$(,)?) => {
+        #[hax_lib::exclude]
+        const _: () = {
+            $(
+                #[cfg(feature = "serde")]
+                impl<'de, const MIN: $t, const MAX: $t> hax_lib::__serde::Deserialize<'de>
+                    for $bounded_t<MIN, MAX>
+                {
+                    fn deserialize<D: hax_lib::__serde::Deserializer<'de>>(
+                        deserializer: D,
+                    ) -> Result<Self, D::Error> {
+                        hax_lib::__serde::deserialize_refined(deserializer)
+                    }
+                }
+
+                #[cfg(feature = "arbitrary")]
+                impl<'a, const MIN: $t, const MAX: $t> hax_lib::__arbitrary::Arbitrary<'a>
+                    for $bounded_t<MIN, MAX>
+                {
+                    fn arbitrary(
+                        u: &mut hax_lib::__arbitrary::Unstructured<'a>,
+                    ) -> hax_lib::__arbitrary::Result<Self> {
+                        if MIN > MAX {
+                            return Err(hax_lib::__arbitrary::Error::IncorrectFormat);
+                        }
+                        Ok(Self::new(u.int_in_range(MIN..=MAX)?))
+                    }
+                }
+            )*
+        };
+    };
+}
+
+derive_checked_traits_for_bounded!(
+    BoundedI8(i8),
+    BoundedI16(i16),
+    BoundedI32(i32),
+    BoundedI64(i64),
+    BoundedI128(i128),
+    BoundedIsize(isize),
+    BoundedU8(u8),
+    BoundedU16(u16),
+    BoundedU32(u32),
+    BoundedU64(u64),
+    BoundedU128(u128),
+    BoundedUsize(usize),
+);
+
 /// Makes a refined new type in a very similar way to
 /// `hax_lib::refinement_tyoe`, but derives the various traits an
 /// integer type is expected to implement.
@@ -372,7 +423,7 @@ fn tests() {
     zzz += 32;
 
     let x: BoundedU8<0, 5> = 2.into_checked();
-    let y: BoundedU8<5, 10> = (x + x).into_checked();
+    let y: BoundedU8<4, 10> = (x + x).into_checked();
 
     let _ = x >> 3;
     let _ = x >> BoundedU8::<0, 5>::new(3);
@@ -391,3 +442,38 @@ fn tests() {
     let _ = 4 + y;
     let _ = 4 - y;
 }
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic]
+fn invariant_is_checked_in_debug_builds() {
+    let _ = BoundedU8::<1, 10>::new(0);
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn deserialize_checks_bounds() {
+    use hax_lib::{Refinement, __serde::Deserialize};
+    use serde::de::value::{Error, U8Deserializer};
+    let deserialize = |x| BoundedU8::<1, 10>::deserialize(U8Deserializer::<Error>::new(x));
+    assert_eq!(deserialize(1).map(Refinement::get), Ok(1));
+    assert_eq!(deserialize(10).map(Refinement::get), Ok(10));
+    assert!(deserialize(0).is_err());
+    assert!(deserialize(11).is_err());
+}
+
+#[test]
+#[cfg(feature = "arbitrary")]
+fn arbitrary_respects_bounds() {
+    use hax_lib::{
+        Refinement,
+        __arbitrary::{Arbitrary, Unstructured},
+    };
+    for byte in 0..=u8::MAX {
+        let x = BoundedU8::<3, 7>::arbitrary(&mut Unstructured::new(&[byte])).unwrap();
+        assert!((3..=7).contains(&x.get()));
+    }
+    let x = BoundedU8::<5, 5>::arbitrary(&mut Unstructured::new(&[])).unwrap();
+    assert_eq!(x.get(), 5);
+    assert!(BoundedU8::<7, 3>::arbitrary(&mut Unstructured::new(&[0])).is_err());
+}
@@ -0,0 +1,45 @@
+//! Benchmarks of mathematical integers (`hax_lib::int::Int`).
+
+mod int {
+    use criterion::Criterion;
+    use hax_lib::int::*;
+    use std::hint::black_box;
+
+    /// Sums the squares of `n` machine integers, as a specification
+    /// would.
+    fn sum_of_squares(n: u64) -> Int {
+        (0..n).fold(0u8.lift(), |acc, i| acc + i.lift() * i.lift())
+    }
+
+    pub fn benches(c: &mut Criterion) {
+        let small: Int = 123456789u64.lift();
+        let large =
+            Int::_unsafe_from_str("123456789012345678901234567890123456789012345678901234567890");
+
+        c.bench_function("lift and concretize", |b| {
+            b.iter(|| black_box(black_box(42u32).lift()).to_u32())
+        });
+        c.bench_function("small arithmetic", |b| {
+            b.iter(|| {
+                let x = black_box(small);
+                (x * x + x - x / x) % x
+            })
+        });
+        c.bench_function("small comparisons", |b| {
+            b.iter(|| black_box(small) < black_box(large) && black_box(small) >= 0)
+        });
+        c.bench_function("large arithmetic", |b| {
+            b.iter(|| {
+                let x = black_box(large);
+                (x * x + x - x / x) % x
+            })
+        });
+        c.bench_function("sum of squares", |b| {
+            b.iter(|| sum_of_squares(black_box(1000)))
+        });
+        c.bench_function("pow2", |b| b.iter(|| black_box(64u8).lift().pow2()));
+    }
+}
+
+criterion::criterion_group!(benches, int::benches);
+criterion::criterion_main!(benches);
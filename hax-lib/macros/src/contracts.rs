@@ -0,0 +1,134 @@
+//! Helpers shared by the runtime checking of contracts: the
+//! `runtime-contracts` and `test-contracts` features.
+
+use crate::hax_paths::*;
+use crate::syn_ext::*;
+use quote::format_ident;
+use syn::{visit::Visit, visit_mut::VisitMut, *};
+
+/// The backends that have quoting macros, see
+/// `make_quoting_proc_macro!` in `implementation.rs`.
+const BACKENDS: &[&str] = &["fstar", "coq", "proverif"];
+
+/// Is `name` the name of a macro that inlines backend code: `fstar`,
+/// `fstar_expr` or `fstar_unsafe_expr` for F*?
+fn is_backend_macro(name: &str) -> bool {
+    let backend = name
+        .strip_suffix("_unsafe_expr")
+        .or_else(|| name.strip_suffix("_expr"))
+        .unwrap_or(name);
+    BACKENDS.contains(&backend)
+}
+
+/// Does `tokens` contain a call to a backend macro? Macro arguments
+/// are not parsed as expressions: this looks for `NAME!` tokens.
+fn tokens_call_backend_macro(tokens: proc_macro2::TokenStream) -> bool {
+    use proc_macro2::TokenTree;
+    let mut previous_is_backend_macro = false;
+    for token in tokens {
+        match &token {
+            TokenTree::Punct(p) if p.as_char() == '!' && previous_is_backend_macro => return true,
+            TokenTree::Group(group) if tokens_call_backend_macro(group.stream()) => return true,
+            _ => (),
+        }
+        previous_is_backend_macro =
+            matches!(&token, TokenTree::Ident(ident) if is_backend_macro(&ident.to_string()));
+    }
+    false
+}
+
+/// Can the contract `phi` be evaluated at runtime?
+pub fn is_evaluable(phi: &Expr) -> bool {
+    struct FindBackendExpr(bool);
+    impl<'a> Visit<'a> for FindBackendExpr {
+        fn visit_macro(&mut self, mac: &'a Macro) {
+            let name = mac.path.segments.last().map(|s| s.ident.to_string());
+            if name.as_deref().is_some_and(is_backend_macro)
+                || tokens_call_backend_macro(mac.tokens.clone())
+            {
+                self.0 = true;
+            }
+        }
+    }
+    let mut visitor = FindBackendExpr(false);
+    visitor.visit_expr(phi);
+    !visitor.0
+}
+
+/// Is `ty` a dynamically sized type: a slice or `str`?
+pub fn is_unsized(ty: &Type) -> bool {
+    match ty {
+        Type::Slice(_) => true,
+        Type::Path(TypePath { qself: None, path }) => path.is_ident("str"),
+        _ => false,
+    }
+}
+
+/// Rewrites a postcondition so that it can be evaluated after the
+/// body: `future(x)` becomes the final value of `*x`, while `x`
+/// becomes the initial value of `*x`, for every `&mut` input `x`.
+/// Collects the inputs whose initial value is needed.
+pub struct RewriteMutRefInputs {
+    pub inputs: Vec<Ident>,
+    pub snapshots: Vec<Ident>,
+}
+
+impl RewriteMutRefInputs {
+    fn input(&self, e: &Expr) -> Option<Ident> {
+        e.expect_ident().filter(|ident| self.inputs.contains(ident))
+    }
+}
+
+pub fn snapshot_ident(input: &Ident) -> Ident {
+    format_ident!("__hax_initial_{}", input)
+}
+
+impl VisitMut for RewriteMutRefInputs {
+    fn visit_expr_mut(&mut self, e: &mut Expr) {
+        if let Expr::Call(call) = e {
+            if call.func.is_ident("future") && call.args.len() == 1 {
+                if let Some(input) = self.input(&call.args[0]) {
+                    *e = parse_quote! {(&*#input)};
+                    return;
+                }
+            }
+        }
+        if let Some(input) = self.input(e) {
+            if !self.snapshots.contains(&input) {
+                self.snapshots.push(input.clone());
+            }
+            let snapshot = snapshot_ident(&input);
+            *e = parse_quote! {(&#snapshot)};
+            return;
+        }
+        visit_mut::visit_expr_mut(self, e);
+    }
+}
+
+/// A `requires` or `ensures` clause of a function.
+pub enum Clause {
+    Requires(Expr),
+    Ensures(Pat, Expr),
+}
+
+/// The `requires` and `ensures` clauses among the attributes `attrs`,
+/// in order.
+pub fn clauses(attrs: &[Attribute]) -> Result<Vec<Clause>> {
+    attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::List(ml) => match expects_path_decoration(&ml.path) {
+                Ok(Some(kind)) => Some((kind, ml.tokens.clone())),
+                _ => None,
+            },
+            _ => None,
+        })
+        .filter_map(|(kind, tokens)| match kind.as_str() {
+            "requires" => Some(parse2(tokens).map(Clause::Requires)),
+            "ensures" => Some(
+                parse2(tokens).map(|ExprClosure1 { arg, body }| Clause::Ensures(arg, body)),
+            ),
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,445 @@
+//! Ghost code: functions, `let`-bindings and statements that exist
+//! only for verification. Ghost code is compiled under hax and in
+//! debug builds, and compiled out of release builds.
+//!
+//! Custom attributes are not allowed on statements by Rust: ghost
+//! `let`-bindings and statements are rewritten by an enclosing
+//! `hax_lib::attributes` item.
+
+use crate::hax_paths::*;
+use proc_macro2::TokenStream;
+use syn::{punctuated::Punctuated, spanned::Spanned, visit_mut::VisitMut, *};
+
+fn is_ghost_attribute(attr: &Attribute) -> bool {
+    matches!(attr.meta, Meta::Path(_))
+        && matches!(expects_hax_path(&["ghost"], attr.path()), Ok(Some(_)))
+}
+
+/// Removes the `ghost` attributes of `attrs`, telling whether there
+/// was one.
+fn take_ghost_attribute(attrs: &mut Vec<Attribute>) -> bool {
+    let len = attrs.len();
+    attrs.retain(|attr| !is_ghost_attribute(attr));
+    attrs.len() != len
+}
+
+/// The attributes of a statement. As `syn` does, the attributes of an
+/// expression statement are those of its leftmost operand.
+fn stmt_attrs_mut(stmt: &mut Stmt) -> Option<&mut Vec<Attribute>> {
+    let mut expr = match stmt {
+        Stmt::Local(local) => return Some(&mut local.attrs),
+        Stmt::Macro(mac) => return Some(&mut mac.attrs),
+        Stmt::Expr(expr, _) => expr,
+        Stmt::Item(_) => return None,
+    };
+    loop {
+        expr = match expr {
+            Expr::Assign(e) => &mut e.left,
+            Expr::Binary(e) => &mut e.left,
+            Expr::Cast(e) => &mut e.expr,
+            _ => break,
+        }
+    }
+    Some(match expr {
+        Expr::Block(e) => &mut e.attrs,
+        Expr::Call(e) => &mut e.attrs,
+        Expr::Field(e) => &mut e.attrs,
+        Expr::ForLoop(e) => &mut e.attrs,
+        Expr::If(e) => &mut e.attrs,
+        Expr::Index(e) => &mut e.attrs,
+        Expr::Loop(e) => &mut e.attrs,
+        Expr::Macro(e) => &mut e.attrs,
+        Expr::Match(e) => &mut e.attrs,
+        Expr::MethodCall(e) => &mut e.attrs,
+        Expr::Paren(e) => &mut e.attrs,
+        Expr::Path(e) => &mut e.attrs,
+        Expr::Unary(e) => &mut e.attrs,
+        Expr::While(e) => &mut e.attrs,
+        _ => return None,
+    })
+}
+
+/// The variables bound by the patterns in `node`.
+fn bound_variables<T: Clone>(node: &T, visit: fn(&mut Collect, &mut T)) -> Vec<Ident> {
+    let mut collect = Collect(vec![]);
+    visit(&mut collect, &mut node.clone());
+    collect.0
+}
+
+struct Collect(Vec<Ident>);
+impl VisitMut for Collect {
+    fn visit_pat_ident_mut(&mut self, pat: &mut PatIdent) {
+        self.0.push(pat.ident.clone());
+        visit_mut::visit_pat_ident_mut(self, pat)
+    }
+}
+
+/// The variable a place (e.g. `x.f[i]`) is rooted in, if any.
+fn place_root(place: &Expr) -> Option<&Ident> {
+    let mut root = place;
+    loop {
+        root = match root {
+            Expr::Field(e) => &e.base,
+            Expr::Index(e) => &e.expr,
+            Expr::Paren(e) => &e.expr,
+            Expr::Unary(ExprUnary {
+                op: UnOp::Deref(_),
+                expr,
+                ..
+            }) => expr,
+            _ => break,
+        }
+    }
+    let Expr::Path(ExprPath { path, .. }) = root else {
+        return None;
+    };
+    path.get_ident()
+}
+
+/// Checks that `stmt` only assigns to or mutably borrows ghost
+/// variables. Method calls, or variables passed to functions, are not
+/// checked: whether they are mutably borrowed is unknown
+/// syntactically. Instead, the non-ghost variables that `stmt`
+/// mentions (`is_var`) are returned, so that the borrow checker can
+/// check them. The arguments of macros are checked if they parse as
+/// expressions.
+fn check_mutations(
+    stmt: &Stmt,
+    is_ghost: &dyn Fn(&Ident) -> bool,
+    is_var: &dyn Fn(&Ident) -> bool,
+) -> Result<Vec<Ident>> {
+    struct Check<'a> {
+        is_ghost: &'a dyn Fn(&Ident) -> bool,
+        is_var: &'a dyn Fn(&Ident) -> bool,
+        mentioned: Vec<Ident>,
+        error: Option<Error>,
+    }
+    impl Check<'_> {
+        fn check_place(&mut self, place: &Expr) {
+            let Some(var) = place_root(place) else {
+                return;
+            };
+            if !(self.is_ghost)(var) && self.error.is_none() {
+                self.error = Some(Error::new(
+                    place.span(),
+                    format!("Ghost code can only modify ghost variables, `{var}` is not one"),
+                ));
+            }
+        }
+    }
+    impl VisitMut for Check<'_> {
+        fn visit_expr_mut(&mut self, expr: &mut Expr) {
+            match &*expr {
+                Expr::Assign(ExprAssign { left, .. }) => self.check_place(left),
+                Expr::Binary(ExprBinary {
+                    left,
+                    op:
+                        BinOp::AddAssign(_)
+                        | BinOp::SubAssign(_)
+                        | BinOp::MulAssign(_)
+                        | BinOp::DivAssign(_)
+                        | BinOp::RemAssign(_)
+                        | BinOp::BitXorAssign(_)
+                        | BinOp::BitAndAssign(_)
+                        | BinOp::BitOrAssign(_)
+                        | BinOp::ShlAssign(_)
+                        | BinOp::ShrAssign(_),
+                    ..
+                }) => self.check_place(left),
+                Expr::Reference(ExprReference {
+                    mutability: Some(_),
+                    expr,
+                    ..
+                }) => self.check_place(expr),
+                Expr::Path(ExprPath { path, .. }) => {
+                    if let Some(var) = path.get_ident() {
+                        if !(self.is_ghost)(var)
+                            && (self.is_var)(var)
+                            && !self.mentioned.contains(var)
+                        {
+                            self.mentioned.push(var.clone());
+                        }
+                    }
+                }
+                _ => (),
+            }
+            visit_mut::visit_expr_mut(self, expr)
+        }
+
+        fn visit_macro_mut(&mut self, mac: &mut Macro) {
+            if let Ok(args) = mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated)
+            {
+                for mut arg in args {
+                    self.visit_expr_mut(&mut arg);
+                }
+            }
+        }
+    }
+    let mut check = Check {
+        is_ghost,
+        is_var,
+        mentioned: vec![],
+        error: None,
+    };
+    check.visit_stmt_mut(&mut stmt.clone());
+    check.error.map_or(Ok(check.mentioned), Err)
+}
+
+/// Checks that the ghost function `sig` cannot modify non-ghost state.
+pub fn check_ghost_fn(sig: &Signature) -> Result<()> {
+    if let Some(unsafety) = sig.unsafety {
+        return Err(Error::new(
+            unsafety.span(),
+            "A ghost function cannot be `unsafe`",
+        ));
+    }
+    for input in &sig.inputs {
+        let mutable_reference = match input {
+            FnArg::Receiver(receiver) => {
+                receiver.reference.is_some() && receiver.mutability.is_some()
+            }
+            FnArg::Typed(PatType { ty, .. }) => matches!(
+                &**ty,
+                Type::Reference(TypeReference {
+                    mutability: Some(_),
+                    ..
+                })
+            ),
+        };
+        if mutable_reference {
+            return Err(Error::new(
+                input.span(),
+                "A ghost function cannot take mutable references: it must not modify non-ghost state",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Compiles the `let`-bindings and statements marked `ghost`,
+/// checking that they only modify ghost variables.
+///
+/// Ghost code is compiled under the configuration predicate `cfg`.
+/// There, the non-ghost variables a ghost statement mentions are
+/// borrowed for the duration of the statement: the borrow checker
+/// rejects the method calls and the functions that would modify them
+/// (e.g. `v.push(x)` or `std::mem::take(v)` with `v: &mut Vec<_>`). Under the configuration predicate
+/// `extraction_cfg`, ghost code is rather wrapped in
+/// `hax_lib::_internal_ghost`, which marks it as ghost for the
+/// backends.
+pub struct GhostStatements {
+    cfg: TokenStream,
+    extraction_cfg: Option<TokenStream>,
+    /// The variables in scope: whether a variable is ghost is given
+    /// by its last binding.
+    vars: Vec<(Ident, bool)>,
+    pub errors: Vec<Error>,
+}
+
+impl GhostStatements {
+    pub fn new(cfg: TokenStream, extraction_cfg: Option<TokenStream>) -> Self {
+        GhostStatements {
+            cfg,
+            extraction_cfg,
+            vars: vec![],
+            errors: vec![],
+        }
+    }
+
+    fn is_ghost(&self, var: &Ident) -> bool {
+        self.vars
+            .iter()
+            .rev()
+            .find(|(v, _)| v == var)
+            .is_some_and(|(_, ghost)| *ghost)
+    }
+
+    fn is_var(&self, var: &Ident) -> bool {
+        self.vars.iter().any(|(v, _)| v == var)
+    }
+
+    fn bind(&mut self, pat: &Pat, ghost: bool) {
+        let bound = bound_variables(pat, Collect::visit_pat_mut);
+        self.vars.extend(bound.into_iter().map(|var| (var, ghost)));
+    }
+
+    /// Binds the inputs of a function or closure, which are not ghost.
+    fn bind_inputs<'a>(&mut self, inputs: impl Iterator<Item = &'a FnArg>) {
+        for input in inputs {
+            match input {
+                FnArg::Receiver(receiver) => self
+                    .vars
+                    .push((Ident::from(receiver.self_token), false)),
+                FnArg::Typed(PatType { pat, .. }) => self.bind(pat, false),
+            }
+        }
+    }
+
+    /// Compiles the ghost statement `stmt` into `stmts`.
+    fn ghost_stmt(&mut self, stmt: Stmt, stmts: &mut Vec<Stmt>) {
+        // The variables bound inside a ghost statement are ghost. The
+        // variables bound by a ghost `let` are so once it was
+        // evaluated.
+        let inner = match &stmt {
+            Stmt::Local(Local {
+                init: Some(init), ..
+            }) => bound_variables(init, Collect::visit_local_init_mut),
+            Stmt::Local(_) => vec![],
+            _ => bound_variables(&stmt, Collect::visit_stmt_mut),
+        };
+        let mentioned = check_mutations(
+            &stmt,
+            &|var| self.is_ghost(var) || inner.contains(var),
+            &|var| self.is_var(var),
+        );
+        let mentioned = mentioned.unwrap_or_else(|err| {
+            self.errors.push(err);
+            vec![]
+        });
+        if let Stmt::Local(local) = &stmt {
+            self.bind(&local.pat, true);
+        }
+
+        let cfg = &self.cfg;
+        let borrows = Ident::new("ghost_borrows", proc_macro2::Span::mixed_site());
+        let (borrow, release): (Vec<Stmt>, Vec<Stmt>) = if mentioned.is_empty() {
+            (vec![], vec![])
+        } else {
+            (
+                vec![parse_quote! {#[cfg(#cfg)] let #borrows = (#(&#mentioned,)*);}],
+                vec![parse_quote! {#[cfg(#cfg)] ::core::mem::drop(#borrows);}],
+            )
+        };
+        let extracted = self.extraction_cfg.as_ref().map(|extraction_cfg| {
+            let mut stmt = stmt.clone();
+            match &mut stmt {
+                Stmt::Local(Local {
+                    attrs,
+                    init: Some(init),
+                    ..
+                }) => {
+                    let expr = &init.expr;
+                    init.expr = parse_quote! {::hax_lib::_internal_ghost(#expr)};
+                    attrs.push(parse_quote! {#[cfg(#extraction_cfg)]});
+                    stmt
+                }
+                Stmt::Local(local) => {
+                    local.attrs.push(parse_quote! {#[cfg(#extraction_cfg)]});
+                    stmt
+                }
+                _ => parse_quote! {
+                    #[cfg(#extraction_cfg)]
+                    ::hax_lib::_internal_ghost({ #stmt });
+                },
+            }
+        });
+        stmts.extend(borrow);
+        match stmt {
+            Stmt::Local(mut local) => {
+                local.attrs.push(parse_quote! {#[cfg(#cfg)]});
+                local.attrs.push(parse_quote! {#[allow(unused)]});
+                stmts.push(Stmt::Local(local));
+            }
+            // `syn` would print the attributes of an expression
+            // statement on its leftmost operand, in parentheses: the
+            // statement is wrapped in a block instead.
+            stmt => stmts.push(parse_quote! {
+                #[cfg(#cfg)]
+                #[allow(unused)]
+                {
+                    #stmt
+                };
+            }),
+        }
+        stmts.extend(release);
+        stmts.extend(extracted);
+    }
+}
+
+impl VisitMut for GhostStatements {
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        let scope = self.vars.len();
+        for mut stmt in std::mem::take(&mut block.stmts) {
+            if stmt_attrs_mut(&mut stmt).is_some_and(take_ghost_attribute) {
+                self.ghost_stmt(stmt, &mut block.stmts);
+            } else {
+                visit_mut::visit_stmt_mut(self, &mut stmt);
+                // A non-ghost `let` shadows ghost variables.
+                if let Stmt::Local(local) = &stmt {
+                    self.bind(&local.pat, false);
+                }
+                block.stmts.push(stmt);
+            }
+        }
+        self.vars.truncate(scope);
+    }
+
+    fn visit_item_fn_mut(&mut self, item: &mut ItemFn) {
+        let vars = std::mem::take(&mut self.vars);
+        self.bind_inputs(item.sig.inputs.iter());
+        visit_mut::visit_item_fn_mut(self, item);
+        self.vars = vars;
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item: &mut ImplItemFn) {
+        let vars = std::mem::take(&mut self.vars);
+        self.bind_inputs(item.sig.inputs.iter());
+        visit_mut::visit_impl_item_fn_mut(self, item);
+        self.vars = vars;
+    }
+
+    fn visit_trait_item_fn_mut(&mut self, item: &mut TraitItemFn) {
+        let vars = std::mem::take(&mut self.vars);
+        self.bind_inputs(item.sig.inputs.iter());
+        visit_mut::visit_trait_item_fn_mut(self, item);
+        self.vars = vars;
+    }
+
+    fn visit_expr_closure_mut(&mut self, closure: &mut ExprClosure) {
+        let scope = self.vars.len();
+        for input in &closure.inputs {
+            self.bind(input, false);
+        }
+        visit_mut::visit_expr_closure_mut(self, closure);
+        self.vars.truncate(scope);
+    }
+
+    fn visit_expr_for_loop_mut(&mut self, for_loop: &mut ExprForLoop) {
+        self.visit_expr_mut(&mut for_loop.expr);
+        let scope = self.vars.len();
+        self.bind(&for_loop.pat, false);
+        self.visit_block_mut(&mut for_loop.body);
+        self.vars.truncate(scope);
+    }
+
+    fn visit_arm_mut(&mut self, arm: &mut Arm) {
+        let scope = self.vars.len();
+        self.bind(&arm.pat, false);
+        visit_mut::visit_arm_mut(self, arm);
+        self.vars.truncate(scope);
+    }
+
+    fn visit_expr_if_mut(&mut self, expr: &mut ExprIf) {
+        let scope = self.vars.len();
+        self.visit_expr_mut(&mut expr.cond);
+        self.visit_block_mut(&mut expr.then_branch);
+        self.vars.truncate(scope);
+        if let Some((_, else_branch)) = &mut expr.else_branch {
+            self.visit_expr_mut(else_branch);
+        }
+    }
+
+    fn visit_expr_while_mut(&mut self, expr: &mut ExprWhile) {
+        let scope = self.vars.len();
+        self.visit_expr_mut(&mut expr.cond);
+        self.visit_block_mut(&mut expr.body);
+        self.vars.truncate(scope);
+    }
+
+    /// The bindings of `let` conditions are in scope in the branches
+    /// or bodies of `if` and `while`, see above.
+    fn visit_expr_let_mut(&mut self, expr: &mut ExprLet) {
+        self.visit_expr_mut(&mut expr.expr);
+        self.bind(&expr.pat, false);
+    }
+}
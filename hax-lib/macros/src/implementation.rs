@@ -1,6 +1,8 @@
+mod ghost;
 mod hax_paths;
 mod impl_fn_decoration;
 mod quote;
+mod refinement_type;
 mod rewrite_self;
 mod syn_ext;
 mod utils;
@@ -23,6 +25,7 @@ mod prelude {
 
 use impl_fn_decoration::*;
 use prelude::*;
+use refinement_type::*;
 use utils::*;
 
 /// When extracting to F*, wrap this item in `#push-options "..."` and
@@ -42,13 +45,16 @@ pub fn fstar_options(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenS
     .into()
 }
 
-/// Add an invariant to a loop which deals with an index. The
-/// invariant cannot refer to any variable introduced within the
-/// loop. An invariant is a closure that takes one argument, the
-/// index, and returns a boolean.
+/// Add an invariant to a loop. This macro must be called on the first
+/// lines of a loop body to be effective. The invariant cannot refer to
+/// any variable introduced within the loop. Note that in the
+/// invariant expression, `forall`, `exists`, and `BACKEND!` (`BACKEND`
+/// can be `fstar`, `proverif`, `coq`...) are in scope.
 ///
-/// Note that loop invariants are unstable (this will be handled in a
-/// better way in the future, see
+/// For a `for` loop which deals with an index, an invariant is a
+/// closure that takes one argument, the index, and returns a
+/// boolean. Note that loop invariants are unstable (this will be
+/// handled in a better way in the future, see
 /// https://github.com/hacspec/hax/issues/858) and only supported on
 /// specific `for` loops with specific iterators:
 ///
@@ -57,24 +63,80 @@ pub fn fstar_options(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenS
 ///  - `for i in slice.enumerate() {...}`
 ///  - `for i in slice.chunks_exact(n).enumerate() {...}`
 ///
-/// This function must be called on the first line of a loop body to
-/// be effective. Note that in the invariant expression, `forall`,
-/// `exists`, and `BACKEND!` (`BACKEND` can be `fstar`, `proverif`,
-/// `coq`...) are in scope.
+/// For a `while` or a `loop` loop, an invariant is a boolean
+/// expression over the variables the loop modifies. It should hold
+/// before the loop, and after each iteration. Such a loop also needs
+/// a termination measure, given with [`loop_decreases!`]: hax rejects
+/// an invariant without one.
+///
+/// # Example
+///
+/// ```
+/// use hax_lib_macros::*;
+/// use hax_lib::int::*;
+/// fn count(n: u32) -> u32 {
+///     let mut i = 0;
+///     while i < n {
+///         loop_invariant!(i <= n);
+///         loop_decreases!(n.lift() - i.lift());
+///         i += 1;
+///     }
+///     i
+/// }
+/// ```
 #[proc_macro]
 pub fn loop_invariant(predicate: pm::TokenStream) -> pm::TokenStream {
-    let predicate: TokenStream = predicate.into();
-    let ts: pm::TokenStream = quote! {
+    let predicate: Expr = parse_macro_input!(predicate);
+    let invariant = match predicate {
+        Expr::Closure(_) => quote! {hax_lib::_internal_loop_invariant},
+        _ => quote! {hax_lib::_internal_while_loop_invariant},
+    };
+    quote! {
         #[cfg(#HaxCfgOptionName)]
         {
-            hax_lib::_internal_loop_invariant({
+            #invariant({
                 #HaxQuantifiers
                 #predicate
             })
         }
     }
-    .into();
-    ts
+    .into()
+}
+
+/// Provide a termination measure for a `while` or a `loop` loop: an
+/// `Int` expression over the variables the loop modifies, which stays
+/// non-negative and decreases at each iteration. Like
+/// [`loop_invariant!`], this macro must be called on the first lines
+/// of a loop body.
+///
+/// # Example
+///
+/// ```
+/// use hax_lib_macros::*;
+/// use hax_lib::int::*;
+/// fn count(n: u32) -> u32 {
+///     let mut i = 0;
+///     while i < n {
+///         loop_invariant!(i <= n);
+///         loop_decreases!(n.lift() - i.lift());
+///         i += 1;
+///     }
+///     i
+/// }
+/// ```
+#[proc_macro]
+pub fn loop_decreases(measure: pm::TokenStream) -> pm::TokenStream {
+    let measure: TokenStream = measure.into();
+    quote! {
+        #[cfg(#HaxCfgOptionName)]
+        {
+            hax_lib::_internal_loop_decreases({
+                #HaxQuantifiers
+                #measure
+            })
+        }
+    }
+    .into()
 }
 
 /// When extracting to F*, inform about what is the current
@@ -196,6 +258,21 @@ pub fn modeled_by(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStre
 /// /// $`\forall n \in \mathbb{N}, \textrm{ackermann}(2, n) = 2 (n + 3) - 3`$
 /// pub fn ackermann_property_m1(n: u64) -> Proof<{ ackermann(2, n) == 2 * (n + 3) - 3 }> {}
 /// ```
+///
+/// A lemma can be given SMT patterns with `smt_pat(TERM, ...)`: the
+/// lemma is then applied automatically to any term matching all of
+/// the `TERM`s, which are Rust expressions over the inputs of the
+/// lemma.
+///
+/// ```
+/// use hax_lib_macros::*;
+/// fn double(n: u64) -> u64 {
+///     n.wrapping_mul(2)
+/// }
+///
+/// #[lemma(smt_pat(double(n)))]
+/// fn double_is_even(n: u64) -> Proof<{ double(n) % 2 == 0 }> {}
+/// ```
 #[proc_macro_error]
 #[proc_macro_attribute]
 pub fn lemma(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
@@ -227,7 +304,20 @@ pub fn lemma(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
         };
         Some(e.clone())
     }
-    let _ = parse_macro_input!(attr as parse::Nothing);
+    let LemmaArgs { smt_patterns } = parse_macro_input!(attr);
+    let (smt_patterns, smt_patterns_attr) = match smt_patterns {
+        Some(patterns) => {
+            let (decoration, attr) = make_fn_decoration(
+                parse_quote! {(#(#patterns),*)},
+                item.sig.clone(),
+                FnDecorationKind::SmtPatterns,
+                None,
+                None,
+            );
+            (decoration, Some(attr))
+        }
+        None => (quote! {}, None),
+    };
     let attr = &AttrPayload::Lemma;
     if let ReturnType::Type(_, r#type) = &item.sig.output {
         if !match r#type.borrow() {
@@ -235,10 +325,12 @@ pub fn lemma(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
             _ => match parse_proof_type(*r#type.clone()) {
                 Some(ensures_clause) => {
                     item.sig.output = ReturnType::Default;
-                    return ensures(
+                    let item: TokenStream = ensures(
                         quote! {|_| #ensures_clause}.into(),
-                        quote! { #attr #item }.into(),
-                    );
+                        quote! { #attr #smt_patterns_attr #item }.into(),
+                    )
+                    .into();
+                    return quote! { #smt_patterns #item }.into();
                 }
                 None => false,
             },
@@ -250,7 +342,92 @@ pub fn lemma(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
         }
     }
     use AttrPayload::NeverErased;
-    quote! { #attr #NeverErased #item }.into()
+    quote! { #smt_patterns #attr #smt_patterns_attr #NeverErased #item }.into()
+}
+
+/// The arguments of `lemma`: nothing, or `smt_pat(TERM, ...)`.
+struct LemmaArgs {
+    smt_patterns: Option<Vec<syn::Expr>>,
+}
+
+impl parse::Parse for LemmaArgs {
+    fn parse(input: parse::ParseStream) -> Result<Self> {
+        if input.is_empty() {
+            return Ok(LemmaArgs { smt_patterns: None });
+        }
+        let list: MetaList = input.parse()?;
+        if !list.path.is_ident("smt_pat") {
+            return Err(Error::new(list.path.span(), "Expected `smt_pat(...)`"));
+        }
+        let patterns = list.parse_args_with(
+            punctuated::Punctuated::<syn::Expr, Token![,]>::parse_separated_nonempty,
+        )?;
+        Ok(LemmaArgs {
+            smt_patterns: Some(patterns.into_iter().collect()),
+        })
+    }
+}
+
+/// The configuration predicate under which ghost code is compiled.
+fn ghost_cfg() -> TokenStream {
+    quote! {any(#HaxCfgOptionName, debug_assertions)}
+}
+
+/// Mark a function as ghost: it exists only for verification, e.g. as
+/// a helper of specifications. A ghost function is compiled under hax
+/// and in debug builds, and compiled out of release builds. It cannot
+/// modify non-ghost state: it takes no mutable reference and is not
+/// `unsafe`. It is extracted as ghost code (e.g. `noextract` in F*).
+///
+/// Inside an item decorated with [`attributes`], `let`-bindings and
+/// statements can be marked `#[ghost]` as well. Ghost statements can
+/// only modify ghost variables. They are extracted as ghost code as
+/// well: their values are marked by `_internal_ghost` (an identity).
+///
+/// # Example
+///
+/// ```
+/// use hax_lib_macros::*;
+/// #[ghost]
+/// fn spec_sum(xs: &[u32]) -> u64 {
+///     xs.iter().map(|x| *x as u64).sum()
+/// }
+///
+/// #[attributes]
+/// #[ensures(|result| result == spec_sum(xs))]
+/// fn sum(xs: &[u32]) -> u64 {
+///     #[ghost]
+///     let mut steps = 0;
+///     let mut sum = 0;
+///     for x in xs {
+///         sum += *x as u64;
+///         #[ghost]
+///         steps += 1;
+///     }
+///     sum
+/// }
+/// ```
+///
+/// The non-ghost variables a ghost statement mentions are borrowed
+/// while it runs: ghost statements cannot pass them mutably to
+/// functions or methods.
+#[proc_macro_error]
+#[proc_macro_attribute]
+pub fn ghost(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
+    let _ = parse_macro_input!(attr as parse::Nothing);
+    let item: FnLike = parse_macro_input!(item);
+    if let Err(err) = ghost::check_ghost_fn(&item.sig) {
+        return err.into_compile_error().into();
+    }
+    let cfg = ghost_cfg();
+    let attr = AttrPayload::Ghost;
+    quote! {
+        #[cfg(#cfg)]
+        #[allow(dead_code)]
+        #attr
+        #item
+    }
+    .into()
 }
 
 /*
@@ -300,6 +477,10 @@ pub fn decreases(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStrea
 /// backend `BACKEND`) to inline F* (or Coq, ProVerif, etc.) code in
 /// the precondition, e.g. `fstar!("true")`.
 ///
+/// With the `runtime-contracts` feature of `hax-lib`, the
+/// precondition is checked by an `assert!` on entry of the function,
+/// compiled in debug builds without hax only.
+///
 /// # Example
 ///
 /// ```
@@ -348,6 +529,13 @@ pub fn requires(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream
 /// backend `BACKEND`) to inline F* (or Coq, ProVerif, etc.) code in
 /// the postcondition, e.g. `fstar!("true")`.
 ///
+/// With the `runtime-contracts` feature of `hax-lib`, the
+/// postcondition is checked by an `assert!` on the result of the
+/// function, compiled in debug builds without hax only. The inputs it
+/// mentions, including the past values of `&mut` inputs, are cloned
+/// before the call: postconditions that mention an input which is not
+/// `Clone` are not checked.
+///
 /// # Example
 ///
 /// ```
@@ -383,6 +571,51 @@ pub fn ensures(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream
     .into()
 }
 
+/// Generate a property-based test from the contract of a function.
+/// With the `test-contracts` feature of `hax-lib`, when compiled
+/// without hax, this generates a QuickCheck test named
+/// `test_contract_<FUNCTION>` that samples the inputs of the
+/// function, discards the ones that violate its `requires` clauses,
+/// and asserts its `ensures` clauses on the result. This attribute
+/// must be placed above the `requires` and `ensures` attributes.
+///
+/// The inputs of the function must implement `quickcheck::Arbitrary`
+/// (slices and `&str` are sampled as vectors and strings).
+/// Postconditions that use backend-specific code (e.g. `fstar!`) are
+/// not checked, and the test is ignored if a precondition does.
+/// Generic functions and methods are not supported.
+///
+/// # Example
+///
+/// ```
+/// use hax_lib_macros::*;
+/// #[test_contract]
+/// #[requires(x < 1000)]
+/// #[ensures(|result| result == x * 2)]
+/// pub fn twice(x: u64) -> u64 {
+///     x + x
+/// }
+/// ```
+#[proc_macro_error]
+#[proc_macro_attribute]
+pub fn test_contract(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
+    let _ = parse_macro_input!(attr as parse::Nothing);
+    let item: ItemFn = parse_macro_input!(item);
+    let has_clause = item.attrs.iter().any(|attr| {
+        matches!(
+            expects_path_decoration(attr.path()),
+            Ok(Some(kind)) if kind == "requires" || kind == "ensures"
+        )
+    });
+    if !has_clause {
+        abort!(
+            item.sig.ident.span(),
+            "`test_contract` found no `requires` or `ensures` clause: it must be placed above them"
+        );
+    }
+    quote! {#item}.into()
+}
+
 mod kw {
     syn::custom_keyword!(hax_lib);
     syn::custom_keyword!(decreases);
@@ -609,12 +842,18 @@ pub fn attributes(_attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStr
         }
     }
 
-    let mut v = AttrVisitor::default();
     let mut item = item;
+    let mut ghost = ghost::GhostStatements::new(
+        quote! {all(not(#HaxCfgOptionName), debug_assertions)},
+        Some(quote! {#HaxCfgOptionName}),
+    );
+    ghost.visit_item_mut(&mut item);
+    let mut v = AttrVisitor::default();
     v.visit_item_mut(&mut item);
     let extra_items = v.extra_items;
+    let errors = ghost.errors.into_iter().map(Error::into_compile_error);
 
-    quote! { #item #(#extra_items)* }.into()
+    quote! { #item #(#extra_items)* #(#errors)* }.into()
 }
 
 /// Mark an item opaque: the extraction will assume the
@@ -889,55 +1128,36 @@ make_quoting_proc_macro!(fstar coq proverif);
 /// requires the base type `T` to implement `Clone`. Pass a first
 /// parameter `no_debug_runtime_check` to disable this behavior.
 ///
+/// The options `serde` and `arbitrary` (e.g.
+/// `#[refinement_type(serde, |x| x > 0)]`) respectively implement
+/// `serde::Deserialize`, failing on values that violate the
+/// refinement, and `arbitrary::Arbitrary`, producing only values that
+/// satisfy the refinement. They require the base type `T` to
+/// implement `Clone` and the corresponding features of `hax-lib`.
+///
 /// When extracted via hax, this is interpreted in the backend as a
 /// refinement type: the use of such a type yields static proof
 /// obligations.
 #[proc_macro_error]
 #[proc_macro_attribute]
-pub fn refinement_type(mut attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
+pub fn refinement_type(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
     let mut item = parse_macro_input!(item as syn::ItemStruct);
-
-    let syn::Fields::Unnamed(fields) = &item.fields else {
-        proc_macro_error::abort!(
-            item.generics.span(),
-            "Expected a newtype (a struct with one unnamed field), got one or more named field"
-        );
+    let args = parse_macro_input!(attr as RefinementTypeArgs);
+    let field = match newtype_field(&item) {
+        Ok(field) => field.clone(),
+        Err(err) => return err.into_compile_error().into(),
     };
-    let paren_token = fields.paren_token;
-    let fields = fields.unnamed.iter().collect::<Vec<_>>();
-    let [field] = &fields[..] else {
-        proc_macro_error::abort!(
-            item.generics.span(),
-            "Expected a newtype (a struct with one unnamed field), got {} fields",
-            fields.len()
-        );
+    let paren_token = match &item.fields {
+        syn::Fields::Unnamed(fields) => fields.paren_token,
+        _ => unreachable!(),
     };
-    if !matches!(field.vis, syn::Visibility::Inherited) {
-        proc_macro_error::abort!(field.vis.span(), "This field was expected to be private");
-    }
-
-    let no_debug_assert = {
-        let mut tokens = attr.clone().into_iter();
-        if let (Some(pm::TokenTree::Ident(ident)), Some(pm::TokenTree::Punct(comma))) =
-            (tokens.next(), tokens.next())
-        {
-            if ident.to_string() != "no_debug_runtime_check" {
-                proc_macro_error::abort!(ident.span(), "Expected 'no_debug_runtime_check'");
-            }
-            if comma.as_char() != ',' {
-                proc_macro_error::abort!(ident.span(), "Expected a comma");
-            }
-            attr = pm::TokenStream::from_iter(tokens);
-            true
-        } else {
-            false
-        }
+    let impls = match refinement_impls(&item, &args) {
+        Ok(impls) => impls,
+        Err(err) => return err.into_compile_error().into(),
     };
-
-    let ExprClosure1 {
-        arg: ret_binder,
-        body: phi,
-    } = parse_macro_input!(attr);
+    let RefinementTypeArgs {
+        ret_binder, phi, ..
+    } = args;
 
     let kind = FnDecorationKind::Ensures {
         ret_binder: ret_binder.clone(),
@@ -956,28 +1176,7 @@ pub fn refinement_type(mut attr: pm::TokenStream, item: pm::TokenStream) -> pm::
         output: syn::ReturnType::Type(parse_quote! {->}, Box::new(field.ty.clone())),
     };
     let ident = &item.ident;
-    let generics = &item.generics;
     let vis = item.vis.clone();
-    let generics_args: syn::punctuated::Punctuated<_, syn::token::Comma> = item
-        .generics
-        .params
-        .iter()
-        .map(|g| match g {
-            syn::GenericParam::Lifetime(p) => {
-                let i = &p.lifetime;
-                quote! { #i }
-            }
-            syn::GenericParam::Type(p) => {
-                let i = &p.ident;
-                quote! { #i }
-            }
-            syn::GenericParam::Const(p) => {
-                let i = &p.ident;
-                quote! { #i }
-            }
-        })
-        .collect();
-    let inner_ty = &field.ty;
     let (refinement_item, refinement_attr) = make_fn_decoration(phi.clone(), sig, kind, None, None);
     let module_ident = syn::Ident::new(
         &format!("hax__autogenerated_refinement__{}", ident),
@@ -985,8 +1184,6 @@ pub fn refinement_type(mut attr: pm::TokenStream, item: pm::TokenStream) -> pm::
     );
 
     item.vis = parse_quote! {pub};
-    let debug_assert =
-        no_debug_assert.then_some(quote! {::core::debug_assert!(Self::invariant(x.clone()));});
     let newtype_as_ref_attr = AttrPayload::NewtypeAsRefinement;
     quote! {
         #[allow(non_snake_case)]
@@ -1000,41 +1197,7 @@ pub fn refinement_type(mut attr: pm::TokenStream, item: pm::TokenStream) -> pm::
             #refinement_attr
             #item
 
-            #[::hax_lib::exclude]
-            impl #generics ::hax_lib::Refinement for #ident <#generics_args> {
-
-                type InnerType = #inner_ty;
-
-                fn new(x: Self::InnerType) -> Self {
-                    #debug_assert
-                    Self(x)
-                }
-                fn get(self) -> Self::InnerType {
-                    self.0
-                }
-                fn get_mut(&mut self) -> &mut Self::InnerType {
-                    &mut self.0
-                }
-                fn invariant(#ret_binder: Self::InnerType) -> bool {
-                    #phi
-                }
-            }
-
-            #[::hax_lib::exclude]
-            impl #generics ::std::ops::Deref for #ident <#generics_args> {
-                type Target = #inner_ty;
-                fn deref(&self) -> &Self::Target {
-                    &self.0
-                }
-            }
-
-            #[::hax_lib::exclude]
-            impl #generics ::hax_lib::RefineAs<#ident <#generics_args>> for #inner_ty {
-                fn into_checked(self) -> #ident <#generics_args> {
-                    use ::hax_lib::Refinement;
-                    #ident::new(self)
-                }
-            }
+            #impls
         }
         #vis use #module_ident::#ident;
 
@@ -0,0 +1,198 @@
+//! The Rust side of `refinement_type`: parsing of its arguments, and
+//! the implementations of `Refinement` and friends for a newtype.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::*;
+
+/// The arguments of `refinement_type`: a few options followed by the
+/// refining predicate, e.g. `serde, |x| x > 0`.
+pub struct RefinementTypeArgs {
+    /// The `no_debug_runtime_check` option.
+    pub no_debug_runtime_check: bool,
+    /// Implement `Deserialize`, checking the invariant.
+    pub serde: bool,
+    /// Implement `Arbitrary`, producing only values that satisfy the
+    /// invariant.
+    pub arbitrary: bool,
+    /// The predicate `|ret_binder| phi`.
+    pub ret_binder: Pat,
+    pub phi: Expr,
+}
+
+impl Parse for RefinementTypeArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut args = RefinementTypeArgs {
+            no_debug_runtime_check: false,
+            serde: false,
+            arbitrary: false,
+            ret_binder: parse_quote! {_},
+            phi: parse_quote! {true},
+        };
+        while input.peek(Ident) && input.peek2(Token![,]) {
+            let option: Ident = input.parse()?;
+            let flag = match option.to_string().as_str() {
+                "no_debug_runtime_check" => &mut args.no_debug_runtime_check,
+                "serde" => &mut args.serde,
+                "arbitrary" => &mut args.arbitrary,
+                _ => {
+                    return Err(Error::new(
+                        option.span(),
+                        "Expected 'no_debug_runtime_check', 'serde' or 'arbitrary'",
+                    ))
+                }
+            };
+            *flag = true;
+            let _: Token![,] = input.parse()?;
+        }
+        let predicate: ExprClosure = input.parse()?;
+        let [ret_binder] = &predicate.inputs.iter().collect::<Vec<_>>()[..] else {
+            return Err(Error::new(
+                predicate.inputs.span(),
+                "Expected exactly one argument",
+            ));
+        };
+        args.ret_binder = (*ret_binder).clone();
+        args.phi = *predicate.body;
+        Ok(args)
+    }
+}
+
+/// Expects `item` to be a newtype `struct RefinedT(T);` with a
+/// private field, and returns that field.
+pub fn newtype_field(item: &ItemStruct) -> Result<&Field> {
+    let Fields::Unnamed(fields) = &item.fields else {
+        return Err(Error::new(
+            item.generics.span(),
+            "Expected a newtype (a struct with one unnamed field), got one or more named field",
+        ));
+    };
+    let fields = fields.unnamed.iter().collect::<Vec<_>>();
+    let [field] = &fields[..] else {
+        return Err(Error::new(
+            item.generics.span(),
+            format!(
+                "Expected a newtype (a struct with one unnamed field), got {} fields",
+                fields.len()
+            ),
+        ));
+    };
+    if !matches!(field.vis, Visibility::Inherited) {
+        return Err(Error::new(
+            field.vis.span(),
+            "This field was expected to be private",
+        ));
+    }
+    Ok(field)
+}
+
+/// Implements `Refinement`, `Deref` and `RefineAs` for the newtype
+/// `item`, and optionally `Deserialize` and `Arbitrary`.
+pub fn refinement_impls(item: &ItemStruct, args: &RefinementTypeArgs) -> Result<TokenStream> {
+    for (enabled, available, option) in [
+        (args.serde, cfg!(feature = "serde"), "serde"),
+        (args.arbitrary, cfg!(feature = "arbitrary"), "arbitrary"),
+    ] {
+        if enabled && !available {
+            return Err(Error::new(
+                item.ident.span(),
+                format!("The `{option}` option requires the `{option}` feature of `hax-lib`"),
+            ));
+        }
+    }
+    let inner_ty = &newtype_field(item)?.ty;
+    let ident = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+    let RefinementTypeArgs {
+        ret_binder, phi, ..
+    } = args;
+    let debug_assert = (!args.no_debug_runtime_check)
+        .then_some(quote! {::core::debug_assert!(Self::invariant(x.clone()));});
+
+    // `Deserialize` and `Arbitrary` are parametrized by a lifetime.
+    let with_lifetime = |lifetime: Lifetime, bound: TokenStream| {
+        let mut generics = item.generics.clone();
+        generics.params.insert(0, parse_quote! {#lifetime});
+        generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote! {#inner_ty: #bound + ::core::clone::Clone});
+        generics
+    };
+    let serde = args.serde.then(|| {
+        let generics = with_lifetime(
+            parse_quote! {'de},
+            quote! {::hax_lib::__serde::Deserialize<'de>},
+        );
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        quote! {
+            #[::hax_lib::exclude]
+            impl #impl_generics ::hax_lib::__serde::Deserialize<'de> for #ident #ty_generics #where_clause {
+                fn deserialize<D: ::hax_lib::__serde::Deserializer<'de>>(
+                    deserializer: D,
+                ) -> ::core::result::Result<Self, D::Error> {
+                    ::hax_lib::__serde::deserialize_refined(deserializer)
+                }
+            }
+        }
+    });
+    let arbitrary = args.arbitrary.then(|| {
+        let generics = with_lifetime(
+            parse_quote! {'arbitrary},
+            quote! {::hax_lib::__arbitrary::Arbitrary<'arbitrary>},
+        );
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        quote! {
+            #[::hax_lib::exclude]
+            impl #impl_generics ::hax_lib::__arbitrary::Arbitrary<'arbitrary> for #ident #ty_generics #where_clause {
+                fn arbitrary(
+                    u: &mut ::hax_lib::__arbitrary::Unstructured<'arbitrary>,
+                ) -> ::hax_lib::__arbitrary::Result<Self> {
+                    ::hax_lib::__arbitrary::arbitrary_refined(u)
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        #[::hax_lib::exclude]
+        impl #impl_generics ::hax_lib::Refinement for #ident #ty_generics #where_clause {
+            type InnerType = #inner_ty;
+
+            fn new(x: Self::InnerType) -> Self {
+                #debug_assert
+                Self(x)
+            }
+            fn get(self) -> Self::InnerType {
+                self.0
+            }
+            fn get_mut(&mut self) -> &mut Self::InnerType {
+                &mut self.0
+            }
+            fn invariant(#ret_binder: Self::InnerType) -> bool {
+                #phi
+            }
+        }
+
+        #[::hax_lib::exclude]
+        impl #impl_generics ::std::ops::Deref for #ident #ty_generics #where_clause {
+            type Target = #inner_ty;
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        #[::hax_lib::exclude]
+        impl #impl_generics ::hax_lib::RefineAs<#ident #ty_generics> for #inner_ty #where_clause {
+            fn into_checked(self) -> #ident #ty_generics {
+                use ::hax_lib::Refinement;
+                #ident::new(self)
+            }
+        }
+
+        #serde
+        #arbitrary
+    })
+}
@@ -0,0 +1,344 @@
+//! Runtime checking of `requires` and `ensures` contracts, enabled by
+//! the `runtime-contracts` feature. Outside of hax, contracts are
+//! checked in debug builds: a precondition is checked on entry of the
+//! function, a postcondition on its result, once the body was
+//! evaluated. Checks are compiled out of release builds, so that they
+//! can use ghost functions.
+//!
+//! The body of a function with a postcondition is evaluated in a
+//! labeled block, in which `return`s and `?`s break out of the block.
+//! The inputs the postcondition reads are cloned before the body
+//! runs, since the body may consume or mutate them: a postcondition
+//! that reads an input that is not `Clone`, or that destructures a
+//! result that is not `Clone`, is not checked.
+//!
+//! Contracts that use backend-specific expressions (e.g. `fstar!`)
+//! cannot be evaluated: they are not checked. The quantifiers
+//! `forall` and `exists` always hold at runtime.
+//!
+//! The contract of a trait method is checked on its default body
+//! only: the methods of the implementations of the trait cannot see
+//! the contract, and are not checked.
+
+use crate::contracts::*;
+use crate::syn_ext::*;
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{visit::Visit, visit_mut::VisitMut, *};
+
+pub type FnLike = syn::ImplItemFn;
+
+/// Are contracts checkable on the function `sig`? The body of a
+/// `const` or `async` function cannot be rewritten.
+fn is_checkable(sig: &Signature) -> bool {
+    sig.constness.is_none() && sig.asyncness.is_none()
+}
+
+/// The names of the `&mut` inputs of a function.
+fn mut_ref_inputs(sig: &Signature) -> Vec<Ident> {
+    sig.inputs
+        .iter()
+        .filter_map(|input| match input {
+            FnArg::Receiver(Receiver {
+                reference: Some(_),
+                mutability: Some(_),
+                self_token,
+                ..
+            }) => Some(Ident::from(*self_token)),
+            FnArg::Typed(PatType { pat, ty, .. }) => match &**ty {
+                Type::Reference(TypeReference {
+                    mutability: Some(_),
+                    ..
+                }) => pat.expect_ident(),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// Checks the precondition `phi` on entry of `item`.
+pub fn add_precondition(phi: Expr, item: &mut FnLike) {
+    if !(is_checkable(&item.sig) && is_evaluable(&phi)) {
+        return;
+    }
+    let name = &item.sig.ident;
+    item.block.stmts.insert(
+        0,
+        parse_quote! {
+            #[cfg(debug_assertions)]
+            ::core::assert!(
+                #phi,
+                "precondition of `{}` violated: {}",
+                ::core::stringify!(#name),
+                ::core::stringify!(#phi),
+            );
+        },
+    );
+}
+
+/// The names of the inputs of a function that are not `&mut`
+/// references (see [`mut_ref_inputs`]).
+fn other_inputs(sig: &Signature) -> Vec<Ident> {
+    sig.inputs
+        .iter()
+        .filter_map(|input| match input {
+            FnArg::Receiver(Receiver {
+                reference: Some(_),
+                mutability: Some(_),
+                ..
+            }) => None,
+            FnArg::Receiver(Receiver { self_token, .. }) => Some(Ident::from(*self_token)),
+            FnArg::Typed(PatType { ty, .. })
+                if matches!(
+                    &**ty,
+                    Type::Reference(TypeReference {
+                        mutability: Some(_),
+                        ..
+                    })
+                ) =>
+            {
+                None
+            }
+            FnArg::Typed(PatType { pat, .. }) => match &**pat {
+                Pat::Ident(PatIdent { ident, .. }) => Some(ident.clone()),
+                _ => None,
+            },
+        })
+        .collect()
+}
+
+/// Rewrites the reads of `inputs` in a postcondition into reads of
+/// their snapshots, collecting the inputs that are read.
+struct RewriteInputs {
+    inputs: Vec<Ident>,
+    read: Vec<Ident>,
+}
+
+impl VisitMut for RewriteInputs {
+    fn visit_expr_mut(&mut self, e: &mut Expr) {
+        if let Some(input) = e.expect_ident().filter(|i| self.inputs.contains(i)) {
+            if !self.read.contains(&input) {
+                self.read.push(input.clone());
+            }
+            let snapshot = snapshot_ident(&input);
+            *e = parse_quote! {#snapshot};
+            return;
+        }
+        visit_mut::visit_expr_mut(self, e);
+    }
+}
+
+/// An owned copy of `value`, if it can be made, see
+/// `hax_lib::__runtime_contracts::Snapshot`.
+fn snapshot_of(value: TokenStream) -> TokenStream {
+    quote! {{
+        #[allow(unused_imports)]
+        use ::hax_lib::__runtime_contracts::{
+            SnapshotClone as _, SnapshotNone as _, SnapshotToOwned as _,
+        };
+        (&&&::hax_lib::__runtime_contracts::Snapshot(&#value)).snapshot()
+    }}
+}
+
+/// Rewrites the `return`s and `?`s of a function body evaluated in
+/// the block labeled `label` into `break`s out of this block. Nested
+/// closures, `async` blocks and items are left untouched.
+struct RewriteReturns {
+    label: Lifetime,
+    has_try: bool,
+}
+
+impl VisitMut for RewriteReturns {
+    fn visit_expr_mut(&mut self, e: &mut Expr) {
+        match e {
+            Expr::Closure(_) | Expr::Async(_) | Expr::Const(_) | Expr::TryBlock(_) => (),
+            Expr::Return(ExprReturn { expr, .. }) => {
+                let label = self.label.clone();
+                let expr = expr.take();
+                let mut expr = expr.map_or_else(|| parse_quote! {()}, |expr| *expr);
+                self.visit_expr_mut(&mut expr);
+                *e = parse_quote! {break #label (#expr)};
+            }
+            Expr::Try(ExprTry { expr, .. }) => {
+                self.has_try = true;
+                let label = self.label.clone();
+                let mut expr = (**expr).clone();
+                self.visit_expr_mut(&mut expr);
+                *e = parse_quote! {
+                    match ::hax_lib::__runtime_contracts::Branch::branch(#expr) {
+                        ::core::result::Result::Ok(value) => value,
+                        ::core::result::Result::Err(residual) => break #label (
+                            ::hax_lib::__runtime_contracts::FromResidual::from_residual(residual)
+                        ),
+                    }
+                };
+            }
+            _ => visit_mut::visit_expr_mut(self, e),
+        }
+    }
+
+    fn visit_item_mut(&mut self, _: &mut Item) {}
+}
+
+/// Checks the postcondition `phi` on the result `ret_binder` of
+/// `item`, once its body was evaluated.
+pub fn add_postcondition(ret_binder: Pat, phi: Expr, item: &mut FnLike) {
+    if !(is_checkable(&item.sig) && is_evaluable(&phi)) {
+        return;
+    }
+    let name = &item.sig.ident;
+    let message = quote! {
+        "postcondition of `{}` violated: {}",
+        ::core::stringify!(#name),
+        ::core::stringify!(#phi),
+    };
+    // The names bound by `ret_binder` shadow the inputs.
+    let bound = bound_idents(&ret_binder);
+    let unshadowed = |inputs: Vec<Ident>| inputs.into_iter().filter(|i| !bound.contains(i));
+    let mut rewrite = RewriteMutRefInputs {
+        inputs: unshadowed(mut_ref_inputs(&item.sig)).collect(),
+        snapshots: vec![],
+    };
+    let mut phi = phi;
+    rewrite.visit_expr_mut(&mut phi);
+    let mut_snapshots = rewrite.snapshots.iter().map(|input| {
+        let snapshot = snapshot_ident(input);
+        let value = snapshot_of(quote! {*#input});
+        quote! {
+            #[cfg(debug_assertions)]
+            let #snapshot = #value;
+        }
+    });
+    let mut snapshots: Vec<_> = rewrite.snapshots.iter().map(snapshot_ident).collect();
+    // The other inputs may be consumed by the body: they are
+    // snapshotted as well.
+    let mut rewrite = RewriteInputs {
+        inputs: unshadowed(other_inputs(&item.sig)).collect(),
+        read: vec![],
+    };
+    rewrite.visit_expr_mut(&mut phi);
+    let input_snapshots = rewrite.read.iter().map(|input| {
+        let snapshot = snapshot_ident(input);
+        let value = snapshot_of(quote! {#input});
+        quote! {
+            #[cfg(debug_assertions)]
+            let #snapshot = #value;
+        }
+    });
+    snapshots.extend(rewrite.read.iter().map(snapshot_ident));
+
+    let label = Lifetime::new("'hax_body", proc_macro2::Span::mixed_site());
+    let mut rewrite = RewriteReturns {
+        label: label.clone(),
+        has_try: false,
+    };
+    let mut block = item.block.clone();
+    rewrite.visit_block_mut(&mut block);
+    // The type of the result is needed to convert the residuals of
+    // `?`s, and for `return`s to be coerced to it.
+    let ty = match &item.sig.output {
+        ReturnType::Default => Some(quote! {: ()}),
+        ReturnType::Type(_, ty) if !contains_impl_trait(ty) => Some(quote! {: #ty}),
+        ReturnType::Type(..) if rewrite.has_try => return,
+        ReturnType::Type(..) => None,
+    };
+    let stmts = &block.stmts;
+    let body = quote! {#label: { #(#stmts)* }};
+
+    let result = Ident::new("result", proc_macro2::Span::mixed_site());
+    let (ret_binder, ret_snapshot) = match ret_binder.expect_ident() {
+        Some(ret) => (ret, None),
+        None => (result.clone(), Some((ret_binder, snapshot_of(quote! {#result})))),
+    };
+    let (patterns, values): (Vec<_>, Vec<_>) = snapshots
+        .iter()
+        .map(|snapshot| (quote! {#snapshot}, quote! {#snapshot}))
+        .chain(ret_snapshot.map(|(pat, value)| (quote! {#pat}, value)))
+        .unzip();
+    item.block = parse_quote! {{
+        #(#mut_snapshots)*
+        #(#input_snapshots)*
+        let #ret_binder #ty = #body;
+        #[cfg(debug_assertions)]
+        if let (#(::core::option::Option::Some(#patterns),)*) = (#(#values,)*) {
+            ::core::assert!(#phi, #message);
+        }
+        #ret_binder
+    }};
+}
+
+/// The names bound by the pattern `pat`.
+fn bound_idents(pat: &Pat) -> Vec<Ident> {
+    struct BoundIdents(Vec<Ident>);
+    impl<'a> Visit<'a> for BoundIdents {
+        fn visit_pat_ident(&mut self, pat: &'a PatIdent) {
+            self.0.push(pat.ident.clone());
+            visit::visit_pat_ident(self, pat);
+        }
+    }
+    let mut visitor = BoundIdents(vec![]);
+    visitor.visit_pat(pat);
+    visitor.0
+}
+
+fn contains_impl_trait(ty: &Type) -> bool {
+    struct FindImplTrait(bool);
+    impl<'a> Visit<'a> for FindImplTrait {
+        fn visit_type_impl_trait(&mut self, _: &'a TypeImplTrait) {
+            self.0 = true;
+        }
+    }
+    let mut visitor = FindImplTrait(false);
+    visitor.visit_type(ty);
+    visitor.0
+}
+
+/// Applies the `requires` and `ensures` attributes of a function of
+/// an `impl` block decorated with `hax_lib::attributes`, innermost
+/// first, as the compiler would have expanded them.
+pub fn apply_decorations(item: &mut FnLike) -> Result<()> {
+    for clause in clauses(&item.attrs)?.into_iter().rev() {
+        match clause {
+            Clause::Requires(phi) => add_precondition(phi, item),
+            Clause::Ensures(ret_binder, phi) => add_postcondition(ret_binder, phi, item),
+        }
+    }
+    Ok(())
+}
+
+/// Applies the `requires` and `ensures` attributes of a method of a
+/// trait decorated with `hax_lib::attributes` to its default body, if
+/// any.
+pub fn apply_trait_decorations(item: &mut TraitItemFn) -> Result<()> {
+    let Some(block) = item.default.take() else {
+        return Ok(());
+    };
+    let mut fun = FnLike {
+        attrs: item.attrs.clone(),
+        vis: Visibility::Inherited,
+        defaultness: None,
+        sig: item.sig.clone(),
+        block,
+    };
+    let result = apply_decorations(&mut fun);
+    item.default = Some(fun.block);
+    result
+}
+
+pub fn requires(attr: TokenStream, item: TokenStream) -> Result<TokenStream> {
+    let phi: Expr = parse2(attr)?;
+    let mut item: FnLike = parse2(item)?;
+    add_precondition(phi, &mut item);
+    Ok(item.into_token_stream())
+}
+
+pub fn ensures(attr: TokenStream, item: TokenStream) -> Result<TokenStream> {
+    let ExprClosure1 {
+        arg: ret_binder,
+        body: phi,
+    } = parse2(attr)?;
+    let mut item: FnLike = parse2(item)?;
+    add_postcondition(ret_binder, phi, &mut item);
+    Ok(item.into_token_stream())
+}
@@ -1,4 +1,5 @@
-use crate::prelude::*;
+use syn::spanned::Spanned;
+use syn::*;
 use syn::parse::*;
 use syn::punctuated::Punctuated;
 
@@ -0,0 +1,252 @@
+//! Property-based testing of contracts, enabled by the
+//! `test-contracts` feature: `#[test_contract]` generates a
+//! QuickCheck test that samples the inputs of a function, discards
+//! the ones that violate its `requires` clauses, and checks its
+//! `ensures` clauses on the result.
+
+use crate::contracts::*;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{spanned::Spanned, visit_mut::VisitMut, *};
+
+/// QuickCheck properties take at most 8 arguments.
+const MAX_INPUTS: usize = 8;
+
+/// An input of the tested function.
+struct Input {
+    name: Ident,
+    /// The type of the values sampled for this input.
+    sampled: Type,
+    /// The input is a `&T` or a `&mut T`.
+    reference: Option<Option<Token![mut]>>,
+    /// The input is a `&[T]` or a `&str`, sampled as a `Vec<T>` or a
+    /// `String`.
+    unsized_: bool,
+}
+
+impl Input {
+    fn new(arg: &FnArg) -> Result<Self> {
+        let FnArg::Typed(PatType { pat, ty, .. }) = arg else {
+            return Err(Error::new(
+                arg.span(),
+                "`test_contract` does not support methods",
+            ));
+        };
+        let Pat::Ident(PatIdent {
+            by_ref: None,
+            ident: name,
+            subpat: None,
+            ..
+        }) = &**pat
+        else {
+            return Err(Error::new(
+                pat.span(),
+                "`test_contract` expects inputs to be bound to variables",
+            ));
+        };
+        let name = name.clone();
+        Ok(match &**ty {
+            Type::Reference(TypeReference {
+                mutability, elem, ..
+            }) => {
+                let sampled = match &**elem {
+                    Type::Slice(TypeSlice { elem, .. }) => parse_quote! {Vec<#elem>},
+                    Type::Path(_) if is_unsized(elem) => parse_quote! {String},
+                    elem => elem.clone(),
+                };
+                Input {
+                    name,
+                    sampled,
+                    reference: Some(*mutability),
+                    unsized_: is_unsized(elem),
+                }
+            }
+            ty => Input {
+                name,
+                sampled: ty.clone(),
+                reference: None,
+                unsized_: false,
+            },
+        })
+    }
+
+    /// For a `&mut` input, the variable holding its final value.
+    fn final_value(&self) -> Option<Ident> {
+        matches!(self.reference, Some(Some(_))).then(|| format_ident!("__hax_final_{}", self.name))
+    }
+
+    /// Borrows the sampled value `value` as seen through a reference.
+    fn as_ref(&self, value: &Ident) -> TokenStream {
+        if self.unsized_ {
+            quote! {&#value[..]}
+        } else {
+            quote! {&#value}
+        }
+    }
+
+    /// The argument passed to the tested function.
+    fn arg(&self) -> TokenStream {
+        let name = &self.name;
+        match (self.reference, self.final_value()) {
+            (Some(_), Some(fin)) if self.unsized_ => quote! {&mut #fin[..]},
+            (Some(_), Some(fin)) => quote! {&mut #fin},
+            (Some(_), None) => self.as_ref(name),
+            (None, _) => quote! {::core::clone::Clone::clone(&#name)},
+        }
+    }
+
+    /// Binds the input as seen by a precondition.
+    fn bind_pre(&self) -> TokenStream {
+        let name = &self.name;
+        let value = match self.reference {
+            Some(_) => self.as_ref(name),
+            None => quote! {::core::clone::Clone::clone(&#name)},
+        };
+        quote! {let #name = #value;}
+    }
+
+    /// Binds the input as seen by a postcondition: a `&mut` input `x`
+    /// denotes its final value, and its initial value is bound to
+    /// [`snapshot_ident`] (see [`RewriteMutRefInputs`]).
+    fn bind_post(&self) -> TokenStream {
+        let name = &self.name;
+        match self.final_value() {
+            Some(fin) => {
+                let initial = snapshot_ident(name);
+                let fin = self.as_ref(&fin);
+                quote! {
+                    let #initial = ::core::clone::Clone::clone(&#name);
+                    let #name = #fin;
+                }
+            }
+            None => self.bind_pre(),
+        }
+    }
+}
+
+fn conjunction(phis: impl Iterator<Item = TokenStream>) -> TokenStream {
+    phis.reduce(|phi, psi| quote! {#phi && #psi})
+        .unwrap_or_else(|| quote! {true})
+}
+
+pub fn test_contract(item: TokenStream) -> Result<TokenStream> {
+    let item: ItemFn = parse2(item)?;
+    let sig = &item.sig;
+    if let Some(param) = sig
+        .generics
+        .params
+        .iter()
+        .find(|param| !matches!(param, GenericParam::Lifetime(_)))
+    {
+        return Err(Error::new(
+            param.span(),
+            "`test_contract` does not support generic functions",
+        ));
+    }
+    if sig.inputs.len() > MAX_INPUTS {
+        return Err(Error::new(
+            sig.inputs.span(),
+            format!("`test_contract` supports functions of at most {MAX_INPUTS} inputs"),
+        ));
+    }
+    let inputs = sig
+        .inputs
+        .iter()
+        .map(Input::new)
+        .collect::<Result<Vec<_>>>()?;
+    let clauses = clauses(&item.attrs)?;
+    if clauses.is_empty() {
+        return Err(Error::new(
+            sig.ident.span(),
+            "`test_contract` found no `requires` or `ensures` clause: it must be placed above them",
+        ));
+    }
+    // Sampling inputs that violate a precondition would make the test
+    // fail spuriously: the test is ignored if one cannot be evaluated.
+    let ignore = clauses
+        .iter()
+        .any(|clause| matches!(clause, Clause::Requires(phi) if !is_evaluable(phi)))
+        .then(|| {
+            let reason = format!(
+                "a precondition of `{}` uses backend-specific code and cannot be checked",
+                sig.ident
+            );
+            quote! {#[ignore = #reason]}
+        });
+    let clauses = clauses.iter().filter(|clause| match clause {
+        Clause::Requires(phi) | Clause::Ensures(_, phi) => is_evaluable(phi),
+    });
+
+    let name = &sig.ident;
+    let bindings: Vec<_> = inputs.iter().map(Input::bind_pre).collect();
+    let preconditions = conjunction(clauses.clone().filter_map(|clause| match clause {
+        Clause::Requires(phi) => Some(quote! {{#(#bindings)* #phi}}),
+        _ => None,
+    }));
+
+    let bindings: Vec<_> = inputs.iter().map(Input::bind_post).collect();
+    let mut_inputs: Vec<_> = inputs
+        .iter()
+        .filter(|input| input.final_value().is_some())
+        .map(|input| input.name.clone())
+        .collect();
+    let postconditions: Vec<_> = clauses
+        .filter_map(|clause| match clause {
+            Clause::Ensures(ret_binder, phi) => Some((ret_binder, phi)),
+            _ => None,
+        })
+        .collect();
+    let several_postconditions = postconditions.len() > 1;
+    let postconditions = conjunction(postconditions.into_iter().map(|(ret_binder, phi)| {
+        let mut phi = phi.clone();
+        RewriteMutRefInputs {
+            inputs: mut_inputs.clone(),
+            snapshots: vec![],
+        }
+        .visit_expr_mut(&mut phi);
+        let result = if several_postconditions {
+            quote! {::core::clone::Clone::clone(&result)}
+        } else {
+            quote! {result}
+        };
+        quote! {{
+            #(#bindings)*
+            let #ret_binder = #result;
+            #phi
+        }}
+    }));
+
+    let params = inputs.iter().map(|Input { name, sampled, .. }| {
+        quote! {#name: #sampled}
+    });
+    let sampled = inputs.iter().map(|input| &input.sampled);
+    let final_values = inputs.iter().filter_map(|input| {
+        let name = &input.name;
+        let fin = input.final_value()?;
+        Some(quote! {let mut #fin = ::core::clone::Clone::clone(&#name);})
+    });
+    let args = inputs.iter().map(Input::arg);
+    let test_name = format_ident!("test_contract_{}", name);
+    let testing = quote! {::hax_lib::__test_contracts};
+    Ok(quote! {
+        #item
+
+        #[cfg(test)]
+        #[test]
+        #ignore
+        fn #test_name() {
+            #[allow(unused_variables, clippy::redundant_clone)]
+            fn property(#(#params),*) -> #testing::TestResult {
+                if !(#preconditions) {
+                    return #testing::TestResult::discard();
+                }
+                #(#final_values)*
+                #[allow(clippy::let_unit_value)]
+                let result = #name(#(#args),*);
+                #testing::TestResult::from_bool(#postconditions)
+            }
+            #testing::QuickCheck::new()
+                .quickcheck(property as fn(#(#sampled),*) -> #testing::TestResult);
+        }
+    })
+}
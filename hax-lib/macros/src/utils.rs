@@ -31,6 +31,8 @@ pub enum FnDecorationKind {
     Requires,
     Ensures { ret_binder: Pat },
     Decreases,
+    /// The SMT patterns of a lemma
+    SmtPatterns,
 }
 
 impl ToString for FnDecorationKind {
@@ -39,6 +41,7 @@ impl ToString for FnDecorationKind {
             FnDecorationKind::Requires => "requires".to_string(),
             FnDecorationKind::Ensures { .. } => "ensures".to_string(),
             FnDecorationKind::Decreases { .. } => "decreases".to_string(),
+            FnDecorationKind::SmtPatterns => "smt_pat".to_string(),
         }
     }
 }
@@ -49,6 +52,7 @@ impl From<FnDecorationKind> for AssociationRole {
             FnDecorationKind::Requires => AssociationRole::Requires,
             FnDecorationKind::Ensures { .. } => AssociationRole::Ensures,
             FnDecorationKind::Decreases => AssociationRole::Decreases,
+            FnDecorationKind::SmtPatterns => AssociationRole::SmtPatterns,
         }
     }
 }
@@ -276,7 +280,7 @@ pub fn make_fn_decoration(
             if let Some(generics) = generics {
                 sig.generics = merge_generics(generics, sig.generics);
             }
-            sig.output = if let FnDecorationKind::Decreases = &kind {
+            sig.output = if let FnDecorationKind::Decreases | FnDecorationKind::SmtPatterns = &kind {
                 syn::parse_quote! { -> Box<dyn Any> }
             } else {
                 syn::parse_quote! { -> bool }
@@ -285,13 +289,13 @@ pub fn make_fn_decoration(
         };
         let uid_attr = AttrPayload::Uid(uid.clone());
         let late_skip = &AttrPayload::ItemStatus(ItemStatus::Included { late_skip: true });
-        let any_trait = if let FnDecorationKind::Decreases = &kind {
+        let any_trait = if let FnDecorationKind::Decreases | FnDecorationKind::SmtPatterns = &kind {
             phi = parse_quote! {Box::new(#phi)};
             quote! {#AttrHaxLang #[allow(unused)] trait Any {} impl<T> Any for T {}}
         } else {
             quote! {}
         };
-        let quantifiers = if let FnDecorationKind::Decreases = &kind {
+        let quantifiers = if let FnDecorationKind::Decreases | FnDecorationKind::SmtPatterns = &kind {
             None
         } else {
             Some(HaxQuantifiers)
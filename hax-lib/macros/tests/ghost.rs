@@ -0,0 +1,62 @@
+//! Ghost functions, `let`-bindings and statements, which are compiled
+//! in debug builds outside of hax: the workspace builds with
+//! `--cfg hax`, run these tests with `RUSTFLAGS= cargo test -p
+//! hax-lib-macros`.
+#![cfg(all(not(hax), debug_assertions))]
+
+#[hax_lib::ghost]
+fn spec_sum(xs: &[u32]) -> u64 {
+    xs.iter().map(|x| *x as u64).sum()
+}
+
+#[hax_lib::attributes]
+#[hax_lib::ensures(|result| result == spec_sum(xs))]
+fn sum(xs: &[u32]) -> u64 {
+    #[ghost]
+    let mut steps = 0;
+    let mut sum = 0;
+    for x in xs {
+        sum += *x as u64;
+        #[ghost]
+        steps += 1;
+    }
+    #[ghost]
+    assert_eq!(steps, xs.len());
+    sum
+}
+
+#[test]
+fn ghost_code_runs_in_debug_builds() {
+    assert_eq!(sum(&[1, 2, 3]), 6);
+    assert_eq!(spec_sum(&[4, 5]), 9);
+}
+
+#[hax_lib::attributes]
+fn shadowing(xs: &mut Vec<u32>) -> u32 {
+    #[ghost]
+    let mut count = 0;
+    {
+        // Shadows the ghost `count` in this block only.
+        let count = xs.len();
+        xs.push(count as u32);
+    }
+    #[ghost]
+    count += 1;
+    #[ghost]
+    let mut log = Vec::new();
+    for x in xs.iter() {
+        // A ghost receiver can be mutated, a non-ghost one can be read.
+        #[ghost]
+        log.push(xs.len() as u32 + x);
+    }
+    #[ghost]
+    assert_eq!((count, log.len()), (1, xs.len()));
+    xs.iter().sum()
+}
+
+#[test]
+fn ghost_variables_are_scoped() {
+    let mut xs = vec![3, 4];
+    assert_eq!(shadowing(&mut xs), 9);
+    assert_eq!(xs, vec![3, 4, 2]);
+}
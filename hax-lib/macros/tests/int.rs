@@ -0,0 +1,64 @@
+//! `hax_lib::int::Int` is executable outside of hax: run these tests
+//! with `RUSTFLAGS= cargo test -p hax-lib-macros`.
+#![cfg(not(hax))]
+
+use hax_lib::int::*;
+
+#[test]
+fn literals() {
+    assert_eq!(int!(42), 42u8.lift());
+    assert_eq!(int!(-42), (-42i8).lift());
+    let large = int!(340282366920938463463374607431768211456);
+    assert_eq!(large, u128::MAX.lift() + 1u8.lift());
+}
+
+#[test]
+fn arithmetic_follows_rust() {
+    let (x, y) = (int!(-7), int!(2));
+    assert_eq!(x / y, (-7i32 / 2).lift());
+    assert_eq!(x % y, (-7i32 % 2).lift());
+    assert_eq!(x.rem_euclid(y), (-7i32).rem_euclid(2).lift());
+    assert_eq!(-x, 7u8.lift());
+    assert_eq!(x.abs().pow(y), 49u8.lift());
+    assert_eq!(x & y, (-7i32 & 2).lift());
+    assert_eq!(x ^ y, (-7i32 ^ 2).lift());
+    assert_eq!(y.pow2(), 4u8.lift());
+}
+
+#[test]
+fn mixed_comparisons() {
+    let x = int!(300);
+    assert!(x > 255u8 && x < 301u16 && x != 0i8);
+    assert!(x.max(int!(1000)) == 1000u32);
+}
+
+#[test]
+fn formatting() {
+    let x = int!(-123456789012345678901234567890);
+    assert_eq!(format!("{x}"), "-123456789012345678901234567890");
+    assert_eq!(format!("{:?}", int!(5)), "5");
+    assert_eq!(x.to_i128(), -123456789012345678901234567890);
+}
+
+#[test]
+fn large_values() {
+    let max = u128::MAX.lift();
+    let min = i128::MIN.lift();
+    let (big, small) = (max * max, min * max);
+    assert!(small < min && min < max && max < big);
+    assert!(-big < small && small < -max);
+    assert_eq!(big / max, max);
+    assert_eq!((big - big).to_u8(), 0);
+    assert_eq!(min - 1u8.lift() + 1u8.lift(), min);
+    assert_eq!(
+        format!("{}", -max),
+        "-340282366920938463463374607431768211455"
+    );
+}
+
+#[test]
+#[should_panic(expected = "limited to 1024 bits")]
+fn values_are_limited_to_1024_bits() {
+    let x = 1024u16.lift().pow2();
+    assert!(x > 0u8);
+}
@@ -0,0 +1,229 @@
+//! Checks of `requires` and `ensures` generated with the
+//! `runtime-contracts` feature, which is enabled for the tests of this
+//! crate. Checks are compiled in debug builds only, outside of hax:
+//! the workspace builds with `--cfg hax`, run these tests with
+//! `RUSTFLAGS= cargo test -p hax-lib-macros`.
+#![cfg(all(not(hax), debug_assertions))]
+
+#[hax_lib::requires(x < 100)]
+#[hax_lib::ensures(|result| result == x * 2)]
+fn double(x: u32) -> u32 {
+    x + x
+}
+
+#[hax_lib::ensures(|result| result == x * 2)]
+fn wrong_double(x: u32) -> u32 {
+    x * 3
+}
+
+#[hax_lib::ensures(|result| result <= 10)]
+fn clamp(x: u32) -> u32 {
+    if x > 10 {
+        return 11;
+    }
+    x
+}
+
+#[hax_lib::ensures(|_| future(v).len() == v.len() + 1)]
+fn push_one(v: &mut Vec<u32>) {
+    v.push(1)
+}
+
+#[hax_lib::ensures(|_| future(v).len() == v.len() + 1)]
+fn push_none(v: &mut Vec<u32>) {
+    v.truncate(v.len())
+}
+
+#[hax_lib::requires(hax_lib::fstar!("False"))]
+#[hax_lib::ensures(|result| hax_lib::fstar_unsafe_expr!("False") && result)]
+fn backend_contract(x: bool) -> bool {
+    x
+}
+
+#[hax_lib::attributes]
+trait Step {
+    #[hax_lib::requires(n > 0)]
+    fn step(&self, n: u32) -> u32 {
+        n
+    }
+}
+
+impl Step for () {}
+
+struct Counter(u32);
+
+#[hax_lib::attributes]
+impl Counter {
+    #[hax_lib::requires(self.0 < 10)]
+    fn incr(&mut self) {
+        self.0 += 1
+    }
+}
+
+#[derive(Clone)]
+struct Cell {
+    x: u32,
+}
+
+#[hax_lib::attributes]
+impl Cell {
+    /// The result borrows from `self`.
+    #[hax_lib::ensures(|r| *r == self.x)]
+    fn get(&mut self) -> &mut u32 {
+        &mut self.x
+    }
+
+    #[hax_lib::ensures(|r| *r != self.x)]
+    fn wrong_get(&mut self) -> &mut u32 {
+        &mut self.x
+    }
+}
+
+/// The body consumes `v`, which the postcondition reads.
+#[hax_lib::ensures(|r| r.len() == v.len())]
+fn own(v: Vec<u32>) -> Vec<u32> {
+    v
+}
+
+#[hax_lib::ensures(|r| r.len() == v.len())]
+fn wrong_own(mut v: Vec<u32>) -> Vec<u32> {
+    v.pop();
+    v
+}
+
+struct NotClone(u32);
+
+#[hax_lib::ensures(|r| r.0 == n.0)]
+fn not_clone(n: NotClone) -> NotClone {
+    n
+}
+
+#[derive(Clone)]
+struct NC(u32);
+
+#[hax_lib::ensures(|NC(x)| x > 0)]
+fn positive(x: u32) -> NC {
+    NC(x)
+}
+
+struct Opaque(u32);
+
+/// The result is destructured, but is not `Clone`.
+#[hax_lib::ensures(|Opaque(x)| x > 0)]
+fn opaque(x: u32) -> Opaque {
+    Opaque(x)
+}
+
+#[hax_lib::ensures(|r| r.is_ok() || s.is_empty())]
+fn parse(s: &str) -> Result<u32, std::num::ParseIntError> {
+    let n: u32 = s.parse()?;
+    Ok(n + 1)
+}
+
+#[hax_lib::ensures(|r| format!("{r:?}").len() == 1)]
+fn boxed(b: bool) -> Box<dyn std::fmt::Debug> {
+    if b {
+        return Box::new(1u8);
+    }
+    Box::new("one")
+}
+
+#[test]
+fn satisfied_contracts_hold() {
+    assert_eq!(double(21), 42);
+    assert_eq!(clamp(3), 3);
+    push_one(&mut vec![]);
+    assert_eq!(().step(1), 1);
+    Counter(0).incr();
+}
+
+#[test]
+#[should_panic(expected = "precondition of `double` violated")]
+fn preconditions_are_checked() {
+    double(100);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of `wrong_double` violated")]
+fn postconditions_are_checked() {
+    wrong_double(1);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of `clamp` violated")]
+fn postconditions_are_checked_on_early_returns() {
+    clamp(20);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of `push_none` violated")]
+fn postconditions_see_initial_and_final_values() {
+    push_none(&mut vec![]);
+}
+
+#[test]
+fn backend_contracts_are_not_checked() {
+    assert!(backend_contract(true));
+}
+
+#[test]
+#[should_panic(expected = "precondition of `step` violated")]
+fn default_trait_methods_are_checked() {
+    ().step(0);
+}
+
+#[test]
+#[should_panic(expected = "precondition of `incr` violated")]
+fn impl_methods_are_checked() {
+    Counter(10).incr();
+}
+
+#[test]
+fn results_borrowing_inputs_are_checked() {
+    let mut cell = Cell { x: 3 };
+    *cell.get() += 1;
+    assert_eq!(cell.x, 4);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of `wrong_get` violated")]
+fn postconditions_on_borrowing_results_are_checked() {
+    Cell { x: 3 }.wrong_get();
+}
+
+#[test]
+fn consumed_inputs_are_snapshotted() {
+    assert_eq!(own(vec![1, 2]), vec![1, 2]);
+    assert_eq!(not_clone(NotClone(1)).0, 1);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of `wrong_own` violated")]
+fn postconditions_on_consumed_inputs_are_checked() {
+    wrong_own(vec![1]);
+}
+
+#[test]
+fn destructured_results_need_not_be_clone() {
+    assert_eq!(opaque(0).0, 0);
+    assert_eq!(positive(1).0, 1);
+}
+
+#[test]
+#[should_panic(expected = "postcondition of `positive` violated")]
+fn destructured_clone_results_are_checked() {
+    positive(0);
+}
+
+#[test]
+fn question_marks_are_checked() {
+    assert_eq!(parse("1"), Ok(2));
+    assert!(parse("").is_err());
+    assert_eq!(format!("{:?}", boxed(true)), "1");
+}
+
+#[test]
+#[should_panic(expected = "postcondition of `parse` violated")]
+fn question_marks_do_not_skip_checks() {
+    let _ = parse("x");
+}
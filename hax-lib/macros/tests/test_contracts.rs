@@ -0,0 +1,67 @@
+//! Tests generated by `test_contract`, enabled with the
+//! `test-contracts` feature for the tests of this crate. Like the ones
+//! of `runtime_contracts.rs`, they only exist outside of hax: run them
+//! with `RUSTFLAGS= cargo test -p hax-lib-macros`.
+#![cfg(not(hax))]
+
+#[hax_lib::test_contract]
+#[hax_lib::requires(x < 1000)]
+#[hax_lib::ensures(|result| result == x * 2)]
+fn twice(x: u64) -> u64 {
+    x + x
+}
+
+/// Panics on the inputs that violate its precondition: they must be
+/// discarded.
+#[hax_lib::test_contract]
+#[hax_lib::requires(y != 0 && x >= y)]
+#[hax_lib::ensures(|result| result >= 1)]
+fn quotient(x: u32, y: u32) -> u32 {
+    assert!(y != 0 && x >= y);
+    x / y
+}
+
+#[hax_lib::test_contract]
+#[hax_lib::ensures(|result| result <= s.len())]
+#[hax_lib::ensures(|result| s.is_empty() || s[result.min(s.len() - 1)] >= s[0])]
+fn index_of_max(s: &[u8]) -> usize {
+    (0..s.len()).fold(0, |max, i| if s[i] > s[max] { i } else { max })
+}
+
+#[hax_lib::test_contract]
+#[hax_lib::ensures(|result| result == s.chars().count())]
+fn count_chars(s: &str) -> usize {
+    s.chars().count()
+}
+
+#[hax_lib::test_contract]
+#[hax_lib::requires(v.len() < 100)]
+#[hax_lib::ensures(|_| future(v).len() == v.len() + 1 && future(v)[v.len()] == x)]
+fn push(v: &mut Vec<u8>, x: u8) {
+    v.push(x)
+}
+
+#[hax_lib::test_contract]
+#[hax_lib::requires(hax_lib::fstar!("False"))]
+#[hax_lib::ensures(|result| result == x)]
+fn backend_precondition(x: bool) -> bool {
+    x
+}
+
+#[test]
+fn contract_tests_are_generated() {
+    test_contract_twice();
+    test_contract_push();
+}
+
+#[test]
+#[should_panic(expected = "TEST FAILED")]
+#[allow(unnameable_test_items)]
+fn violated_contracts_fail() {
+    #[hax_lib::test_contract]
+    #[hax_lib::ensures(|result| result == x.wrapping_mul(2))]
+    fn wrong_twice(x: u8) -> u8 {
+        x.wrapping_mul(3)
+    }
+    test_contract_wrong_twice();
+}
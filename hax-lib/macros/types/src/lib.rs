@@ -62,6 +62,8 @@ pub enum AssociationRole {
     ProcessWrite,
     ProcessInit,
     ProtocolMessages,
+    /// The SMT patterns of a lemma
+    SmtPatterns,
 }
 
 /// Where should a item quote appear?
@@ -122,6 +124,9 @@ pub enum AttrPayload {
     NewtypeAsRefinement,
     /// Mark an item as a lemma statement to prove in the backend
     Lemma,
+    /// Mark an item as ghost: it exists only for verification, and is
+    /// not extracted as executable code
+    Ghost,
     Language,
     ProcessRead,
     ProcessWrite,
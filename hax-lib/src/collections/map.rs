@@ -0,0 +1,76 @@
+use super::Set;
+use alloc::collections::BTreeMap;
+
+/// A finite map from keys to values, for specifications. Operations
+/// on maps consume them and return new ones. Like [`Set`]s, maps
+/// cannot be compared with `==`.
+#[derive(Clone, Debug)]
+pub struct Map<K, V>(BTreeMap<K, V>);
+
+impl<K: Ord, V> Map<K, V> {
+    /// The empty map
+    pub fn new() -> Self {
+        Map(BTreeMap::new())
+    }
+
+    /// The value associated with `key`, if any
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.0.get(key)
+    }
+
+    /// Is a value associated with `key`?
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.0.contains_key(key)
+    }
+
+    /// Associates `value` with `key`, replacing the value previously
+    /// associated with `key`, if any
+    pub fn insert(mut self, key: K, value: V) -> Self {
+        self.0.insert(key, value);
+        self
+    }
+
+    /// Removes the value associated with `key`, if any
+    pub fn remove(mut self, key: &K) -> Self {
+        self.0.remove(key);
+        self
+    }
+
+    /// The set of the keys with an associated value
+    pub fn keys(&self) -> Set<K>
+    where
+        K: Clone,
+    {
+        self.0.keys().cloned().collect()
+    }
+}
+
+impl<K: Ord, V> Default for Map<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for Map<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Map(iter.into_iter().collect())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Ord, V, S> crate::int::Abstraction for std::collections::HashMap<K, V, S> {
+    type AbstractType = Map<K, V>;
+    fn lift(self) -> Self::AbstractType {
+        self.into_iter().collect()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Ord + Clone, V: Clone, S> crate::int::Abstraction for &std::collections::HashMap<K, V, S> {
+    type AbstractType = Map<K, V>;
+    fn lift(self) -> Self::AbstractType {
+        self.iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+}
@@ -0,0 +1,18 @@
+//! Collection types for writing specifications: sequences ([`Seq`]),
+//! sets ([`Set`]) and maps ([`Map`]). They are extracted to the
+//! sequences, sets and maps of the backends' libraries, and are
+//! executable in Rust, so that specifications using them can be
+//! tested.
+//!
+//! Concrete collections are mapped to those types with
+//! [`Abstraction`](crate::int::Abstraction): vectors and slices are
+//! lifted to [`Seq`]s, and `HashMap`s (with the `std` feature) to
+//! [`Map`]s.
+
+mod map;
+mod seq;
+mod set;
+
+pub use map::Map;
+pub use seq::Seq;
+pub use set::Set;
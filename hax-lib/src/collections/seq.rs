@@ -0,0 +1,118 @@
+use crate::int::Abstraction;
+use alloc::vec::Vec;
+
+/// A finite sequence of values, for specifications. Operations on
+/// sequences consume them and return new ones.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Seq<T>(Vec<T>);
+
+impl<T> Seq<T> {
+    /// The empty sequence
+    pub fn new() -> Self {
+        Seq(Vec::new())
+    }
+
+    /// The number of elements of the sequence
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Is the sequence empty?
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The element at index `i`. Panics if `i` is out of bounds.
+    pub fn at(&self, i: usize) -> &T {
+        &self.0[i]
+    }
+
+    /// Appends `x` at the end of the sequence
+    pub fn push(mut self, x: T) -> Self {
+        self.0.push(x);
+        self
+    }
+
+    /// The concatenation of `self` and `other`
+    pub fn concat(mut self, mut other: Self) -> Self {
+        self.0.append(&mut other.0);
+        self
+    }
+
+    /// The subsequence from index `lo` (included) to `hi` (excluded).
+    /// Panics if the bounds are invalid.
+    pub fn slice(mut self, lo: usize, hi: usize) -> Self {
+        core::assert!(lo <= hi && hi <= self.len(), "`Seq::slice`: invalid bounds");
+        self.0.truncate(hi);
+        self.0.drain(..lo);
+        self
+    }
+
+    /// Replaces the element at index `i` with `x`. Panics if `i` is
+    /// out of bounds.
+    pub fn update(mut self, i: usize, x: T) -> Self {
+        self.0[i] = x;
+        self
+    }
+
+    /// Does `x` occur in the sequence?
+    pub fn contains(&self, x: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.0.contains(x)
+    }
+}
+
+impl<T> Default for Seq<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<T> for Seq<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Seq(iter.into_iter().collect())
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Seq<T> {
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<'a, T> crate::Domain for &'a Seq<T> {
+    type Element = &'a T;
+}
+
+impl<T> Abstraction for Vec<T> {
+    type AbstractType = Seq<T>;
+    fn lift(self) -> Self::AbstractType {
+        Seq(self)
+    }
+}
+
+impl<T: Clone> Abstraction for &Vec<T> {
+    type AbstractType = Seq<T>;
+    fn lift(self) -> Self::AbstractType {
+        Seq(self.clone())
+    }
+}
+
+impl<T: Clone> Abstraction for &[T] {
+    type AbstractType = Seq<T>;
+    fn lift(self) -> Self::AbstractType {
+        Seq(self.to_vec())
+    }
+}
+
+impl<T: Clone, const N: usize> Abstraction for &[T; N] {
+    type AbstractType = Seq<T>;
+    fn lift(self) -> Self::AbstractType {
+        Seq(self.to_vec())
+    }
+}
@@ -0,0 +1,69 @@
+use alloc::collections::BTreeSet;
+
+/// A finite set of values, for specifications. Operations on sets
+/// consume them and return new ones. Sets cannot be compared with
+/// `==`: the equality of the finite sets of the backends is not
+/// computable.
+#[derive(Clone, Debug)]
+pub struct Set<T>(BTreeSet<T>);
+
+impl<T: Ord> Set<T> {
+    /// The empty set
+    pub fn new() -> Self {
+        Set(BTreeSet::new())
+    }
+
+    /// Is `x` a member of the set?
+    pub fn contains(&self, x: &T) -> bool {
+        self.0.contains(x)
+    }
+
+    /// Adds `x` to the set
+    pub fn insert(mut self, x: T) -> Self {
+        self.0.insert(x);
+        self
+    }
+
+    /// Removes `x` from the set
+    pub fn remove(mut self, x: &T) -> Self {
+        self.0.remove(x);
+        self
+    }
+
+    /// The union of `self` and `other`
+    pub fn union(mut self, other: Self) -> Self {
+        self.0.extend(other.0);
+        self
+    }
+
+    /// The intersection of `self` and `other`
+    pub fn intersection(mut self, other: Self) -> Self {
+        self.0.retain(|x| other.0.contains(x));
+        self
+    }
+}
+
+impl<T: Ord> Default for Set<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for Set<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Set(iter.into_iter().collect())
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Set<T> {
+    type Item = &'a T;
+    type IntoIter = alloc::collections::btree_set::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<'a, T> crate::Domain for &'a Set<T> {
+    type Element = &'a T;
+}
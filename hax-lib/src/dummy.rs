@@ -1,6 +1,94 @@
 #[cfg(feature = "macros")]
 pub use crate::proc_macros::*;
 
+#[cfg(feature = "test-contracts")]
+#[doc(hidden)]
+pub mod __test_contracts {
+    pub use quickcheck::{QuickCheck, TestResult};
+}
+
+#[cfg(feature = "runtime-contracts")]
+#[doc(hidden)]
+pub mod __runtime_contracts {
+    //! Helpers of the checks generated by the `runtime-contracts`
+    //! feature.
+
+    use alloc::borrow::ToOwned;
+    use core::convert::Infallible;
+
+    /// Clones the value it points to if it can, using autoref-based
+    /// specialization: `(&&&Snapshot(&x)).snapshot()` is `Some` clone
+    /// of `x` when `x` is `Clone`, `Some` owned copy of `x` when `x`
+    /// is a `ToOwned` slice or string, and `None` otherwise.
+    pub struct Snapshot<'a, T: ?Sized>(pub &'a T);
+
+    pub trait SnapshotClone<T> {
+        fn snapshot(&self) -> Option<T>;
+    }
+    impl<T: Clone> SnapshotClone<T> for &&Snapshot<'_, T> {
+        fn snapshot(&self) -> Option<T> {
+            Some(self.0.clone())
+        }
+    }
+
+    pub trait SnapshotToOwned<T: ?Sized + ToOwned> {
+        fn snapshot(&self) -> Option<T::Owned>;
+    }
+    impl<T: ?Sized + ToOwned> SnapshotToOwned<T> for &Snapshot<'_, T> {
+        fn snapshot(&self) -> Option<T::Owned> {
+            Some(self.0.to_owned())
+        }
+    }
+
+    pub trait SnapshotNone<T> {
+        fn snapshot(&self) -> Option<T>;
+    }
+    impl<T> SnapshotNone<T> for Snapshot<'_, T> {
+        fn snapshot(&self) -> Option<T> {
+            None
+        }
+    }
+
+    /// The `?` operator, on stable Rust: a checked body is evaluated
+    /// in a labeled block, which `?` would return out of.
+    pub trait Branch {
+        type Output;
+        type Residual;
+        fn branch(self) -> Result<Self::Output, Self::Residual>;
+    }
+    impl<T, E> Branch for Result<T, E> {
+        type Output = T;
+        type Residual = Result<Infallible, E>;
+        fn branch(self) -> Result<T, Result<Infallible, E>> {
+            self.map_err(Err)
+        }
+    }
+    impl<T> Branch for Option<T> {
+        type Output = T;
+        type Residual = Option<Infallible>;
+        fn branch(self) -> Result<T, Option<Infallible>> {
+            self.ok_or(None)
+        }
+    }
+
+    pub trait FromResidual<R> {
+        fn from_residual(residual: R) -> Self;
+    }
+    impl<T, E, F: From<E>> FromResidual<Result<Infallible, E>> for Result<T, F> {
+        fn from_residual(residual: Result<Infallible, E>) -> Self {
+            match residual {
+                Ok(never) => match never {},
+                Err(e) => Err(From::from(e)),
+            }
+        }
+    }
+    impl<T> FromResidual<Option<Infallible>> for Option<T> {
+        fn from_residual(_: Option<Infallible>) -> Self {
+            None
+        }
+    }
+}
+
 #[macro_export]
 macro_rules! debug_assert {
     ($($arg:tt)*) => {
@@ -30,6 +118,35 @@ pub fn exists<T>(_f: impl Fn(T) -> bool) -> bool {
     true
 }
 
+pub fn with_triggers<T>(_triggers: impl Fn() -> T, formula: bool) -> bool {
+    formula
+}
+
+pub trait Domain: IntoIterator<Item = <Self as Domain>::Element> {
+    type Element;
+}
+
+impl<T> Domain for core::ops::Range<T>
+where
+    core::ops::Range<T>: Iterator<Item = T>,
+{
+    type Element = T;
+}
+impl<'a, T> Domain for &'a [T] {
+    type Element = &'a T;
+}
+impl<'a, T, const N: usize> Domain for &'a [T; N] {
+    type Element = &'a T;
+}
+
+pub fn forall_in<D: Domain>(domain: D, f: impl Fn(D::Element) -> bool) -> bool {
+    !cfg!(debug_assertions) || domain.into_iter().all(f)
+}
+
+pub fn exists_in<D: Domain>(domain: D, f: impl Fn(D::Element) -> bool) -> bool {
+    !cfg!(debug_assertions) || domain.into_iter().any(f)
+}
+
 pub fn implies(lhs: bool, rhs: impl Fn() -> bool) -> bool {
     !lhs || rhs()
 }
@@ -45,6 +162,17 @@ pub fn inline_unsafe<T>(_: &str) -> T {
 #[doc(hidden)]
 pub fn _internal_loop_invariant<T, P: FnOnce(T) -> bool>(_: P) {}
 
+#[doc(hidden)]
+pub fn _internal_while_loop_invariant(_: bool) {}
+
+#[doc(hidden)]
+pub fn _internal_loop_decreases(_: crate::int::Int) {}
+
+#[doc(hidden)]
+pub fn _internal_ghost<T>(x: T) -> T {
+    x
+}
+
 pub trait Refinement {
     type InnerType;
     fn new(x: Self::InnerType) -> Self;
@@ -57,52 +185,9 @@ pub trait RefineAs<RefinedType> {
     fn into_checked(self) -> RefinedType;
 }
 
-pub mod int {
-    #[derive(Clone, Copy)]
-    pub struct Int(pub u8);
-
-    impl Int {
-        pub fn new(x: impl Into<u8>) -> Self {
-            Int(x.into())
-        }
-        pub fn get(self) -> u8 {
-            self.0
-        }
-    }
-
-    impl Int {}
-    impl Int {}
-    impl Int {}
-    impl Int {}
-
-    impl Int {
-        pub fn pow2(self) -> Self {
-            self
-        }
-        pub fn _unsafe_from_str(_s: &str) -> Self {
-            Int(0)
-        }
-    }
-
-    pub trait Abstraction {
-        type AbstractType;
-        fn lift(self) -> Self::AbstractType;
-    }
-
-    pub trait Concretization<T> {
-        fn concretize(self) -> T;
-    }
-
-    impl Abstraction for u8 {
-        type AbstractType = Int;
-        fn lift(self) -> Self::AbstractType {
-            Int(0)
-        }
-    }
-
-    impl Concretization<u32> for Int {
-        fn concretize(self) -> u32 {
-            0
-        }
-    }
-}
+/// Mathematical integers need an allocator for big values.
+#[cfg(feature = "alloc")]
+pub mod int;
+#[cfg(not(feature = "alloc"))]
+#[path = "int/placeholder.rs"]
+pub mod int;
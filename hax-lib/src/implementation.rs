@@ -113,6 +113,65 @@ pub fn exists<T>(_f: impl Fn(T) -> bool) -> bool {
     true
 }
 
+/// Attaches triggers to the body of a [`forall`], [`exists`],
+/// [`forall_in`] or [`exists_in`] quantifier: the SMT solver
+/// instantiates the quantifier only for the terms that match
+/// `triggers`. `triggers` returns a term, or a tuple of terms that
+/// must all match: it is never called. In Rust, this is `formula`.
+///
+/// # Example:
+///
+/// The Rust expression `forall(|i: usize| with_triggers(|| f(i), phi(i)))`
+/// corresponds to `∀ (i: usize) {:pattern f(i)}, phi(i)`.
+pub fn with_triggers<T>(_triggers: impl Fn() -> T, formula: bool) -> bool {
+    formula
+}
+
+/// A finite domain of values, over which the bounded quantifiers
+/// [`forall_in`] and [`exists_in`] range: integer ranges, slices and
+/// arrays. `Element` is the type of the elements of the domain, which
+/// the backends model as the `Element` of their `Domain` class.
+pub trait Domain: IntoIterator<Item = <Self as Domain>::Element> {
+    type Element;
+}
+
+impl<T> Domain for core::ops::Range<T>
+where
+    core::ops::Range<T>: Iterator<Item = T>,
+{
+    type Element = T;
+}
+impl<'a, T> Domain for &'a [T] {
+    type Element = &'a T;
+}
+impl<'a, T, const N: usize> Domain for &'a [T; N] {
+    type Element = &'a T;
+}
+
+/// The bounded universal quantifier. In Rust, in debug builds, this
+/// checks `f` on every element of `domain`; in release builds, this
+/// is always true.
+///
+/// # Example:
+///
+/// The Rust expression `forall_in(domain, |x: T| phi(x))` corresponds
+/// to `∀ (x: T), x ∈ domain ⟹ phi(x)`.
+pub fn forall_in<D: Domain>(domain: D, f: impl Fn(D::Element) -> bool) -> bool {
+    !cfg!(debug_assertions) || domain.into_iter().all(f)
+}
+
+/// The bounded existential quantifier. In Rust, in debug builds, this
+/// looks for an element of `domain` satisfying `f`; in release
+/// builds, this is always true.
+///
+/// # Example:
+///
+/// The Rust expression `exists_in(domain, |x: T| phi(x))` corresponds
+/// to `∃ (x: T), x ∈ domain ∧ phi(x)`.
+pub fn exists_in<D: Domain>(domain: D, f: impl Fn(D::Element) -> bool) -> bool {
+    !cfg!(debug_assertions) || domain.into_iter().any(f)
+}
+
 /// The logical implication `a ==> b`.
 pub fn implies(lhs: bool, rhs: impl Fn() -> bool) -> bool {
     !lhs || rhs()
@@ -132,6 +191,23 @@ pub fn inline_unsafe<T>(_: &str) -> T {
 #[doc(hidden)]
 pub fn _internal_loop_invariant<T, P: FnOnce(T) -> bool>(_: P) {}
 
+/// A dummy function that holds the invariant of a `while` or `loop`
+/// loop.
+#[doc(hidden)]
+pub fn _internal_while_loop_invariant(_: bool) {}
+
+/// A dummy function that holds the termination measure of a `while`
+/// or `loop` loop.
+#[doc(hidden)]
+pub fn _internal_loop_decreases(_: crate::int::Int) {}
+
+/// A dummy function that marks the values of ghost `let`-bindings and
+/// ghost statements.
+#[doc(hidden)]
+pub fn _internal_ghost<T>(x: T) -> T {
+    x
+}
+
 /// A type that implements `Refinement` should be a newtype for a
 /// type `T`. The field holding the value of type `T` should be
 /// private, and `Refinement` should be the only interface to the
@@ -1,46 +1,116 @@
-//! This module provides an approximation of `BigInt` which is
-//! copiable, via an big array of `u8` of an fixed arbitrary size
-//! `BYTES`.
-//! Its interface provides bridges to `num_bigint::BigInt`.
-
-/// Maximal number of bytes stored in our copiable `BigInt`s.
-const BYTES: usize = 1024;
-#[derive(Copy, Clone)]
-pub(super) struct BigInt {
-    sign: num_bigint::Sign,
-    data: [u8; BYTES],
+//! This module provides a copiable `BigInt`. Integers that fit in an
+//! `i128` are stored inline, which makes arithmetic on them cheap:
+//! this is the common case in specifications. Bigger integers are
+//! stored inline as well, as the magnitude of a
+//! [`num_bigint::BigInt`] in an array of a fixed arbitrary size
+//! [`LIMBS`], so that [`BigInt`] can stay `Copy` without allocating.
+//!
+//! The representation is canonical: an integer is stored as a
+//! [`BigInt::Small`] if and only if it fits in an `i128`.
+
+use num_bigint::Sign;
+use num_traits::cast::ToPrimitive;
+
+/// Maximal number of 32-bit digits stored in our copiable `BigInt`s:
+/// integers are limited to 1024 bits.
+const LIMBS: usize = 32;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub(super) enum BigInt {
+    Small(i128),
+    Large {
+        negative: bool,
+        /// The digits of the magnitude, least significant first,
+        /// padded with zeros.
+        digits: [u32; LIMBS],
+    },
 }
+
 impl BigInt {
     /// Construct a [`BigInt`] from a [`num_bigint::BigInt`]. This
     /// operation panics when the provided [`num_bigint::BigInt`]
-    /// has more than [`BYTES`] bytes.
-    pub(super) fn new(i: &num_bigint::BigInt) -> Self {
-        let (sign, data) = i.to_bytes_be();
-        let data = data
-            .try_into()
-            .expect("`copiable_bigint::BigInt::new`: too big, please consider increasing `BYTES`");
-        BigInt { sign, data }
+    /// has more than [`LIMBS`] 32-bit digits.
+    pub(super) fn new(i: num_bigint::BigInt) -> Self {
+        if let Some(i) = i.to_i128() {
+            return BigInt::Small(i);
+        }
+        let (sign, magnitude) = i.to_u32_digits();
+        let mut digits = [0; LIMBS];
+        digits
+            .get_mut(..magnitude.len())
+            .expect("`hax_lib::int::Int`: integers are limited to 1024 bits")
+            .copy_from_slice(&magnitude);
+        BigInt::Large {
+            negative: sign == Sign::Minus,
+            digits,
+        }
     }
 
-    /// Constructs a [`num_bigint::BigInt`] out of a [`BigInt`].
-    pub(super) fn get(self) -> num_bigint::BigInt {
-        num_bigint::BigInt::from_bytes_be(self.sign, &self.data)
+    /// The value of a [`BigInt`] that fits in an `i128`.
+    pub(super) fn small(&self) -> Option<i128> {
+        match self {
+            BigInt::Small(i) => Some(*i),
+            BigInt::Large { .. } => None,
+        }
     }
-}
 
-impl core::cmp::PartialEq for BigInt {
-    fn eq(&self, other: &Self) -> bool {
-        self.get() == other.get()
+    /// Constructs a [`num_bigint::BigInt`] out of a [`BigInt`].
+    pub(super) fn get(&self) -> num_bigint::BigInt {
+        match self {
+            BigInt::Small(i) => (*i).into(),
+            BigInt::Large { negative, digits } => {
+                let sign = if *negative { Sign::Minus } else { Sign::Plus };
+                num_bigint::BigInt::from_slice(sign, digits)
+            }
+        }
     }
 }
-impl core::cmp::Eq for BigInt {}
+
 impl core::cmp::Ord for BigInt {
     fn cmp(&self, other: &Self) -> core::cmp::Ordering {
-        self.get().cmp(&other.get())
+        use core::cmp::Ordering;
+        match (self, other) {
+            (BigInt::Small(x), BigInt::Small(y)) => x.cmp(y),
+            (
+                BigInt::Large {
+                    negative: x_negative,
+                    digits: x,
+                },
+                BigInt::Large {
+                    negative: y_negative,
+                    digits: y,
+                },
+            ) => match (x_negative, y_negative) {
+                (false, true) => Ordering::Greater,
+                (true, false) => Ordering::Less,
+                // Magnitudes compare from their most significant digit.
+                (false, false) => x.iter().rev().cmp(y.iter().rev()),
+                (true, true) => y.iter().rev().cmp(x.iter().rev()),
+            },
+            // A large integer is either bigger or smaller than any
+            // small one, depending on its sign.
+            (BigInt::Small(_), BigInt::Large { negative, .. }) => match negative {
+                true => Ordering::Greater,
+                false => Ordering::Less,
+            },
+            (BigInt::Large { negative, .. }, BigInt::Small(_)) => match negative {
+                true => Ordering::Less,
+                false => Ordering::Greater,
+            },
+        }
     }
 }
 impl core::cmp::PartialOrd for BigInt {
     fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
-        self.get().partial_cmp(&other.get())
+        Some(self.cmp(other))
+    }
+}
+
+impl core::fmt::Display for BigInt {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BigInt::Small(i) => core::fmt::Display::fmt(i, f),
+            BigInt::Large { .. } => core::fmt::Display::fmt(&self.get(), f),
+        }
     }
 }
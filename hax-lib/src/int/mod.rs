@@ -10,23 +10,48 @@ pub use hax_lib_macros::int;
 /// Mathematical integers for writting specifications. Mathematical
 /// integers are unbounded and arithmetic operation on them never over
 /// or underflow.
+///
+/// Values that fit in an `i128` are represented inline, and
+/// arithmetic on them is cheap. So that `Int` stays `Copy`, bigger
+/// values are represented inline as well and are limited to 1024
+/// bits: operations panic on bigger results.
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub struct Int(BigInt);
 
 impl Int {
     fn new(x: impl Into<num_bigint::BigInt>) -> Self {
-        Int(BigInt::new(&x.into()))
+        Int(BigInt::new(x.into()))
     }
-    fn get(self) -> num_bigint::BigInt {
+    fn get(&self) -> num_bigint::BigInt {
         self.0.get()
     }
+    fn small(x: i128) -> Self {
+        Int(BigInt::Small(x))
+    }
+
+    /// Applies a binary operation: `small` is tried first when both
+    /// operands fit in an `i128`, and `big` is used when `small`
+    /// overflows.
+    fn binop(
+        self,
+        other: Self,
+        small: impl FnOnce(i128, i128) -> Option<i128>,
+        big: impl FnOnce(num_bigint::BigInt, num_bigint::BigInt) -> num_bigint::BigInt,
+    ) -> Self {
+        if let (Some(x), Some(y)) = (self.0.small(), other.0.small()) {
+            if let Some(z) = small(x, y) {
+                return Self::small(z);
+            }
+        }
+        Self::new(big(self.get(), other.get()))
+    }
 }
 
 impl Add for Int {
     type Output = Self;
 
     fn add(self, other: Self) -> Self::Output {
-        Self::new(self.get() + other.get())
+        self.binop(other, i128::checked_add, |x, y| x + y)
     }
 }
 
@@ -34,7 +59,7 @@ impl Sub for Int {
     type Output = Self;
 
     fn sub(self, other: Self) -> Self::Output {
-        Self::new(self.get() - other.get())
+        self.binop(other, i128::checked_sub, |x, y| x - y)
     }
 }
 
@@ -42,7 +67,7 @@ impl Mul for Int {
     type Output = Self;
 
     fn mul(self, other: Self) -> Self::Output {
-        Self::new(self.get() * other.get())
+        self.binop(other, i128::checked_mul, |x, y| x * y)
     }
 }
 
@@ -50,15 +75,14 @@ impl Div for Int {
     type Output = Self;
 
     fn div(self, other: Self) -> Self::Output {
-        Self::new(self.get() / other.get())
+        self.binop(other, i128::checked_div, |x, y| x / y)
     }
 }
 
 impl Int {
     /// Raises `2` at the power `self`
     pub fn pow2(self) -> Self {
-        let exponent = self.get().to_u32().expect("Exponent doesn't fit in a u32");
-        Self::new(num_bigint::BigInt::from(2u8).pow(exponent))
+        Self::small(2).pow(self)
     }
 
     /// Constructs a `Int` out of a string literal. This function
@@ -66,7 +90,10 @@ impl Int {
     /// optionally a minus sign prefix.
     pub fn _unsafe_from_str(s: &str) -> Self {
         use core::str::FromStr;
-        Self::new(num_bigint::BigInt::from_str(s).unwrap())
+        match i128::from_str(s) {
+            Ok(x) => Self::small(x),
+            Err(_) => Self::new(num_bigint::BigInt::from_str(s).unwrap()),
+        }
     }
 }
 
@@ -109,7 +136,10 @@ macro_rules! implement_abstraction {
         impl Abstraction for $ty {
             type AbstractType = Int;
             fn lift(self) -> Self::AbstractType {
-                Int::new(num_bigint::BigInt::from(self))
+                match i128::try_from(self) {
+                    Ok(x) => Int::small(x),
+                    Err(_) => Int::new(num_bigint::BigInt::from(self)),
+                }
             }
         }
     };
@@ -125,7 +155,10 @@ macro_rules! implement_concretize {
     ($ty:ident $method:ident) => {
         impl Concretization<$ty> for Int {
             fn concretize(self) -> $ty {
-                let concretized = self.get().$method();
+                let concretized = match &self.0 {
+                    BigInt::Small(x) => x.$method(),
+                    large => large.get().$method(),
+                };
                 debug_assert!(concretized.is_some());
                 concretized.unwrap().into()
             }
@@ -157,3 +190,170 @@ implement_concretize!(
     i128  to_i128,
     isize to_isize,
 );
+
+/// Like Rust's `%` on machine integers, the remainder of an `Int`
+/// division has the sign of its left operand. For modular reduction,
+/// see [`Int::rem_euclid`].
+impl Rem for Int {
+    type Output = Self;
+
+    fn rem(self, other: Self) -> Self::Output {
+        self.binop(other, i128::checked_rem, |x, y| x % y)
+    }
+}
+
+impl Neg for Int {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        match self.0.small().and_then(i128::checked_neg) {
+            Some(x) => Self::small(x),
+            None => Self::new(-self.get()),
+        }
+    }
+}
+
+/// Bitwise operations on `Int`s act on their infinite two's
+/// complement representation.
+impl BitAnd for Int {
+    type Output = Self;
+
+    fn bitand(self, other: Self) -> Self::Output {
+        self.binop(other, |x, y| Some(x & y), |x, y| x & y)
+    }
+}
+
+impl BitOr for Int {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self::Output {
+        self.binop(other, |x, y| Some(x | y), |x, y| x | y)
+    }
+}
+
+impl BitXor for Int {
+    type Output = Self;
+
+    fn bitxor(self, other: Self) -> Self::Output {
+        self.binop(other, |x, y| Some(x ^ y), |x, y| x ^ y)
+    }
+}
+
+impl Shl for Int {
+    type Output = Self;
+
+    fn shl(self, other: Self) -> Self::Output {
+        let small = |x: i128, y: i128| {
+            let y = u32::try_from(y).ok()?;
+            let z = x.checked_shl(y)?;
+            (z >> y == x).then_some(z)
+        };
+        self.binop(other, small, |x, _| x << other.to_shift_amount())
+    }
+}
+
+/// Shifting an `Int` to the right rounds towards negative infinity.
+impl Shr for Int {
+    type Output = Self;
+
+    fn shr(self, other: Self) -> Self::Output {
+        let small = |x: i128, y: i128| Some(x >> u32::try_from(y).ok()?.min(127));
+        self.binop(other, small, |x, _| x >> other.to_shift_amount())
+    }
+}
+
+impl Int {
+    fn to_shift_amount(self) -> usize {
+        self.get()
+            .to_usize()
+            .expect("Shift amount is negative or doesn't fit in a usize")
+    }
+
+    /// Raises `self` at the power `exponent`
+    pub fn pow(self, exponent: Self) -> Self {
+        let small = |x: i128, e: i128| x.checked_pow(u32::try_from(e).ok()?);
+        self.binop(exponent, small, |x, e| {
+            x.pow(e.to_u32().expect("Exponent doesn't fit in a u32"))
+        })
+    }
+
+    /// The absolute value of `self`
+    pub fn abs(self) -> Self {
+        if self < 0 {
+            -self
+        } else {
+            self
+        }
+    }
+
+    /// The smallest of `self` and `other`
+    pub fn min(self, other: Self) -> Self {
+        core::cmp::min(self, other)
+    }
+
+    /// The biggest of `self` and `other`
+    pub fn max(self, other: Self) -> Self {
+        core::cmp::max(self, other)
+    }
+
+    /// Reduces `self` modulo `modulus`: the result is always
+    /// non-negative, and smaller than the absolute value of
+    /// `modulus`.
+    pub fn rem_euclid(self, modulus: Self) -> Self {
+        let r = self % modulus;
+        if r < 0 {
+            r + modulus.abs()
+        } else {
+            r
+        }
+    }
+
+    /// The quotient of the euclidean division of `self` by `divisor`,
+    /// whose remainder is [`Int::rem_euclid`].
+    pub fn div_euclid(self, divisor: Self) -> Self {
+        (self - self.rem_euclid(divisor)) / divisor
+    }
+}
+
+impl core::fmt::Display for Int {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl core::fmt::Debug for Int {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// Compares `Int`s with machine integers, so that specifications can
+/// be written `x < 256` instead of `x < 256u16.lift()`.
+macro_rules! implement_mixed_comparisons {
+    ($($ty:ident)*) => {
+        $(
+            impl PartialEq<$ty> for Int {
+                fn eq(&self, other: &$ty) -> bool {
+                    *self == other.lift()
+                }
+            }
+            impl PartialEq<Int> for $ty {
+                fn eq(&self, other: &Int) -> bool {
+                    self.lift() == *other
+                }
+            }
+            impl PartialOrd<$ty> for Int {
+                fn partial_cmp(&self, other: &$ty) -> Option<core::cmp::Ordering> {
+                    self.partial_cmp(&other.lift())
+                }
+            }
+            impl PartialOrd<Int> for $ty {
+                fn partial_cmp(&self, other: &Int) -> Option<core::cmp::Ordering> {
+                    self.lift().partial_cmp(other)
+                }
+            }
+        )*
+    };
+}
+
+implement_mixed_comparisons!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
@@ -15,6 +15,11 @@
 
 #![no_std]
 
+#[cfg(any(hax, feature = "alloc"))]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
 #[cfg(feature = "macros")]
 mod proc_macros;
 
@@ -26,3 +31,59 @@ mod proc_macros;
 core::include!("dummy.rs");
 #[cfg(hax)]
 core::include!("implementation.rs");
+
+#[cfg(any(hax, feature = "alloc"))]
+pub mod collections;
+#[cfg(any(hax, feature = "alloc"))]
+pub use collections::{Map, Seq, Set};
+
+/// Support for the `serde` option of `refinement_type`.
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub mod __serde {
+    pub use serde::{Deserialize, Deserializer};
+
+    /// Deserializes a value of the refinement type `R`, checking its
+    /// invariant.
+    pub fn deserialize_refined<'de, R: crate::Refinement, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<R, D::Error>
+    where
+        R::InnerType: Deserialize<'de> + Clone,
+    {
+        let value = R::InnerType::deserialize(deserializer)?;
+        if R::invariant(value.clone()) {
+            Ok(R::new(value))
+        } else {
+            Err(serde::de::Error::custom(
+                "the value does not satisfy the invariant of the refinement type",
+            ))
+        }
+    }
+}
+
+/// Support for the `arbitrary` option of `refinement_type`.
+#[cfg(feature = "arbitrary")]
+#[doc(hidden)]
+pub mod __arbitrary {
+    pub use arbitrary::{Arbitrary, Error, Result, Unstructured};
+
+    /// How many values of the base type are sampled before giving up
+    /// on finding one that satisfies the invariant.
+    const MAX_ATTEMPTS: usize = 1000;
+
+    /// Samples a value of the refinement type `R`, by rejection of the
+    /// values of its base type that violate its invariant.
+    pub fn arbitrary_refined<'a, R: crate::Refinement>(u: &mut Unstructured<'a>) -> Result<R>
+    where
+        R::InnerType: Arbitrary<'a> + Clone,
+    {
+        for _ in 0..MAX_ATTEMPTS {
+            let value = R::InnerType::arbitrary(u)?;
+            if R::invariant(value.clone()) {
+                return Ok(R::new(value));
+            }
+        }
+        Err(Error::IncorrectFormat)
+    }
+}
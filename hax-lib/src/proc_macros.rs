@@ -2,8 +2,9 @@
 //! proc-macro crate cannot export anything but procedural macros.
 
 pub use hax_lib_macros::{
-    attributes, ensures, exclude, impl_fn_decoration, include, lemma, loop_invariant, opaque,
-    opaque_type, refinement_type, requires, trait_fn_decoration, transparent,
+    attributes, ensures, exclude, ghost, impl_fn_decoration, include, lemma, loop_decreases,
+    loop_invariant, opaque, opaque_type, refinement_type, requires, test_contract,
+    trait_fn_decoration, transparent,
 };
 
 pub use hax_lib_macros::{
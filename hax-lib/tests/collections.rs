@@ -0,0 +1,53 @@
+use hax_lib::{int::Abstraction, Map, Seq, Set};
+
+#[test]
+fn seq() {
+    let s: Seq<u8> = [1, 2, 3].iter().copied().collect();
+    assert_eq!(s, vec![1, 2, 3].lift());
+    assert_eq!((&[1u8, 2, 3]).lift(), s);
+    assert_eq!(s.clone().push(4).slice(1, 4), (&[2u8, 3, 4]).lift());
+    assert_eq!(s.clone().concat(s.clone()).len(), 6);
+    assert_eq!(*s.clone().update(0, 7).at(0), 7);
+    assert!(s.contains(&2) && !s.contains(&4));
+    assert!(Seq::<u8>::new().is_empty());
+    assert!(hax_lib::forall_in(&s, |x| *x > 0));
+}
+
+#[test]
+#[should_panic(expected = "invalid bounds")]
+fn seq_slice_checks_bounds() {
+    Seq::<u8>::new().push(1).slice(1, 2);
+}
+
+#[test]
+fn set() {
+    let s: Set<u8> = Set::new().insert(1).insert(2).insert(2);
+    let t: Set<u8> = [2, 3].into_iter().collect();
+    assert!(s.contains(&1) && !s.contains(&3));
+    assert!(!s.clone().remove(&1).contains(&1));
+    let union = s.clone().union(t.clone());
+    assert!(hax_lib::forall_in(&union, |x| s.contains(x) || t.contains(x)));
+    assert!(hax_lib::forall_in(&[1, 2, 3], |x| union.contains(x)));
+    let intersection = s.intersection(t);
+    assert!(intersection.contains(&2) && !intersection.contains(&1));
+}
+
+#[test]
+fn map() {
+    let m: Map<u8, &str> = Map::new().insert(1, "one").insert(2, "two");
+    assert_eq!(m.get(&1), Some(&"one"));
+    assert_eq!(m.clone().insert(1, "uno").get(&1), Some(&"uno"));
+    assert!(!m.clone().remove(&1).contains_key(&1));
+    assert!(hax_lib::forall_in(&m.keys(), |k| m.contains_key(k)));
+    assert!(m.keys().contains(&2) && !m.keys().contains(&3));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn hash_map() {
+    let mut h = std::collections::HashMap::new();
+    h.insert(1u8, 10u8);
+    let m = (&h).lift();
+    assert_eq!(m.get(&1), Some(&10));
+    assert!(h.lift().keys().contains(&1));
+}
@@ -0,0 +1,18 @@
+use hax_lib::{exists_in, forall_in, with_triggers};
+
+#[test]
+fn bounded_quantifiers() {
+    assert!(forall_in(0..10u8, |i| i < 10));
+    assert!(exists_in(&[1, 2, 3], |x| *x == 2));
+    if cfg!(debug_assertions) {
+        assert!(!forall_in(0..10u8, |i| i < 9));
+        assert!(!exists_in(&[1, 2, 3], |x| *x == 4));
+    }
+}
+
+#[test]
+fn triggers_are_not_evaluated() {
+    let trigger = |_: u8| -> u8 { panic!("a trigger was evaluated") };
+    assert!(forall_in(0..10u8, |i| with_triggers(|| trigger(i), i < 10)));
+    assert!(exists_in(0..10u8, |i| with_triggers(|| trigger(i), i == 3)));
+}
@@ -196,6 +196,22 @@ impl fmt::Display for Backend<()> {
     }
 }
 
+impl<E: Extension> Backend<E> {
+    /// Whether the backend translates `dyn Trait` types, i.e. whether
+    /// the `dyn` feature is enabled in the input language of the
+    /// backend in the engine.
+    pub fn supports_dyn(&self) -> bool {
+        matches!(self, Backend::Fstar(..))
+    }
+
+    /// Whether the backend translates `unsafe` blocks, i.e. whether
+    /// the `unsafe` feature is enabled in the input language of the
+    /// backend in the engine.
+    pub fn supports_unsafe(&self) -> bool {
+        matches!(self, Backend::Fstar(..))
+    }
+}
+
 #[derive_group(Serializers)]
 #[derive(JsonSchema, Debug, Clone)]
 pub enum DepsKind {
@@ -386,7 +402,9 @@ pub enum Command<E: Extension> {
     #[clap(name = "into")]
     Backend(BackendOptions<E>),
 
-    /// Export directly as a JSON file
+    /// Export directly as a JSON file. Items the frontend failed to
+    /// translate are exported as `Error` items: the failures are
+    /// reported, and the command then exits with a non-zero status.
     JSON {
         /// Path to the output JSON file, "-" denotes stdout.
         #[arg(
@@ -395,10 +413,13 @@ pub enum Command<E: Extension> {
             default_value = "hax_frontend_export.json"
         )]
         output_file: PathOrDash,
-        /// Whether the bodies are exported as THIR, built MIR, const
-        /// MIR, or a combination. Repeat this option to extract a
+        /// Whether the bodies are exported as THIR, built MIR,
+        /// promoted MIR, or a combination. Repeat this option to extract a
         /// combination (e.g. `-k thir -k mir-built`). Pass `--kind`
-        /// alone with no value to disable body extraction.
+        /// alone with no value to disable body extraction. Evaluating
+        /// constants steals the MIR bodies they call: when MIR bodies
+        /// are exported, the values of `const` and `static` items are
+        /// not.
         #[arg(
             value_enum,
             short,
@@ -418,6 +439,52 @@ pub enum Command<E: Extension> {
         /// Whether to include extra informations about `DefId`s.
         #[arg(short = 'E', long = "include-extra", default_value = "false")]
         include_extra: bool,
+
+        /// Export monomorphized copies of the functions reachable
+        /// from the non-generic functions matching these patterns
+        /// (e.g. `my_crate::**::main`): in those copies, every type
+        /// is concrete and every trait reference is resolved to an
+        /// implementation, and calls to trait methods are resolved to
+        /// the methods of the implementations. The copies are
+        /// exported as `mono_items`: this implies `--include-extra`.
+        #[arg(
+            long = "mono",
+            value_name = "PATTERN",
+            value_parser,
+            value_delimiter = ','
+        )]
+        mono_entry_points: Vec<Namespace>,
+
+        /// Export the facts computed by the borrow checker (loans,
+        /// region constraints and liveness) along with the MIR
+        /// bodies. The borrow checker runs on promoted MIR: the facts
+        /// are only exported for `mir-promoted` bodies, this implies
+        /// `--kind mir-promoted`.
+        #[arg(long)]
+        borrowck_facts: bool,
+
+        /// Compute the layouts (size, alignment, field offsets) of
+        /// the non-generic structs, enums and unions of the crate.
+        /// Their definitions are exported as `adt_defs`, with their
+        /// layouts: this implies `--include-extra`.
+        #[arg(long)]
+        layouts: bool,
+
+        /// Instead of the items, output the call graph of the crate:
+        /// the caller → callee edges found in the exported bodies,
+        /// with trait method calls resolved to concrete
+        /// implementations when possible.
+        #[arg(long = "call-graph", value_enum, value_name = "FORMAT")]
+        call_graph: Option<CallGraphFormat>,
+    },
+
+    /// Report the Rust constructs hax cannot translate (placeholders
+    /// left by the frontend, unsafe code, raw pointers, `dyn`,
+    /// coroutines, inline assembly), without running any backend.
+    Lint {
+        /// Only report the constructs unsupported by this backend.
+        #[command(subcommand)]
+        backend: Option<Backend<E>>,
     },
 
     #[command(flatten)]
@@ -427,10 +494,43 @@ pub enum Command<E: Extension> {
 impl<E: Extension> Command<E> {
     pub fn body_kinds(&self) -> Vec<ExportBodyKind> {
         match self {
-            Command::JSON { kind, .. } => kind.clone(),
+            Command::JSON {
+                kind,
+                borrowck_facts,
+                ..
+            } => {
+                let mut kind = kind.clone();
+                if *borrowck_facts && !kind.contains(&ExportBodyKind::MirPromoted) {
+                    kind.push(ExportBodyKind::MirPromoted)
+                }
+                kind
+            }
             _ => vec![ExportBodyKind::Thir],
         }
     }
+
+    pub fn mono_entry_points(&self) -> Vec<Namespace> {
+        match self {
+            Command::JSON {
+                mono_entry_points, ..
+            } => mono_entry_points.clone(),
+            _ => vec![],
+        }
+    }
+
+    pub fn borrowck_facts(&self) -> bool {
+        match self {
+            Command::JSON { borrowck_facts, .. } => *borrowck_facts,
+            _ => false,
+        }
+    }
+
+    pub fn layouts(&self) -> bool {
+        match self {
+            Command::JSON { layouts, .. } => *layouts,
+            _ => false,
+        }
+    }
 }
 
 #[derive_group(Serializers)]
@@ -438,6 +538,17 @@ impl<E: Extension> Command<E> {
 pub enum ExportBodyKind {
     Thir,
     MirBuilt,
+    MirPromoted,
+}
+
+/// The output formats of `cargo hax json --call-graph`.
+#[derive_group(Serializers)]
+#[derive(JsonSchema, ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallGraphFormat {
+    /// A list of edges, as JSON.
+    Json,
+    /// A Graphviz graph.
+    Dot,
 }
 
 #[derive_group(Serializers)]
@@ -536,6 +647,8 @@ impl From<Options> for hax_frontend_exporter_options::Options {
     fn from(opts: Options) -> hax_frontend_exporter_options::Options {
         hax_frontend_exporter_options::Options {
             inline_macro_calls: opts.inline_macro_calls,
+            borrowck_facts: opts.command.borrowck_facts(),
+            layouts: opts.command.layouts(),
         }
     }
 }
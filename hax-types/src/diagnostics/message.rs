@@ -27,6 +27,21 @@ pub enum HaxMessage {
     Stats {
         errors_per_item: Vec<(hax_frontend_exporter::DefId, usize)>,
     } = 6,
+    /// The `haxmeta` file of the crate could not be read.
+    HaxMetaReadFailure {
+        path: PathBuf,
+        reason: String,
+    } = 7,
+    /// The `haxmeta` file of a dependency could not be read: the
+    /// items of that dependency are not given to the engine.
+    DependencyHaxMetaReadFailure {
+        path: PathBuf,
+        reason: String,
+    } = 8,
+    /// The output of `cargo hax json` could not be written.
+    OutputWriteFailure {
+        reason: String,
+    } = 9,
 }
 
 impl HaxMessage {
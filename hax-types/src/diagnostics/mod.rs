@@ -68,6 +68,16 @@ impl std::fmt::Display for Diagnostics {
 
             Kind::NonTrivialAndMutFnInput => write!(f, "The support in hax of function with one or more inputs of type `&mut _` is limited. Onlu trivial patterns are allowed there: `fn f(x: &mut (T, U)) ...` is allowed while `f((x, y): &mut (T, U))` is rejected."),
 
+            Kind::FrontendPlaceholder {node, details} => write!(f, "The frontend could not translate this {node}, a placeholder was exported instead. Any backend will fail on it.\nDetails: {details}"),
+
+            Kind::RawPointer => write!(f, "Raw pointers are not supported."),
+
+            Kind::DynTrait => write!(f, "{} types are not supported by this backend.", "dyn Trait".bold()),
+
+            Kind::Coroutine => write!(f, "Coroutines ({} code, generators) are not supported.", "async".bold()),
+
+            Kind::InlineAsm => write!(f, "Inline assembly is not supported."),
+
             _ => write!(f, "{:?}", self.kind),
         }
     }
@@ -135,7 +145,26 @@ pub enum Kind {
     /// An hax attribute (from `hax-lib-macros`) was rejected
     AttributeRejected {
         reason: String,
-    },
+    } = 12,
+
+    /// The frontend could not translate a node and exported a
+    /// placeholder instead
+    FrontendPlaceholder {
+        node: String,
+        details: String,
+    } = 13,
+
+    /// Raw pointers are not supported
+    RawPointer = 14,
+
+    /// `dyn Trait` types are not supported by this backend
+    DynTrait = 15,
+
+    /// Coroutines (`async` code, generators) are not supported
+    Coroutine = 16,
+
+    /// Inline assembly is not supported
+    InlineAsm = 17,
 }
 
 impl Kind {
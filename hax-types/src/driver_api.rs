@@ -28,28 +28,284 @@ pub struct HaxMeta<Body: hax_frontend_exporter::IsBody> {
     )>,
     pub def_ids: Vec<hax_frontend_exporter::DefId>,
     pub comments: Vec<(hax_frontend_exporter::Span, String)>,
+    /// The comments of `comments`, attached to items.
+    pub item_comments: Vec<(
+        hax_frontend_exporter::DefId,
+        Vec<hax_frontend_exporter::AttachedComment>,
+    )>,
+    /// Monomorphized copies of functions, see `--mono`.
+    pub mono_items: Vec<hax_frontend_exporter::MonoItem<Body>>,
+    /// The ADTs of the crate with their layouts, see `--layouts`.
+    pub adt_defs: Vec<hax_frontend_exporter::AdtDef>,
+    /// Errors that occurred while exporting `items`.
+    pub diagnostics: Vec<crate::diagnostics::Diagnostics>,
+}
+
+/// The first chunk of a `haxmeta` file.
+#[derive_group(Serializers)]
+#[derive(Debug, Clone)]
+pub struct HaxMetaHeader {
+    pub crate_name: String,
+    pub cg_metadata: String,
+    pub externs: Vec<PathBuf>,
+}
+
+/// The last chunk of a `haxmeta` file: everything but the items that
+/// can be computed only once every item was exported.
+#[derive_group(Serializers)]
+#[derive(Debug, Clone)]
+pub struct HaxMetaFooter<Body: hax_frontend_exporter::IsBody> {
+    pub impl_infos: Vec<(
+        hax_frontend_exporter::DefId,
+        hax_frontend_exporter::ImplInfos,
+    )>,
+    pub def_ids: Vec<hax_frontend_exporter::DefId>,
+    pub comments: Vec<(hax_frontend_exporter::Span, String)>,
+    pub item_comments: Vec<(
+        hax_frontend_exporter::DefId,
+        Vec<hax_frontend_exporter::AttachedComment>,
+    )>,
+    pub mono_items: Vec<hax_frontend_exporter::MonoItem<Body>>,
+    pub adt_defs: Vec<hax_frontend_exporter::AdtDef>,
+    pub diagnostics: Vec<crate::diagnostics::Diagnostics>,
+}
+
+/// A chunk of a `haxmeta` file, after its header.
+#[derive_group(Serializers)]
+#[derive(Debug, Clone)]
+pub enum HaxMetaChunk<Body: hax_frontend_exporter::IsBody> {
+    Item(hax_frontend_exporter::Item<Body>),
+    Footer(HaxMetaFooter<Body>),
 }
 
 use hax_frontend_exporter::id_table;
 
-impl<Body: hax_frontend_exporter::IsBody> HaxMeta<Body>
+/// An error that occurred while reading a `haxmeta` file.
+#[derive(Debug)]
+pub enum HaxMetaReadError {
+    Io(std::io::Error),
+    /// The file was written by another version of hax.
+    Version {
+        found: String,
+    },
+    /// The file is not a valid `haxmeta` file.
+    Decode(serde_brief::Error),
+}
+
+impl std::fmt::Display for HaxMetaReadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Version { found } => write!(
+                f,
+                "the file was produced by hax {found}, but this is hax {}",
+                crate::HAX_VERSION
+            ),
+            Self::Decode(e) => write!(f, "invalid haxmeta file: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for HaxMetaReadError {}
+
+impl From<std::io::Error> for HaxMetaReadError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<serde_brief::Error> for HaxMetaReadError {
+    fn from(e: serde_brief::Error) -> Self {
+        Self::Decode(e)
+    }
+}
+
+/// Writes one chunk of a length-prefixed chunk stream.
+fn write_chunk(write: &mut impl std::io::Write, value: &impl serde::Serialize) {
+    let bytes = serde_brief::to_vec(value).unwrap();
+    write
+        .write_all(&(bytes.len() as u64).to_le_bytes())
+        .unwrap();
+    write.write_all(&bytes).unwrap();
+}
+
+/// Reads one chunk of a length-prefixed chunk stream.
+fn read_chunk<T: serde::de::DeserializeOwned>(
+    read: &mut impl std::io::Read,
+) -> Result<T, HaxMetaReadError> {
+    let mut len = [0; 8];
+    read.read_exact(&mut len)?;
+    Ok(serde_brief::from_reader(std::io::Read::take(
+        read,
+        u64::from_le_bytes(len),
+    ))?)
+}
+
+/// Writes a `haxmeta` file chunk by chunk, so that items can be
+/// written as they are exported. A `haxmeta` file is a
+/// zstd-compressed stream of length-prefixed chunks: the version of
+/// hax that wrote it, a [`HaxMetaHeader`], then one
+/// [`HaxMetaChunk::Item`] chunk per item, then one
+/// [`HaxMetaChunk::Footer`] chunk. The chunks after the header are
+/// [`id_table::Chunk`]s sharing the ID table of the export.
+///
+/// Items are written with default fingerprints: fingerprints depend
+/// on every item, readers compute them (see [`HaxMeta::read`]).
+pub struct HaxMetaWriter<W: std::io::Write, Body> {
+    encoder: zstd::stream::write::Encoder<'static, W>,
+    _body: std::marker::PhantomData<Body>,
+}
+
+impl<W: std::io::Write, Body: hax_frontend_exporter::IsBody + serde::Serialize>
+    HaxMetaWriter<W, Body>
+{
+    pub fn new(write: W, header: &HaxMetaHeader) -> Self {
+        let mut encoder = zstd::stream::write::Encoder::new(write, 0).unwrap();
+        write_chunk(&mut encoder, &crate::HAX_VERSION);
+        write_chunk(&mut encoder, header);
+        Self {
+            encoder,
+            _body: std::marker::PhantomData,
+        }
+    }
+
+    /// Writes a chunk. `entries` are the entries of the ID table
+    /// created since the previous chunk, see
+    /// [`id_table::Session::new_entries`].
+    #[tracing::instrument(level = "trace", skip_all)]
+    fn write(&mut self, chunk: HaxMetaChunk<Body>, entries: id_table::Table) {
+        id_table::WithTable::run(entries, chunk, |with_table| {
+            write_chunk(&mut self.encoder, with_table)
+        })
+    }
+
+    pub fn item(&mut self, item: hax_frontend_exporter::Item<Body>, entries: id_table::Table) {
+        self.write(HaxMetaChunk::Item(item), entries)
+    }
+
+    /// Writes the footer, which ends the file.
+    pub fn finish(mut self, footer: HaxMetaFooter<Body>, entries: id_table::Table) -> W {
+        self.write(HaxMetaChunk::Footer(footer), entries);
+        self.encoder.finish().unwrap()
+    }
+}
+
+/// Reads a `haxmeta` file chunk by chunk, see [`HaxMetaWriter`].
+pub struct HaxMetaReader<R: std::io::Read, Body> {
+    decoder: std::io::BufReader<zstd::stream::read::Decoder<'static, std::io::BufReader<R>>>,
+    table: id_table::Table,
+    pub header: HaxMetaHeader,
+    _body: std::marker::PhantomData<Body>,
+}
+
+impl<R: std::io::Read, Body: hax_frontend_exporter::IsBody> HaxMetaReader<R, Body>
 where
-    Body: serde::Serialize + for<'de> serde::Deserialize<'de>,
+    Body: for<'de> serde::Deserialize<'de>,
 {
-    #[tracing::instrument(level = "trace", skip(self, write, id_table))]
-    pub fn write(self, write: &mut impl std::io::Write, id_table: id_table::Table) {
-        let mut write = zstd::stream::write::Encoder::new(write, 0).unwrap();
+    pub fn new(read: R) -> Result<Self, HaxMetaReadError> {
+        let decoder = zstd::stream::read::Decoder::new(read)?;
+        let mut decoder = std::io::BufReader::new(decoder);
+        let version: String = read_chunk(&mut decoder)?;
+        if version != crate::HAX_VERSION {
+            return Err(HaxMetaReadError::Version { found: version });
+        }
+        let header = read_chunk(&mut decoder)?;
+        Ok(Self {
+            decoder,
+            table: id_table::Table::default(),
+            header,
+            _body: std::marker::PhantomData,
+        })
+    }
+
+    /// Reads the next chunk, together with the entries of the ID
+    /// table it introduces. The footer is the last chunk.
+    #[tracing::instrument(level = "trace", skip_all)]
+    pub fn next_chunk(
+        &mut self,
+    ) -> Result<(HaxMetaChunk<Body>, id_table::Table), HaxMetaReadError> {
+        let decoder = &mut self.decoder;
+        self.table.read_chunk(|| read_chunk(decoder))
+    }
 
-        id_table::WithTable::run(id_table, self, |with_table| {
-            serde_brief::to_writer(with_table, &mut write).unwrap();
-            write.finish().unwrap();
+    /// The ID table of the chunks read so far.
+    pub fn table(&self) -> &id_table::Table {
+        &self.table
+    }
+
+    /// The remaining items, up to the footer. The iterator ends after
+    /// the first error.
+    pub fn items(
+        mut self,
+    ) -> impl Iterator<Item = Result<hax_frontend_exporter::Item<Body>, HaxMetaReadError>> {
+        let mut done = false;
+        std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+            match self.next_chunk() {
+                Ok((HaxMetaChunk::Item(item), _)) => Some(Ok(item)),
+                Ok((HaxMetaChunk::Footer(_), _)) => {
+                    done = true;
+                    None
+                }
+                Err(error) => {
+                    done = true;
+                    Some(Err(error))
+                }
+            }
         })
     }
+
+    /// Reads the remaining chunks, calling `f` on each item, and
+    /// returns the footer together with the ID table of the whole
+    /// file. Items are dropped once `f` returns.
+    pub fn for_each_item(
+        mut self,
+        mut f: impl FnMut(hax_frontend_exporter::Item<Body>),
+    ) -> Result<(HaxMetaFooter<Body>, id_table::Table), HaxMetaReadError> {
+        loop {
+            match self.next_chunk()?.0 {
+                HaxMetaChunk::Item(item) => f(item),
+                HaxMetaChunk::Footer(footer) => return Ok((footer, self.table)),
+            }
+        }
+    }
+}
+
+impl<Body: hax_frontend_exporter::IsBody> HaxMeta<Body>
+where
+    Body: hax_frontend_exporter::visitors::Visitable
+        + serde::Serialize
+        + for<'de> serde::Deserialize<'de>,
+{
+    /// Reads a whole `haxmeta` file and computes the fingerprints of
+    /// its items, see [`HaxMetaReader`] for reading it chunk by chunk.
     #[tracing::instrument(level = "trace", skip(reader))]
-    pub fn read(reader: impl std::io::Read) -> (Self, id_table::Table) {
-        let reader = zstd::stream::read::Decoder::new(reader).unwrap();
-        let reader = std::io::BufReader::new(reader);
-        id_table::WithTable::destruct(serde_brief::from_reader(reader).unwrap())
+    pub fn read(reader: impl std::io::Read) -> Result<(Self, id_table::Table), HaxMetaReadError> {
+        let reader = HaxMetaReader::new(reader)?;
+        let HaxMetaHeader {
+            crate_name,
+            cg_metadata,
+            externs,
+        } = reader.header.clone();
+        let mut items = vec![];
+        let (footer, table) = reader.for_each_item(|item| items.push(item))?;
+        hax_frontend_exporter::fingerprint_items(&mut items);
+        let haxmeta = HaxMeta {
+            crate_name,
+            cg_metadata,
+            externs,
+            items,
+            impl_infos: footer.impl_infos,
+            def_ids: footer.def_ids,
+            comments: footer.comments,
+            item_comments: footer.item_comments,
+            mono_items: footer.mono_items,
+            adt_defs: footer.adt_defs,
+            diagnostics: footer.diagnostics,
+        };
+        Ok((haxmeta, table))
     }
 }
 
@@ -57,11 +313,15 @@ where
 macro_rules! with_kind_type {
     ($kind:expr, <$t:ident>|| $body:expr) => {{
         mod from {
-            pub use hax_types::cli_options::ExportBodyKind::{MirBuilt as MB, Thir as T};
+            pub use hax_types::cli_options::ExportBodyKind::{
+                MirBuilt as MB, MirPromoted as MP, Thir as T,
+            };
         }
         mod to {
             pub type T = hax_frontend_exporter::ThirBody;
             pub type MB = hax_frontend_exporter::MirBody<hax_frontend_exporter::mir_kinds::Built>;
+            pub type MP =
+                hax_frontend_exporter::MirBody<hax_frontend_exporter::mir_kinds::Promoted>;
         }
         let mut kind: Vec<::hax_types::cli_options::ExportBodyKind> = $kind;
         kind.sort();
@@ -71,6 +331,10 @@ macro_rules! with_kind_type {
                 type $t = to::MB;
                 $body
             }
+            [from::MP] => {
+                type $t = to::MP;
+                $body
+            }
             [from::T] => {
                 type $t = to::T;
                 $body
@@ -79,6 +343,10 @@ macro_rules! with_kind_type {
                 type $t = (to::MB, to::T);
                 $body
             }
+            [from::T, from::MP] => {
+                type $t = (to::MP, to::T);
+                $body
+            }
             [] => {
                 type $t = ();
                 $body
@@ -88,3 +356,54 @@ macro_rules! with_kind_type {
     }};
 }
 pub use with_kind_type;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hax_frontend_exporter::ThirBody;
+
+    fn write_empty_haxmeta() -> Vec<u8> {
+        let header = HaxMetaHeader {
+            crate_name: "krate".into(),
+            cg_metadata: "".into(),
+            externs: vec![],
+        };
+        let footer = HaxMetaFooter {
+            impl_infos: vec![],
+            def_ids: vec![],
+            comments: vec![],
+            item_comments: vec![],
+            mono_items: vec![],
+            adt_defs: vec![],
+            diagnostics: vec![],
+        };
+        HaxMetaWriter::<_, ThirBody>::new(vec![], &header)
+            .finish(footer, id_table::Table::default())
+    }
+
+    #[test]
+    fn read_written_haxmeta() {
+        let bytes = write_empty_haxmeta();
+        let (haxmeta, _) = HaxMeta::<ThirBody>::read(bytes.as_slice()).unwrap();
+        assert_eq!(haxmeta.crate_name, "krate");
+        assert!(haxmeta.items.is_empty());
+    }
+
+    #[test]
+    fn truncated_haxmeta_is_an_error() {
+        let bytes = write_empty_haxmeta();
+        let bytes = &bytes[..bytes.len() / 2];
+        assert!(HaxMeta::<ThirBody>::read(bytes).is_err());
+    }
+
+    #[test]
+    fn haxmeta_of_another_version_is_an_error() {
+        let mut encoder = zstd::stream::write::Encoder::new(vec![], 0).unwrap();
+        write_chunk(&mut encoder, &"0.0.0-other");
+        let bytes = encoder.finish().unwrap();
+        assert!(matches!(
+            HaxMeta::<ThirBody>::read(bytes.as_slice()),
+            Err(HaxMetaReadError::Version { found }) if found == "0.0.0-other"
+        ));
+    }
+}
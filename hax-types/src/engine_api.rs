@@ -1,19 +1,42 @@
 use crate::cli_options::*;
 use crate::prelude::*;
 
+use hax_frontend_exporter::id_table;
+
 type ThirBody = hax_frontend_exporter::ThirBody;
 
 #[derive_group(Serializers)]
 #[derive(JsonSchema, Debug, Clone)]
 pub struct EngineOptions {
     pub backend: BackendOptions<()>,
-    pub input: Vec<hax_frontend_exporter::Item<ThirBody>>,
     pub impl_infos: Vec<(
         hax_frontend_exporter::DefId,
         hax_frontend_exporter::ImplInfos,
     )>,
 }
 
+/// The engine reads its input as a stream of chunks (see
+/// [`id_table::Chunk`]), so that items can be sent as they are read,
+/// and imported by the engine as they are received: one `Options`
+/// chunk, then any number of `Items` and `DependencyItems` chunks,
+/// then one `End` chunk. The engine asks for each chunk with
+/// [`protocol::FromEngine::RequestInput`].
+#[derive_group(Serializers)]
+#[derive(JsonSchema, Debug, Clone)]
+pub enum EngineInput {
+    /// The options of the engine, which start the input.
+    Options(EngineOptions),
+    /// Items of the crate being translated.
+    Items(Vec<hax_frontend_exporter::Item<ThirBody>>),
+    /// Items of the dependencies of the crate being translated. Those
+    /// are not translated: they are provided as signature-only
+    /// context, so that e.g. contracts flow across crates. Their
+    /// nodes are inlined, since they come from other ID tables.
+    DependencyItems(id_table::Inlined<Vec<hax_frontend_exporter::Item<ThirBody>>>),
+    /// The end of the input.
+    End,
+}
+
 #[derive_group(Serializers)]
 #[allow(non_snake_case)]
 #[derive(JsonSchema, Debug, Clone)]
@@ -91,6 +114,8 @@ pub mod protocol {
         ProfilingData(ProfilingData),
         /// Declares a list of items that will be processed by the engine
         ItemProcessed(Vec<hax_frontend_exporter::DefId>),
+        /// Asks for the next chunk of input (see [`EngineInput`])
+        RequestInput,
         Exit,
         Ping,
     }
@@ -107,12 +132,22 @@ pub mod protocol {
 // to the engine (yet?).
 #[derive_group(Serializers)]
 #[derive(JsonSchema, Debug, Clone)]
-pub struct WithDefIds<Body: hax_frontend_exporter::IsBody> {
+pub struct WithDefIds<
+    Body: hax_frontend_exporter::IsBody,
+    Items = Vec<hax_frontend_exporter::Item<Body>>,
+> {
     pub def_ids: Vec<hax_frontend_exporter::DefId>,
     pub impl_infos: Vec<(
         hax_frontend_exporter::DefId,
         hax_frontend_exporter::ImplInfos,
     )>,
-    pub items: Vec<hax_frontend_exporter::Item<Body>>,
+    /// The items, or a value serializing as a sequence of items.
+    pub items: Items,
     pub comments: Vec<(hax_frontend_exporter::Span, String)>,
+    pub item_comments: Vec<(
+        hax_frontend_exporter::DefId,
+        Vec<hax_frontend_exporter::AttachedComment>,
+    )>,
+    pub mono_items: Vec<hax_frontend_exporter::MonoItem<Body>>,
+    pub adt_defs: Vec<hax_frontend_exporter::AdtDef>,
 }
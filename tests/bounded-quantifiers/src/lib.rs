@@ -0,0 +1,20 @@
+#[hax_lib::requires(hax_lib::forall_in(0..n, |i| i < 10))]
+pub fn range(n: u8) -> u8 {
+    n
+}
+
+#[hax_lib::requires(hax_lib::forall_in(s, |x| *x > 0) && hax_lib::exists_in(s, |x| *x == 1))]
+pub fn slice(s: &[u32]) -> usize {
+    s.len()
+}
+
+#[hax_lib::requires(hax_lib::exists_in(a, |x| *x == 0))]
+pub fn array(a: &[u32; 4]) -> u32 {
+    a[0]
+}
+
+/// `D::Element` is the type of the bound variable of `forall_in`.
+#[hax_lib::requires(hax_lib::forall_in(d, |x| is_valid(x)))]
+pub fn generic<D: hax_lib::Domain>(d: D, is_valid: impl Fn(D::Element) -> bool) -> bool {
+    d.into_iter().all(is_valid)
+}
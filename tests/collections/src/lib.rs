@@ -0,0 +1,36 @@
+use hax_lib::int::Abstraction;
+use hax_lib::{Map, Seq, Set};
+
+#[hax_lib::ensures(|result| result.lift() == v.lift().push(x))]
+pub fn push(mut v: Vec<u8>, x: u8) -> Vec<u8> {
+    v.push(x);
+    v
+}
+
+#[hax_lib::requires(s.len() > 0)]
+#[hax_lib::ensures(|result| s.lift().contains(&result))]
+pub fn first(s: &[u8]) -> u8 {
+    s[0]
+}
+
+pub fn lift(v: &Vec<u8>) -> Seq<u8> {
+    v.lift()
+}
+
+pub fn seqs(s: Seq<u8>, t: Seq<u8>) -> bool {
+    s.clone().concat(t).len() >= s.len() && s.slice(0, 0).is_empty()
+}
+
+pub fn sets(s: Set<u8>, x: u8) -> bool {
+    let s = s.insert(x);
+    s.contains(&x) && !s.clone().remove(&x).contains(&x) && hax_lib::exists_in(&s, |y| *y == x)
+}
+
+pub fn maps(m: Map<u8, u16>, key: u8) -> Option<u16> {
+    let m = m.insert(key, 0);
+    if m.keys().contains(&key) {
+        m.get(&key).copied()
+    } else {
+        None
+    }
+}
@@ -0,0 +1,13 @@
+#![allow(dead_code)]
+
+#[hax_lib::requires(x < 255)]
+pub fn incr(x: u8) -> u8 {
+    x + 1
+}
+
+#[hax_lib::attributes]
+pub trait Counter {
+    #[hax_lib::requires(self.value() < 255)]
+    fn bump(&mut self);
+    fn value(&self) -> u8;
+}
@@ -0,0 +1,22 @@
+#![allow(dead_code)]
+//! Uses the contracts of the items of another crate of the workspace:
+//! the items of `cross-crate-dependency` are only given as context to
+//! the engine, and are not extracted with this crate.
+
+use cross_crate_dependency::{incr, Counter};
+
+#[hax_lib::requires(x < 254)]
+fn incr_twice(x: u8) -> u8 {
+    incr(incr(x))
+}
+
+struct Saturating(u8);
+
+impl Counter for Saturating {
+    fn bump(&mut self) {
+        self.0 = incr(self.0)
+    }
+    fn value(&self) -> u8 {
+        self.0
+    }
+}
@@ -0,0 +1,33 @@
+#[hax_lib::ghost]
+fn spec_sum(xs: &[u32]) -> u64 {
+    xs.iter().map(|x| *x as u64).sum()
+}
+
+#[hax_lib::attributes]
+#[hax_lib::ensures(|result| result == spec_sum(xs))]
+pub fn sum(xs: &[u32]) -> u64 {
+    #[ghost]
+    let mut steps: usize = 0;
+    let mut sum = 0;
+    for x in xs {
+        sum += *x as u64;
+        #[ghost]
+        steps += 1;
+    }
+    #[ghost]
+    hax_lib::assert!(steps == xs.len());
+    sum
+}
+
+#[hax_lib::attributes]
+pub fn shadowing(xs: &[u32]) -> usize {
+    #[ghost]
+    let len = xs.len();
+    let doubled = {
+        let len = 2 * xs.len();
+        len
+    };
+    #[ghost]
+    hax_lib::assert!(doubled == 2 * len);
+    doubled
+}
@@ -138,6 +138,48 @@ mod while_loops {
         }
         x + 12
     }
+    fn while_invariant(n: u32) -> u32 {
+        let mut i = 0;
+        while i < n {
+            hax_lib::loop_invariant!(i <= n);
+            hax_lib::loop_decreases!(n.lift() - i.lift());
+            i += 1;
+        }
+        i
+    }
+    fn while_decreases_only(mut x: u64) -> u64 {
+        while x > 0 {
+            hax_lib::loop_decreases!(x.lift());
+            x /= 2;
+        }
+        x
+    }
+    fn while_invariant_break(v: &[u8], needle: u8) -> usize {
+        let mut i = 0;
+        while i < v.len() {
+            hax_lib::loop_invariant!(i <= v.len());
+            hax_lib::loop_decreases!(v.len().lift() - i.lift());
+            if v[i] == needle {
+                break;
+            }
+            i += 1;
+        }
+        i
+    }
+    fn loop_invariant_return(v: &[u8], needle: u8) -> Option<usize> {
+        let mut i = 0;
+        loop {
+            hax_lib::loop_invariant!(i <= v.len());
+            hax_lib::loop_decreases!(v.len().lift() - i.lift());
+            if i == v.len() {
+                return None;
+            }
+            if v[i] == needle {
+                return Some(i);
+            }
+            i += 1;
+        }
+    }
 }
 
 mod control_flow {
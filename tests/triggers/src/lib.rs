@@ -0,0 +1,45 @@
+use hax_lib::*;
+
+fn double(n: u64) -> u64 {
+    n.wrapping_mul(2)
+}
+
+fn f(x: u8, y: u8) -> bool {
+    x <= y
+}
+
+/// A single trigger term: `{:pattern double i}`.
+#[requires(forall(|i: u64| with_triggers(|| double(i), double(i) % 2 == 0)))]
+pub fn single_trigger(x: u64) -> u64 {
+    double(x)
+}
+
+/// A tuple of trigger terms, which must all match.
+#[requires(forall(|x: u8, y: u8| with_triggers(|| (f(x, y), f(y, x)), f(x, y) || f(y, x))))]
+pub fn several_triggers(x: u8, y: u8) -> bool {
+    f(x, y) || f(y, x)
+}
+
+#[requires(exists(|i: u64| with_triggers(|| double(i), double(i) == x)))]
+pub fn exists_trigger(x: u64) -> u64 {
+    x / 2
+}
+
+/// Bounded quantifiers with triggers.
+#[requires(forall_in(s, |v| with_triggers(|| f(*v, 10), *v < 10)))]
+pub fn forall_in_trigger(s: &[u8]) -> usize {
+    s.len()
+}
+
+#[requires(exists_in(0..n, |i| with_triggers(|| double(i), double(i) == 4)))]
+pub fn exists_in_trigger(n: u64) -> u64 {
+    n - 1
+}
+
+/// `[SMTPat (double n)]`
+#[lemma(smt_pat(double(n)))]
+pub fn double_is_even(n: u64) -> Proof<{ double(n) % 2 == 0 }> {}
+
+/// `[SMTPat (f x y); SMTPat (f y x)]`
+#[lemma(smt_pat(f(x, y), f(y, x)))]
+pub fn f_total(x: u8, y: u8) -> Proof<{ f(x, y) || f(y, x) }> {}